@@ -0,0 +1,23 @@
+#!/usr/bin/env bash
+# Fixture for `smart-code-chunker conformance`, the chunks it must produce are listed
+# in bash.expected.yaml
+set -euo pipefail
+
+LOG_DIR="${LOG_DIR:-/var/log/deploy}"
+
+# Prints a timestamped message.
+log() {
+  echo "[$(date +%T)] $*" >&2
+}
+
+function deploy {
+  local target="$1"
+  log "deploying to ${target}"
+  rsync -a build/ "${target}:/srv/app/"
+}
+
+function rollback() {
+  log "rolling back"
+}
+
+deploy "${1:-staging}" || rollback
@@ -0,0 +1,8 @@
+# Fixture for `smart-code-chunker conformance`, the chunks it must produce are listed
+# in bash_commands.expected.yaml
+apt-get update
+apt-get install -y curl jq
+
+for user in alice bob; do
+  useradd --create-home "$user"
+done
@@ -0,0 +1,30 @@
+// Fixture for `smart-code-chunker conformance`, the chunks it must produce are listed
+// in c.expected.yaml
+#ifndef FIXTURE_H
+#define FIXTURE_H
+
+#include <stddef.h>
+
+/* A point in the plane */
+typedef struct {
+    double x;
+    double y;
+} Point;
+
+struct list {
+    struct list *next;
+    int value;
+};
+
+enum color { RED, GREEN };
+
+extern int counter;
+size_t list_length(const struct list *head);
+void (*handler_for(int signal))(int);
+
+// Header-only helper
+static inline int square(int value) {
+    return value * value;
+}
+
+#endif
@@ -0,0 +1,27 @@
+// Fixture for `smart-code-chunker conformance`, the chunks it must produce are listed
+// in c_preprocessor.expected.yaml
+#include <stdlib.h>
+#include <string.h>
+
+typedef int (*compare_fn)(const void *, const void *);
+
+union value {
+    int i;
+    double d;
+};
+
+/* Copies a string, the caller frees it */
+char *duplicate(const char *text) {
+    char *copy = malloc(strlen(text) + 1);
+    return strcpy(copy, text);
+}
+
+#ifdef _WIN32
+static int platform_init(void) {
+    return 1;
+}
+#else
+static int platform_init(void) {
+    return 0;
+}
+#endif
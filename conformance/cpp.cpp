@@ -0,0 +1,52 @@
+// Fixture for `smart-code-chunker conformance`, the chunks it must produce are listed
+// in cpp.expected.yaml
+#include <string>
+
+namespace app {
+
+class UserService {
+public:
+    explicit UserService(int limit) : limit_(limit) {}
+    ~UserService() {}
+
+    bool operator==(const UserService &other) const { return limit_ == other.limit_; }
+
+    std::string createUser(const std::string &name);
+
+private:
+    int limit_;
+};
+
+// Defined out of line
+std::string UserService::createUser(const std::string &name) {
+    return name;
+}
+
+template <typename T>
+T largest(T a, T b) {
+    return a > b ? a : b;
+}
+
+template <typename T>
+struct Box {
+    T value;
+};
+
+int helper(int value);
+
+}  // namespace app
+
+namespace {
+int instances = 0;
+}
+
+namespace app::detail {
+union Storage {
+    int i;
+    float f;
+};
+}
+
+extern "C" {
+int c_entry(void);
+}
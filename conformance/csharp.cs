@@ -0,0 +1,41 @@
+// Fixture for `smart-code-chunker conformance`, the chunks it must produce are listed
+// in csharp.expected.yaml
+using System;
+
+namespace MyApp.Services;
+
+/// <summary>Looks up users.</summary>
+public class UserService
+{
+    private readonly int _limit;
+
+    public UserService(int limit)
+    {
+        _limit = limit;
+    }
+
+    public int Limit => _limit;
+
+    public string Name { get; set; }
+
+    [Obsolete("Use GetUserAsync")]
+    public User GetUser(int id)
+    {
+        return new User(id);
+    }
+
+    public T Find<T>(
+        int id,
+        bool cached)
+        where T : class
+    {
+        return null;
+    }
+}
+
+public record User(int Id);
+
+public interface IRepository
+{
+    void Save(User user);
+}
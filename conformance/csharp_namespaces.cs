@@ -0,0 +1,14 @@
+// Fixture for `smart-code-chunker conformance`, the chunks it must produce are listed
+// in csharp_namespaces.expected.yaml
+namespace MyApp
+{
+    namespace Geometry
+    {
+        public struct Point
+        {
+            public double Length() => 0;
+        }
+    }
+
+    public enum Color { Red, Green }
+}
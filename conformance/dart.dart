@@ -0,0 +1,54 @@
+import 'package:flutter/material.dart';
+
+/// Entry point
+void main() {
+  runApp(const CounterApp());
+}
+
+int add(int a, int b) => a + b;
+
+class CounterApp extends StatefulWidget {
+  const CounterApp({super.key});
+
+  @override
+  State<CounterApp> createState() => _CounterAppState();
+}
+
+class _CounterAppState extends State<CounterApp> {
+  int _count = 0;
+
+  void _increment() {
+    setState(() => _count++);
+  }
+
+  // Rebuilt on every setState
+  @override
+  Widget build(BuildContext context) {
+    return Text('$_count');
+  }
+}
+
+class User {
+  final String name;
+
+  User(this.name);
+  User.guest() : name = 'guest';
+
+  factory User.fromJson(Map<String, dynamic> json) {
+    return User(json['name'] as String);
+  }
+
+  String get display => name;
+}
+
+enum Status { active, banned }
+
+extension StringX on String {
+  bool get isBlank => trim().isEmpty;
+}
+
+mixin Logger {
+  void log(String message) {
+    print(message);
+  }
+}
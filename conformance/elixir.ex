@@ -0,0 +1,38 @@
+# Fixture for `smart-code-chunker conformance`, the chunks it must produce are listed
+# in elixir.expected.yaml
+require Logger
+
+defmodule MyApp.Worker do
+  use GenServer
+
+  @moduledoc "Counts events."
+
+  # Starts the worker under a supervisor.
+  def start_link(opts \\ []) do
+    GenServer.start_link(__MODULE__, opts, name: __MODULE__)
+  end
+
+  def handle_call(:count, _from, state), do: {:reply, state, state}
+
+  def handle_call({:add, n}, _from, state) when is_integer(n) do
+    {:reply, :ok, state + n}
+  end
+
+  defp log(message), do: IO.puts(message)
+
+  defmacro debug(expr) do
+    quote do: IO.inspect(unquote(expr))
+  end
+
+  defmodule State do
+    def new, do: 0
+  end
+end
+
+defprotocol MyApp.Describable do
+  def describe(value)
+end
+
+defimpl MyApp.Describable, for: MyApp.Worker.State do
+  def describe(_state), do: "state"
+end
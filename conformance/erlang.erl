@@ -0,0 +1,27 @@
+%% Fixture for `smart-code-chunker conformance`, the chunks it must produce are listed
+%% in erlang.expected.yaml
+-module(counter).
+-behaviour(gen_server).
+
+-export([start_link/0, init/1, handle_call/3]).
+
+-record(state, {count = 0 :: non_neg_integer()}).
+
+%% Starts the server.
+-spec start_link() -> {ok, pid()}.
+start_link() ->
+    gen_server:start_link({local, ?MODULE}, ?MODULE, [], []).
+
+-spec init(list()) -> {ok, #state{}}.
+init([]) ->
+    {ok, #state{}}.
+
+handle_call(increment, _From, #state{count = Count} = State) ->
+    {reply, ok, State#state{count = Count + 1}};
+handle_call(get, _From, State) ->
+    {reply, State#state.count, State}.
+
+-spec add(integer(), integer()) -> integer().
+add(A, B) -> A + B.
+
+add(A) -> add(A, 1).
@@ -0,0 +1,34 @@
+// Fixture for `smart-code-chunker conformance`, the chunks it must produce are listed
+// in go.expected.yaml
+package main
+
+import "fmt"
+
+const Version = "1.0"
+
+var (
+	defaultPort = 8080
+	host        = "localhost"
+)
+
+// Server handles requests.
+type Server struct {
+	port int
+}
+
+type Handler interface {
+	Handle() error
+}
+
+// HandleRequest serves one request.
+func (s *Server) HandleRequest(name string) error {
+	cb := func() { fmt.Println(name) }
+	cb()
+	return nil
+}
+
+func (l List[T]) Len() int { return 0 }
+
+func main() {
+	fmt.Println("hi")
+}
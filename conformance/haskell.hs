@@ -0,0 +1,33 @@
+-- Fixture for `smart-code-chunker conformance`, the chunks it must produce are listed
+-- in haskell.expected.yaml
+module Shapes where
+
+import Data.List (sortOn)
+
+-- | A shape in the plane.
+data Shape = Circle Double | Square Double
+  deriving (Show, Eq)
+
+newtype Area = Area Double
+
+-- | Sums the areas.
+total :: [Shape] -> Double
+total [] = 0
+total (s:ss) = area s + total ss
+
+largest :: [Shape]
+        -> Maybe Shape
+largest [] = Nothing
+largest xs = Just (last (sortOn area xs))
+
+main = print (total [Circle 1])
+
+class HasArea a where
+  area :: a -> Double
+
+instance Show Area where
+  show (Area a) = show a
+
+instance HasArea Shape where
+  area (Circle r) = pi * r * r
+  area (Square s) = s * s
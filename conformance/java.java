@@ -0,0 +1,43 @@
+// Fixture for `smart-code-chunker conformance`, the chunks it must produce are listed
+// in java.expected.yaml
+package com.example;
+
+import java.util.List;
+
+/** Service for users. */
+public class UserService {
+    private final List<String> users;
+
+    public UserService(List<String> users) {
+        this.users = users;
+    }
+
+    /**
+     * Creates a user.
+     */
+    @Override
+    @Deprecated(since = "2")
+    public String createUser(String name) {
+        return name;
+    }
+
+    static class Inner {
+        void run() {}
+    }
+
+    enum Role { ADMIN, USER }
+}
+
+interface Repository {
+    void save(String user);
+}
+
+record Point(int x, int y) {
+    Point {
+        if (x < 0) throw new IllegalArgumentException();
+    }
+
+    int sum() {
+        return x + y;
+    }
+}
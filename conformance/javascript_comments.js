@@ -0,0 +1,36 @@
+// Fixture for `smart-code-chunker conformance`, the chunks it must produce are listed
+// in javascript_comments.expected.yaml. Comments in layouts where it's unclear which
+// item they belong to
+
+/**
+ * JSDoc above an export, attaches to the function
+ */
+export function exported() {}
+
+// Comment above an arrow function in a const
+const arrow = () => 1;
+
+// One blank line between the comment and the item still attaches
+
+function oneBlankLine() {}
+
+// Two blank lines don't
+
+
+function twoBlankLines() {}
+
+const limit = 3; // Trailing comment of a statement, not of the next function
+function afterTrailing() {}
+
+/* Block comment */ function sameLineBlock() {}
+
+class Service {
+  start() {}
+  // Right under the method before, attaches to `stop`
+  stop() {}
+
+  // Last comment of the class, no method follows
+}
+
+// Comment above a default export
+export default class Widget {}
@@ -0,0 +1,25 @@
+// Fixture for `smart-code-chunker conformance`, the chunks it must produce are listed
+// in javascript_jsx.expected.yaml
+
+const Card = (props) => {
+  const { title, children } = props;
+  return (
+    <div className="card">
+      <h2>{title}</h2>
+      <section>
+        {children}
+        {props.footer && <footer>{props.footer}</footer>}
+      </section>
+    </div>
+  );
+};
+
+function App() {
+  return (
+    <Card title="Hello" footer={<small>bye</small>}>
+      <p>Body</p>
+    </Card>
+  );
+}
+
+export default () => <App />;
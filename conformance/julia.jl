@@ -0,0 +1,48 @@
+# Geometry helpers
+module Geometry
+
+export Point, area
+
+abstract type Shape end
+
+"""
+    Point(x, y)
+
+A point on the plane.
+"""
+struct Point{T<:Real} <: Shape
+    x::T
+    y::T
+    Point(x::T) where {T} = new{T}(x, zero(T))
+end
+
+Base.@kwdef mutable struct Circle <: Shape
+    center::Point = Point(0.0)
+    radius::Float64 = 1.0
+end
+
+# Area of a circle, `end` in an index doesn't close anything
+function area(c::Circle)
+    radii = [c.radius, c.radius]
+    return pi * radii[end]^2
+end
+
+norm2(p::Point) = p.x^2 +
+    p.y^2
+
+Base.show(io::IO, p::Point) = print(io, "(", p.x, ", ", p.y, ")")
+
+macro twice(ex)
+    quote
+        $(esc(ex))
+        $(esc(ex))
+    end
+end
+
+primitive type Byte 8 end
+
+end # module
+
+function (p::Geometry.Point)(scale)
+    map(x -> x * scale, [p.x, p.y])
+end
@@ -0,0 +1,25 @@
+# Constants named the way the paper does
+const α = 0.5
+const ħ = 1.054571817e-34
+
+"""
+    σ(x)
+
+Logistic function, `σ(0) == 0.5`.
+"""
+σ(x) = 1 / (1 + exp(-x))
+
+module Größen
+
+struct Δ
+    δt::Float64
+end
+
+function schritt(Δ₀::Δ, ε = 1e-9)
+    # Step of at least ε
+    max(Δ₀.δt, ε)
+end
+
+end
+
+ξ₂(x) = 2x
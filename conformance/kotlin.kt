@@ -0,0 +1,41 @@
+// Fixture for `smart-code-chunker conformance`, the chunks it must produce are listed
+// in kotlin.expected.yaml
+package com.example
+
+import kotlinx.coroutines.delay
+
+val defaultName: String = "guest"
+
+/** Turns a title into a URL slug. */
+fun String.toSlug(): String = lowercase().replace(" ", "-")
+
+class UserService(private val repository: Repository) {
+    private val cache = mutableMapOf<String, User>()
+
+    val settings: Settings by lazy {
+        Settings.load()
+    }
+
+    private val onError = { error: Throwable ->
+        log(error)
+    }
+
+    init {
+        require(repository.isOpen())
+    }
+
+    private inline suspend fun load(id: String): User {
+        delay(10)
+        return repository.find(id)
+    }
+
+    companion object {
+        fun create(): UserService = UserService(Repository())
+    }
+}
+
+object Registry {
+    fun register(name: String) {}
+}
+
+fun <T> List<T>.second(): T = this[1]
@@ -0,0 +1,43 @@
+-- Fixture for `smart-code-chunker conformance`, the chunks it must produce are listed
+-- in lua.expected.yaml
+local M = {}
+
+local DEFAULT_RATE = 0.2
+
+-- Rounds to two decimals.
+local function round(value)
+  return math.floor(value * 100 + 0.5) / 100
+end
+
+-- Tax on a net amount.
+function M.tax(amount, rate)
+  return round(amount * (rate or DEFAULT_RATE))
+end
+
+function M.util.clamp(value, low, high)
+  return math.max(low, math.min(high, value))
+end
+
+local Invoice = {}
+Invoice.__index = Invoice
+
+function Invoice.new(lines)
+  return setmetatable({ lines = lines }, Invoice)
+end
+
+-- Sum of the line amounts with tax.
+function Invoice:total()
+  local sum = 0
+  for _, line in ipairs(self.lines) do
+    sum = sum + line.amount
+  end
+  return sum + M.tax(sum)
+end
+
+function log(message)
+  print(message)
+end
+
+M.Invoice = Invoice
+
+return M
@@ -0,0 +1,34 @@
+(* Shapes and the stack they are kept in *)
+type shape = Circle of float | Square of float
+
+(** Area of a shape *)
+let area = function
+  | Circle r -> 3.14 *. r *. r
+  | Square s -> s *. s
+
+let rec sum = function [] -> 0 | x :: xs -> x + sum xs
+and len = function [] -> 0 | _ :: xs -> 1 + len xs
+
+module Stack = struct
+  type 'a t = 'a list
+
+  (* The inner `let ... in` is part of push *)
+  let push x s =
+    let y = x in
+    y :: s
+end
+
+module Make (O : Ord) = struct
+  let max a b = if O.compare a b > 0 then a else b
+end
+
+module type Printable = sig
+  val print : Format.formatter -> unit
+end
+
+class counter = object
+  val mutable n = 0
+  method incr = n <- n + 1
+end
+
+let () = print_endline (string_of_float (area (Circle 1.0)))
@@ -0,0 +1,12 @@
+(** Stacks *)
+type 'a t
+
+(** The empty stack *)
+val empty : 'a t
+
+val push :
+  'a -> 'a t -> 'a t
+
+module Ops : sig
+  val size : 'a t -> int
+end
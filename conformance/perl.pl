@@ -0,0 +1,48 @@
+#!/usr/bin/perl
+use strict;
+use warnings;
+
+# Helpers in main
+sub log_line {
+    my ($msg) = @_;
+    print "$msg\n";
+}
+
+package My::Counter;
+
+=head2 new
+
+Creates a counter starting at zero.
+
+=cut
+
+sub new {
+    my ($class, %args) = @_;
+    my $self = { count => 0, step => $args{step} // 1 };
+    return bless $self, $class;
+}
+
+# Braces in strings and $#array don't count
+sub increment {
+    my $self = shift;
+    my @history = (1, 2, 3);
+    for my $i (0 .. $#history) {
+        print "{";
+    }
+    $self->{count} += $self->{step};
+}
+
+sub forward_declared;
+
+package My::Report {
+    sub render {
+        my ($rows) = @_;
+        return join "\n", map { "- $_" } @$rows;
+    }
+}
+
+package main;
+
+sub run { log_line("done") }
+
+run();
@@ -0,0 +1,18 @@
+use utf8;
+use strict;
+
+# Café prices, in €
+my $naïve = 1;
+my %größen = (klein => "S", groß => "L");
+
+sub prix_café {
+    my ($tasse) = @_;
+    return $naïve * 2.5;
+}
+
+package Größe::Tabelle;
+
+# Sizes for the menu ☕
+sub größe {
+    return $größen{groß};
+}
@@ -0,0 +1,53 @@
+<!-- Fixture for `smart-code-chunker conformance`, the chunks it must produce are listed
+     in php.expected.yaml -->
+<html>
+<body>
+<?php
+
+namespace App\Models;
+
+use App\Contracts\Arrayable;
+
+interface HasName
+{
+    public function name(): string;
+}
+
+trait Greets
+{
+    public function greet(): string
+    {
+        return "Hello, " . $this->name();
+    }
+}
+
+enum Status: string
+{
+    case Active = 'active';
+}
+
+/**
+ * A user of the site.
+ */
+#[Entity]
+final class User implements HasName
+{
+    use Greets;
+
+    public function __construct(private string $name) {}
+
+    public static function named(
+        string $name,
+    ): static {
+        return new static($name);
+    }
+}
+
+function format_user(User $user): string
+{
+    return strtoupper($user->name());
+}
+?>
+<p><?= format_user(new User("Ada")) ?></p>
+</body>
+</html>
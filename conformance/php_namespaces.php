@@ -0,0 +1,18 @@
+<?php
+// Fixture for `smart-code-chunker conformance`, the chunks it must produce are listed
+// in php_namespaces.expected.yaml
+declare(strict_types=1);
+
+namespace App\Http {
+    abstract class Controller
+    {
+        abstract public function handle(array $request): array;
+    }
+}
+
+namespace {
+    function main(): void
+    {
+        echo "ready\n";
+    }
+}
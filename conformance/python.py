@@ -0,0 +1,29 @@
+# Fixture for `smart-code-chunker conformance`, the chunks it must produce are listed
+# in python.expected.yaml
+
+
+def top_level(value):
+    """Docstring inside the function."""
+    return value * 2
+
+
+# Comment above a decorated class
+@dataclass
+class Config:
+    name: str
+
+    def describe(self):
+        return self.name
+
+    @staticmethod
+    def default():
+        def build():
+            return Config("default")
+
+        return build()
+
+
+if sys.platform == "win32":
+
+    def windows_only():
+        pass
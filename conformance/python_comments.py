@@ -0,0 +1,53 @@
+# Fixture for `smart-code-chunker conformance`, the chunks it must produce are listed
+# in python_comments.expected.yaml. Comments in layouts where it's unclear which item
+# they belong to
+
+
+# Comment above a decorator, attaches across it
+@decorator
+def above_decorator():
+    pass
+
+
+@decorator
+# Comment between the decorator and the def
+def between_decorator_and_def():
+    pass
+
+
+# One blank line between the comment and the def still attaches
+
+def one_blank_line():
+    pass
+
+
+# Two blank lines don't
+
+
+def two_blank_lines():
+    pass
+
+
+LIMIT = 3  # Trailing comment of an assignment
+def after_trailing():
+    pass
+
+
+class Service:
+    """Docstring of the class."""
+    # Right under the docstring, attaches to the method
+    def start(self):
+        pass
+
+    def stop(self):
+        pass
+    # Right under the body of `stop`, attaches to `restart`
+    def restart(self):
+        pass
+
+    # Last comment of the class, no method follows
+
+
+# Comment above an async def
+async def fetch():
+    pass
@@ -0,0 +1,39 @@
+# Fixture for `smart-code-chunker conformance`, the chunks it must produce are listed
+# in ruby.expected.yaml
+require "json"
+
+# A user account.
+class User
+  module Validations
+    # Checks the address format.
+    def validate_email(address,
+                       strict: false)
+      parts.each do |part|
+        part.strip
+      end
+    end
+  end
+
+  def self.find(id)
+    new
+  end
+
+  class << self
+    def count
+      0
+    end
+  end
+end
+
+module Api
+  class Client
+    def get(path)
+    end
+  end
+end
+
+describe "User" do
+  it "validates" do
+    User.new
+  end
+end
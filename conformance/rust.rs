@@ -0,0 +1,43 @@
+// Fixture for `smart-code-chunker conformance`, the chunks it must produce are listed
+// in rust.expected.yaml
+
+use std::fmt;
+
+/// Documented free function, the doc comment is part of the chunk
+pub fn add(a: i32, b: i32) -> i32 {
+    a + b
+}
+
+#[derive(Debug)]
+struct Point {
+    x: f64,
+    y: f64,
+}
+
+impl Point {
+    fn norm(&self) -> f64 {
+        (self.x * self.x + self.y * self.y).sqrt()
+    }
+}
+
+mod geometry {
+    pub struct Line;
+
+    impl Line {
+        // Plain comments attach too
+        pub fn length(&self) -> f64 {
+            0.0
+        }
+    }
+}
+
+impl<T> From<T> for Point where T: Into<f64> {
+    fn from(value: T) -> Self {
+        let v = value.into();
+        Point { x: v, y: v }
+    }
+}
+
+extern "C" {
+    fn abs(input: i32) -> i32;
+}
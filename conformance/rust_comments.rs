@@ -0,0 +1,48 @@
+// Fixture for `smart-code-chunker conformance`, the chunks it must produce are listed
+// in rust_comments.expected.yaml. Comments in layouts where it's unclear which item they
+// belong to
+
+/// Doc comment above an attribute, attaches across it
+#[inline]
+pub fn across_attribute() {}
+
+#[must_use]
+/// Doc comment between attributes
+#[inline]
+pub fn between_attributes() -> i32 {
+    0
+}
+
+// One blank line between the comment and the item still attaches
+
+pub fn one_blank_line() {}
+
+// Two blank lines don't
+
+
+pub fn two_blank_lines() {}
+
+pub fn with_trailing() {} // Trailing comment of the item before, not of the next one
+pub fn after_trailing() {}
+
+/* Block comment */
+pub fn block_comment() {}
+
+// First of two comment blocks, split by a blank line
+
+// Second block, both attach
+pub fn two_blocks() {}
+
+pub struct Counter;
+
+impl Counter {
+    pub fn first(&self) {}
+    // Right under the closing brace of `first`, attaches to `second`
+    pub fn second(&self) {}
+
+    // Last comment of the impl, no item follows
+}
+
+pub const LIMIT: u32 = 3; // Trailing comment of a const
+
+pub fn last() {}
@@ -0,0 +1,26 @@
+// Fixture for `smart-code-chunker conformance`: one chunk per enum, trait, type alias,
+// const and static, listed in rust_items.expected.yaml
+
+use std::fmt::Debug;
+
+/// Shapes the renderer knows
+#[derive(Debug, Clone)]
+pub enum Shape {
+    Circle { radius: f64 },
+    Square(f64),
+}
+
+pub trait Area {
+    fn area(&self) -> f64;
+
+    // Default methods are chunked in the trait's context
+    fn describe(&self) -> String {
+        format!("area {}", self.area())
+    }
+}
+
+pub type ShapeList = Vec<Shape>;
+
+const MAX_SHAPES: usize = 64;
+
+static DEFAULT_NAME: &str = "shape";
@@ -0,0 +1,25 @@
+// Fixture for `smart-code-chunker conformance`: declarative and procedural macros,
+// listed in rust_macros.expected.yaml
+
+use proc_macro::TokenStream;
+
+/// Squares an expression
+macro_rules! square {
+    ($x:expr) => {
+        $x * $x
+    };
+}
+
+#[proc_macro_derive(MyTrait)]
+pub fn derive_my_trait(input: TokenStream) -> TokenStream {
+    input
+}
+
+#[proc_macro_attribute]
+pub fn traced(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    item
+}
+
+fn helper() -> u32 {
+    square!(3)
+}
@@ -0,0 +1,135 @@
+// Fixture for `smart-code-chunker conformance`: a function over the 800 token limit is
+// split into parts, each with the line range of its own code, listed in
+// rust_split.expected.yaml
+
+use std::collections::HashMap;
+
+/// Builds the lookup table, long enough to be split into several parts
+pub fn build_table() -> HashMap<&'static str, u32> {
+    let mut table = HashMap::new();
+    table.insert("alpha_0", 0 * 1 + "alpha".len() as u32);
+    table.insert("bravo_1", 1 * 2 + "bravo".len() as u32);
+    table.insert("charlie_2", 2 * 3 + "charlie".len() as u32);
+    table.insert("delta_3", 3 * 4 + "delta".len() as u32);
+    table.insert("echo_4", 4 * 5 + "echo".len() as u32);
+    table.insert("foxtrot_5", 5 * 6 + "foxtrot".len() as u32);
+    table.insert("golf_6", 6 * 7 + "golf".len() as u32);
+    table.insert("hotel_7", 7 * 1 + "hotel".len() as u32);
+    table.insert("india_8", 8 * 2 + "india".len() as u32);
+    table.insert("juliet_9", 9 * 3 + "juliet".len() as u32);
+    table.insert("kilo_10", 10 * 4 + "kilo".len() as u32);
+    table.insert("lima_11", 11 * 5 + "lima".len() as u32);
+    table.insert("mike_12", 12 * 6 + "mike".len() as u32);
+    table.insert("november_13", 13 * 7 + "november".len() as u32);
+    table.insert("oscar_14", 14 * 1 + "oscar".len() as u32);
+    table.insert("papa_15", 15 * 2 + "papa".len() as u32);
+    table.insert("quebec_16", 16 * 3 + "quebec".len() as u32);
+    table.insert("romeo_17", 17 * 4 + "romeo".len() as u32);
+    table.insert("sierra_18", 18 * 5 + "sierra".len() as u32);
+    table.insert("tango_19", 19 * 6 + "tango".len() as u32);
+    table.insert("alpha_20", 20 * 7 + "alpha".len() as u32);
+    table.insert("bravo_21", 21 * 1 + "bravo".len() as u32);
+    table.insert("charlie_22", 22 * 2 + "charlie".len() as u32);
+    table.insert("delta_23", 23 * 3 + "delta".len() as u32);
+    table.insert("echo_24", 24 * 4 + "echo".len() as u32);
+    table.insert("foxtrot_25", 25 * 5 + "foxtrot".len() as u32);
+    table.insert("golf_26", 26 * 6 + "golf".len() as u32);
+    table.insert("hotel_27", 27 * 7 + "hotel".len() as u32);
+    table.insert("india_28", 28 * 1 + "india".len() as u32);
+    table.insert("juliet_29", 29 * 2 + "juliet".len() as u32);
+    table.insert("kilo_30", 30 * 3 + "kilo".len() as u32);
+    table.insert("lima_31", 31 * 4 + "lima".len() as u32);
+    table.insert("mike_32", 32 * 5 + "mike".len() as u32);
+    table.insert("november_33", 33 * 6 + "november".len() as u32);
+    table.insert("oscar_34", 34 * 7 + "oscar".len() as u32);
+    table.insert("papa_35", 35 * 1 + "papa".len() as u32);
+    table.insert("quebec_36", 36 * 2 + "quebec".len() as u32);
+    table.insert("romeo_37", 37 * 3 + "romeo".len() as u32);
+    table.insert("sierra_38", 38 * 4 + "sierra".len() as u32);
+    table.insert("tango_39", 39 * 5 + "tango".len() as u32);
+    table.insert("alpha_40", 40 * 6 + "alpha".len() as u32);
+    table.insert("bravo_41", 41 * 7 + "bravo".len() as u32);
+    table.insert("charlie_42", 42 * 1 + "charlie".len() as u32);
+    table.insert("delta_43", 43 * 2 + "delta".len() as u32);
+    table.insert("echo_44", 44 * 3 + "echo".len() as u32);
+    table.insert("foxtrot_45", 45 * 4 + "foxtrot".len() as u32);
+    table.insert("golf_46", 46 * 5 + "golf".len() as u32);
+    table.insert("hotel_47", 47 * 6 + "hotel".len() as u32);
+    table.insert("india_48", 48 * 7 + "india".len() as u32);
+    table.insert("juliet_49", 49 * 1 + "juliet".len() as u32);
+    table.insert("kilo_50", 50 * 2 + "kilo".len() as u32);
+    table.insert("lima_51", 51 * 3 + "lima".len() as u32);
+    table.insert("mike_52", 52 * 4 + "mike".len() as u32);
+    table.insert("november_53", 53 * 5 + "november".len() as u32);
+    table.insert("oscar_54", 54 * 6 + "oscar".len() as u32);
+    table.insert("papa_55", 55 * 7 + "papa".len() as u32);
+    table.insert("quebec_56", 56 * 1 + "quebec".len() as u32);
+    table.insert("romeo_57", 57 * 2 + "romeo".len() as u32);
+    table.insert("sierra_58", 58 * 3 + "sierra".len() as u32);
+    table.insert("tango_59", 59 * 4 + "tango".len() as u32);
+    table.insert("alpha_60", 60 * 5 + "alpha".len() as u32);
+    table.insert("bravo_61", 61 * 6 + "bravo".len() as u32);
+    table.insert("charlie_62", 62 * 7 + "charlie".len() as u32);
+    table.insert("delta_63", 63 * 1 + "delta".len() as u32);
+    table.insert("echo_64", 64 * 2 + "echo".len() as u32);
+    table.insert("foxtrot_65", 65 * 3 + "foxtrot".len() as u32);
+    table.insert("golf_66", 66 * 4 + "golf".len() as u32);
+    table.insert("hotel_67", 67 * 5 + "hotel".len() as u32);
+    table.insert("india_68", 68 * 6 + "india".len() as u32);
+    table.insert("juliet_69", 69 * 7 + "juliet".len() as u32);
+    table.insert("kilo_70", 70 * 1 + "kilo".len() as u32);
+    table.insert("lima_71", 71 * 2 + "lima".len() as u32);
+    table.insert("mike_72", 72 * 3 + "mike".len() as u32);
+    table.insert("november_73", 73 * 4 + "november".len() as u32);
+    table.insert("oscar_74", 74 * 5 + "oscar".len() as u32);
+    table.insert("papa_75", 75 * 6 + "papa".len() as u32);
+    table.insert("quebec_76", 76 * 7 + "quebec".len() as u32);
+    table.insert("romeo_77", 77 * 1 + "romeo".len() as u32);
+    table.insert("sierra_78", 78 * 2 + "sierra".len() as u32);
+    table.insert("tango_79", 79 * 3 + "tango".len() as u32);
+    table.insert("alpha_80", 80 * 4 + "alpha".len() as u32);
+    table.insert("bravo_81", 81 * 5 + "bravo".len() as u32);
+    table.insert("charlie_82", 82 * 6 + "charlie".len() as u32);
+    table.insert("delta_83", 83 * 7 + "delta".len() as u32);
+    table.insert("echo_84", 84 * 1 + "echo".len() as u32);
+    table.insert("foxtrot_85", 85 * 2 + "foxtrot".len() as u32);
+    table.insert("golf_86", 86 * 3 + "golf".len() as u32);
+    table.insert("hotel_87", 87 * 4 + "hotel".len() as u32);
+    table.insert("india_88", 88 * 5 + "india".len() as u32);
+    table.insert("juliet_89", 89 * 6 + "juliet".len() as u32);
+    table.insert("kilo_90", 90 * 7 + "kilo".len() as u32);
+    table.insert("lima_91", 91 * 1 + "lima".len() as u32);
+    table.insert("mike_92", 92 * 2 + "mike".len() as u32);
+    table.insert("november_93", 93 * 3 + "november".len() as u32);
+    table.insert("oscar_94", 94 * 4 + "oscar".len() as u32);
+    table.insert("papa_95", 95 * 5 + "papa".len() as u32);
+    table.insert("quebec_96", 96 * 6 + "quebec".len() as u32);
+    table.insert("romeo_97", 97 * 7 + "romeo".len() as u32);
+    table.insert("sierra_98", 98 * 1 + "sierra".len() as u32);
+    table.insert("tango_99", 99 * 2 + "tango".len() as u32);
+    table.insert("alpha_100", 100 * 3 + "alpha".len() as u32);
+    table.insert("bravo_101", 101 * 4 + "bravo".len() as u32);
+    table.insert("charlie_102", 102 * 5 + "charlie".len() as u32);
+    table.insert("delta_103", 103 * 6 + "delta".len() as u32);
+    table.insert("echo_104", 104 * 7 + "echo".len() as u32);
+    table.insert("foxtrot_105", 105 * 1 + "foxtrot".len() as u32);
+    table.insert("golf_106", 106 * 2 + "golf".len() as u32);
+    table.insert("hotel_107", 107 * 3 + "hotel".len() as u32);
+    table.insert("india_108", 108 * 4 + "india".len() as u32);
+    table.insert("juliet_109", 109 * 5 + "juliet".len() as u32);
+    table.insert("kilo_110", 110 * 6 + "kilo".len() as u32);
+    table.insert("lima_111", 111 * 7 + "lima".len() as u32);
+    table.insert("mike_112", 112 * 1 + "mike".len() as u32);
+    table.insert("november_113", 113 * 2 + "november".len() as u32);
+    table.insert("oscar_114", 114 * 3 + "oscar".len() as u32);
+    table.insert("papa_115", 115 * 4 + "papa".len() as u32);
+    table.insert("quebec_116", 116 * 5 + "quebec".len() as u32);
+    table.insert("romeo_117", 117 * 6 + "romeo".len() as u32);
+    table.insert("sierra_118", 118 * 7 + "sierra".len() as u32);
+    table.insert("tango_119", 119 * 1 + "tango".len() as u32);
+    table
+}
+
+fn small() -> u32 {
+    1
+}
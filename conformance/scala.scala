@@ -0,0 +1,33 @@
+// Fixture for `smart-code-chunker conformance`, the chunks it must produce are listed
+// in scala.expected.yaml
+package com.example.shapes
+
+/** A shape with an area. */
+sealed trait Shape {
+  def area: Double
+}
+
+final case class Circle(radius: Double) extends Shape {
+  def area: Double = math.Pi * radius * radius
+}
+
+object Circle {
+  def unit: Circle = Circle(1)
+
+  object Defaults {
+    val radius = 1.0
+
+    def describe(): String =
+      s"radius $radius"
+  }
+}
+
+case object Empty extends Shape {
+  def area: Double = 0
+}
+
+enum Color:
+  case Red, Green
+
+def main(args: Array[String]): Unit =
+  println(Circle.unit.area)
@@ -0,0 +1,24 @@
+// Fixture for `smart-code-chunker conformance`, the chunks it must produce are listed
+// in scala_script.expected.yaml
+import scala.concurrent.ExecutionContext
+
+val retries = 3
+
+val (host, port) = ("localhost", 8080)
+
+/** A user of the service. */
+case class User(id: Long, name: String) {
+  val display = s"$name#$id"
+}
+
+object User {
+  val guest = User(0, "guest")
+
+  def parse[A: Ordering](raw: String)(implicit ec: ExecutionContext): Option[User] =
+    raw.split(',') match {
+      case Array(id, name) => Some(User(id.toLong, name))
+      case _ => None
+    }
+}
+
+def greet(user: User): String = s"Hello, ${user.name}"
@@ -0,0 +1,48 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+interface IERC20 {
+    function totalSupply() external view returns (uint256);
+
+    event Transfer(address indexed from, address indexed to, uint256 value);
+}
+
+library SafeMath {
+    function add(uint256 a, uint256 b) internal pure returns (uint256) {
+        return a + b;
+    }
+}
+
+abstract contract Ownable {
+    address internal owner;
+
+    modifier onlyOwner() {
+        require(msg.sender == owner, "not owner");
+        _;
+    }
+}
+
+/// A fixed supply token
+contract ERC20Token is IERC20, Ownable {
+    using SafeMath for uint256;
+
+    uint256 private _total;
+
+    constructor(uint256 supply) {
+        owner = msg.sender;
+        _total = supply;
+    }
+
+    function totalSupply() external view override returns (uint256) {
+        return _total;
+    }
+
+    function mint(uint256 amount) external onlyOwner {
+        _total = _total.add(amount);
+        emit Transfer(address(0), msg.sender, amount);
+    }
+
+    receive() external payable {}
+
+    fallback() external payable {}
+}
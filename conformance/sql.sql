@@ -0,0 +1,32 @@
+-- Accounts that can sign in
+CREATE TABLE IF NOT EXISTS public.users (
+    id BIGSERIAL PRIMARY KEY,
+    email TEXT NOT NULL UNIQUE, -- lowercased; checked below
+    created_at TIMESTAMPTZ DEFAULT now()
+);
+
+CREATE UNIQUE INDEX CONCURRENTLY users_email_idx ON public.users (lower(email));
+
+CREATE OR REPLACE VIEW "Active Users" AS
+    SELECT id, email FROM public.users WHERE created_at > now() - interval '30 days';
+
+/* Touches updated_at; the body is one string */
+CREATE OR REPLACE FUNCTION touch_updated_at() RETURNS trigger AS $$
+BEGIN
+    NEW.updated_at := now();
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER users_touch BEFORE UPDATE ON public.users
+    FOR EACH ROW EXECUTE FUNCTION touch_updated_at();
+
+CREATE PROCEDURE archive_users(days INT)
+BEGIN
+    IF days > 0 THEN
+        DELETE FROM users WHERE created_at < NOW() - INTERVAL days DAY;
+    END IF;
+    INSERT INTO audit_log (action) VALUES ('archive;users');
+END;
+
+INSERT INTO public.users (email) VALUES ('seed@example.com');
@@ -0,0 +1,10 @@
+-- Seed data
+INSERT INTO roles (name) VALUES ('admin');
+INSERT INTO roles (name) VALUES ('member');
+
+INSERT INTO users (email, role) VALUES ('a@example.com', 'admin');
+INSERT INTO users (email, role) VALUES ('b@example.com', 'member');
+
+UPDATE users SET role = 'admin' WHERE email = 'b@example.com';
+
+DELETE FROM roles WHERE name = 'guest';
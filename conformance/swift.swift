@@ -0,0 +1,42 @@
+// Fixture for `smart-code-chunker conformance`, the chunks it must produce are listed
+// in swift.expected.yaml
+import UIKit
+
+/// Something that can be drawn.
+protocol Drawable {
+    func draw()
+}
+
+struct Point: Drawable {
+    var x: Double
+    var y: Double
+
+    init(x: Double, y: Double) {
+        self.x = x
+        self.y = y
+    }
+
+    func draw() {}
+}
+
+enum Direction {
+    case north, south
+
+    func opposite() -> Direction {
+        self == .north ? .south : .north
+    }
+}
+
+class ViewModel {
+    func load() async throws {}
+}
+
+actor Counter {
+    var value = 0
+}
+
+extension UIViewController {
+    func viewDidLoad() {
+        super.viewDidLoad()
+    }
+}
@@ -0,0 +1,34 @@
+// Fixture for `smart-code-chunker conformance`, the chunks it must produce are listed
+// in swift_extensions.expected.yaml
+import Foundation
+
+protocol Shape {
+    var area: Double { get }
+    func describe() -> String
+}
+
+// Default implementations for every shape
+extension Shape {
+    var isEmpty: Bool {
+        area == 0
+    }
+
+    func describe() -> String {
+        "Shape with area \(area)"
+    }
+}
+
+struct Square: Shape {
+    var side: Double
+
+    var area: Double {
+        get { side * side }
+        set { side = newValue.squareRoot() }
+    }
+}
+
+extension Array where Element: Shape {
+    func totalArea() -> Double {
+        reduce(0) { $0 + $1.area }
+    }
+}
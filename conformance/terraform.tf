@@ -0,0 +1,47 @@
+terraform {
+  required_version = ">= 1.5"
+}
+
+# Where the logs go
+resource "aws_s3_bucket" "logs" {
+  bucket = "${var.prefix}-logs"
+
+  lifecycle_rule {
+    enabled = true
+  }
+
+  tags = {
+    Team = "infra"
+  }
+}
+
+variable "prefix" {
+  type    = string
+  default = "acme" # overridden per stage
+}
+
+data "aws_iam_policy_document" "read" {
+  statement {
+    actions   = ["s3:GetObject"]
+    resources = ["${aws_s3_bucket.logs.arn}/*"]
+  }
+}
+
+/*
+ * Shared network
+ */
+module "vpc" {
+  source = "./modules/vpc"
+}
+
+provider "aws" {
+  region = "eu-west-1"
+}
+
+locals {
+  name = "${var.prefix}-app"
+}
+
+output "bucket_arn" {
+  value = aws_s3_bucket.logs.arn
+}
@@ -0,0 +1,36 @@
+// Fixture for `smart-code-chunker conformance`, the chunks it must produce are listed
+// in typescript_jsx.expected.yaml. Nested JSX must parse without errors, which
+// would otherwise drop the components
+
+import React from "react";
+
+interface ListProps {
+  items: string[];
+}
+
+// Arrow function component bound to a constant
+export const List = ({ items }: ListProps) => {
+  return (
+    <ul className="list">
+      {items.map((item) => (
+        <li key={item}>
+          <Item label={item}>{item.length > 3 ? <b>{item}</b> : item}</Item>
+        </li>
+      ))}
+    </ul>
+  );
+};
+
+function Item(props: { label: string; children?: React.ReactNode }) {
+  return <span title={props.label}>{props.children}</span>;
+}
+
+export default class Page extends React.Component<{}> {
+  render() {
+    return (
+      <>
+        <List items={["a", "b"]} />
+      </>
+    );
+  }
+}
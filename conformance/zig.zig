@@ -0,0 +1,52 @@
+//! Two-dimensional points
+const std = @import("std");
+
+/// A point on the plane
+pub const Point = struct {
+    x: f32,
+    y: f32,
+
+    pub fn init(x: f32, y: f32) Point {
+        return .{ .x = x, .y = y };
+    }
+
+    // The brace in this string doesn't close the struct: "}"
+    pub fn format(self: Point, writer: anytype) !void {
+        try writer.print("({d}, {d})", .{ self.x, self.y });
+    }
+};
+
+pub const Shape = union(enum) {
+    circle: f32,
+    square: f32,
+
+    pub fn area(self: Shape) f32 {
+        return switch (self) {
+            .circle => |r| std.math.pi * r * r,
+            .square => |s| s * s,
+        };
+    }
+};
+
+const Direction = enum { north, south };
+
+extern "c" fn abs(x: c_int) c_int;
+
+const banner =
+    \\ fn not_a_function() {
+    \\ }
+;
+
+pub fn distance(a: Point, b: Point) f32 {
+    const Local = struct {
+        fn square(v: f32) f32 {
+            return v * v;
+        }
+    };
+    return @sqrt(Local.square(a.x - b.x) + Local.square(a.y - b.y));
+}
+
+test "distance of a point to itself" {
+    const p = Point.init(1, 2);
+    try std.testing.expectEqual(@as(f32, 0), distance(p, p));
+}
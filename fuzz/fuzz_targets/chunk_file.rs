@@ -0,0 +1,32 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use smart_code_chunker::files::{decode_lossy, find_chunks};
+use smart_code_chunker::invariants::{check_chunks, check_options};
+use smart_code_chunker::lang_driver::all_drivers;
+use smart_code_chunker::scan::{all_scan_drivers, find_scanned_chunks};
+use std::path::Path;
+use tree_sitter::Parser;
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    // Picks one of every driver built in, tree-sitter ones first, then the scanners
+    driver: u8,
+    max_tokens: u16,
+    bytes: Vec<u8>,
+}
+
+fuzz_target!(|input: Input| {
+    let drivers = all_drivers();
+    let scanners = all_scan_drivers();
+    let index = usize::from(input.driver) % (drivers.len() + scanners.len());
+    let (content, _) = decode_lossy(input.bytes);
+    let options = check_options(usize::from(input.max_tokens));
+    let path = Path::new("fuzz");
+    let chunks = match drivers.get(index) {
+        Some(driver) => find_chunks(path, &content, driver.as_ref(), &mut Parser::new(), &options).unwrap(),
+        None => find_scanned_chunks(path, &content, scanners[index - drivers.len()].as_ref(), &options),
+    };
+    check_chunks(&content, &chunks).unwrap();
+});
@@ -0,0 +1,33 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use smart_code_chunker::files::{Tokenizer, decode_lossy, split_text_by_token_limit};
+use smart_code_chunker::invariants::{check_decoded, check_split};
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    max_tokens: u16,
+    overlap_tokens: u8,
+    // Invalid UTF-8 goes through the lossy decoding --emit-gaps uses instead of being dropped
+    lossy: bool,
+    bytes: Vec<u8>,
+}
+
+fuzz_target!(|input: Input| {
+    let text = if input.lossy {
+        let source = decode_lossy(input.bytes);
+        check_decoded(&source).unwrap();
+        source.0
+    } else {
+        match String::from_utf8(input.bytes) {
+            Ok(text) => text,
+            Err(_) => return,
+        }
+    };
+    let max_tokens = usize::from(input.max_tokens);
+    let overlap_tokens = usize::from(input.overlap_tokens);
+    let tokenizer = Tokenizer::default();
+    let parts = split_text_by_token_limit(&text, max_tokens, overlap_tokens, &tokenizer);
+    check_split(&text, max_tokens, overlap_tokens, &tokenizer, &parts).unwrap();
+});
@@ -0,0 +1,181 @@
+use crate::hash::compute_hash;
+use crate::lsp::LineIndex;
+use crate::safety;
+use crate::stream::read_lines;
+use crate::types::{ChunkData, ChunkId};
+use anyhow::{Result, anyhow};
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+// Previous hash of the first entry
+const GENESIS: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+// First line of a chain, naming the folder the output's file paths resolve against
+const BASE_PREFIX: &str = "# base ";
+
+// sha256 of the chunk's lines exactly as they are in the file, line ending of the last
+// one excluded. Unlike `code` this can be re-derived from the source tree alone
+pub fn source_hash(content: &str, start_line: usize, end_line: usize) -> String {
+    let index = LineIndex::new(content);
+    let start = index.line_start(start_line.saturating_sub(1));
+    let end = index.line_end(end_line.saturating_sub(1)).max(start);
+    compute_hash(&content[start..end])
+}
+
+pub fn add_content_hashes(chunks: &mut [ChunkData], content: &str) {
+    for chunk in chunks {
+        chunk.content_hash = Some(source_hash(content, chunk.start_line, chunk.end_line));
+    }
+}
+
+fn chain_hash(previous: &str, id: &str, content_hash: &str) -> String {
+    compute_hash(&format!("{}\n{}\n{}", previous, id, content_hash))
+}
+
+// String ids as they are, integer ids in decimal, the same text `id_text` reads back
+fn chunk_id_text(id: &ChunkId) -> String {
+    match id {
+        ChunkId::Text(text) => text.clone(),
+        ChunkId::Int(n) => n.to_string(),
+    }
+}
+
+fn id_text(id: &Value) -> String {
+    id.as_str().map_or_else(|| id.to_string(), str::to_string)
+}
+
+// A `# base <folder>` line, then one `chain_hash id content_hash` line per chunk, in
+// the order they are written
+pub struct ChainWriter {
+    writer: BufWriter<File>,
+    previous: String,
+}
+
+impl ChainWriter {
+    pub fn create(path: &Path, base: &Path) -> Result<Self> {
+        let mut writer = BufWriter::new(safety::create_file("--audit-chain", path)?);
+        writeln!(writer, "{}{}", BASE_PREFIX, base.display())?;
+        Ok(ChainWriter {
+            writer,
+            previous: GENESIS.to_string(),
+        })
+    }
+
+    pub fn append(&mut self, chunk: &ChunkData) -> Result<()> {
+        let id = chunk_id_text(&chunk.id);
+        let content_hash = chunk.content_hash.as_deref().unwrap_or("");
+        self.previous = chain_hash(&self.previous, &id, content_hash);
+        writeln!(self.writer, "{} {} {}", self.previous, id, content_hash)?;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+// Why a record doesn't check out against its chain entry and source, None if it does
+fn check_record(
+    line: &str,
+    entry: Option<&str>,
+    previous: &str,
+    root: &Path,
+    file: &mut Option<(PathBuf, String)>,
+) -> Option<String> {
+    let Some(entry) = entry else {
+        return Some("the chain ends before the output".to_string());
+    };
+    let mut fields = entry.split(' ');
+    let (Some(hash), Some(chain_id), Some(chain_content)) =
+        (fields.next(), fields.next(), fields.next())
+    else {
+        return Some("malformed chain entry".to_string());
+    };
+    if chain_hash(previous, chain_id, chain_content) != hash {
+        return Some("chain hash does not follow from the previous entry".to_string());
+    }
+
+    let Ok(Value::Object(record)) = serde_json::from_str::<Value>(line) else {
+        return Some("output record is not a JSON object".to_string());
+    };
+    let id = record.get("id").map(id_text).unwrap_or_default();
+    if id != chain_id {
+        return Some(format!("output id {} but chain id {}", id, chain_id));
+    }
+    let content_hash = record.get("content_hash").and_then(Value::as_str);
+    if content_hash != Some(chain_content) {
+        return Some("content_hash differs from the chain".to_string());
+    }
+
+    let file_path = root.join(
+        record
+            .get("file_path")
+            .and_then(Value::as_str)
+            .unwrap_or(""),
+    );
+    if file.as_ref().is_none_or(|(path, _)| *path != file_path) {
+        let Ok(bytes) = fs::read(&file_path) else {
+            return Some(format!("{} can't be read", file_path.display()));
+        };
+        *file = Some((file_path, String::from_utf8_lossy(&bytes).into_owned()));
+    }
+    let (file_path, content) = file.as_ref().expect("file was just read");
+    let line_of = |field| record.get(field).and_then(Value::as_u64).unwrap_or(0) as usize;
+    if source_hash(content, line_of("start_line"), line_of("end_line")) != chain_content {
+        return Some(format!(
+            "lines {}-{} of {} changed since the chunk was written",
+            line_of("start_line"),
+            line_of("end_line"),
+            file_path.display()
+        ));
+    }
+    None
+}
+
+// Walks output and chain side by side, re-deriving every content hash from the files
+// under the chain's base folder, or `root` when given, and stops at the first record
+// that doesn't check out
+pub fn run_verify(output: &Path, chain: &Path, root: Option<&Path>) -> Result<()> {
+    let mut entries = read_lines(chain)?.peekable();
+    let base = match entries.peek() {
+        Some(Ok(line)) => line.strip_prefix(BASE_PREFIX).map(PathBuf::from),
+        _ => None,
+    };
+    if base.is_some() {
+        entries.next();
+    }
+    let root = match (root, base) {
+        (Some(root), _) => root.to_path_buf(),
+        (None, Some(base)) => base,
+        (None, None) => return Err(anyhow!("{} has no base line, give --root", chain.display())),
+    };
+    let mut previous = GENESIS.to_string();
+    let mut file = None;
+    let mut records = 0;
+
+    for line in read_lines(output)? {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        records += 1;
+        let entry = entries.next().transpose()?;
+        if let Some(problem) = check_record(&line, entry.as_deref(), &previous, &root, &mut file) {
+            return Err(anyhow!("Chain diverges at record {}: {}", records, problem));
+        }
+        previous = entry
+            .and_then(|e| e.split(' ').next().map(str::to_string))
+            .unwrap_or_default();
+    }
+    if entries.next().is_some() {
+        return Err(anyhow!(
+            "Chain diverges after record {}: the chain has more entries than the output",
+            records
+        ));
+    }
+    println!("Verified {} records, chain head {}", records, previous);
+    Ok(())
+}
@@ -0,0 +1,154 @@
+use crate::types::ChunkData;
+use anyhow::{Context, Result, anyhow};
+use rusqlite::{Connection, OpenFlags, OptionalExtension, params};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// `--cache`: the content hash every file had when it was last chunked and the chunks it
+// gave then, so the next run can skip the files that haven't changed since
+pub struct FileCache {
+    connection: Mutex<Connection>,
+    // As the previous run left them, files chunked in this run don't update it
+    hashes: HashMap<String, String>,
+    // Send the stored chunks of unchanged files to the output again
+    replay: bool,
+    // Dry runs and --list-types look the cache up but never store to it
+    read_only: bool,
+}
+
+impl FileCache {
+    // `fingerprint` stands for the version and the options the chunks were made with,
+    // a cache written with another one is emptied, or ignored when opened read-only
+    pub fn open(path: &Path, replay: bool, fingerprint: &str) -> Result<Self> {
+        let connection = Connection::open(path)
+            .with_context(|| format!("Failed to open cache {}", path.display()))?;
+        // WAL without a sync per commit, every chunked file commits on its own
+        connection.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             PRAGMA synchronous = NORMAL;
+             CREATE TABLE IF NOT EXISTS meta (
+                 key TEXT PRIMARY KEY,
+                 value TEXT NOT NULL
+             );
+             CREATE TABLE IF NOT EXISTS cache (
+                 file_path TEXT PRIMARY KEY,
+                 file_hash TEXT NOT NULL,
+                 processed_at INTEGER NOT NULL
+             );
+             CREATE TABLE IF NOT EXISTS chunks (
+                 file_path TEXT NOT NULL,
+                 position INTEGER NOT NULL,
+                 record TEXT NOT NULL,
+                 PRIMARY KEY (file_path, position)
+             );",
+        )?;
+        if stored_fingerprint(&connection)?.as_deref() != Some(fingerprint) {
+            connection.execute_batch("DELETE FROM cache; DELETE FROM chunks;")?;
+            connection.execute(
+                "INSERT INTO meta (key, value) VALUES ('fingerprint', ?1)
+                 ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+                params![fingerprint],
+            )?;
+        }
+        Self::with_hashes(connection, replay, false)
+    }
+
+    // None when there is no cache at `path` yet, a read-only open doesn't create one
+    pub fn open_read_only(path: &Path, replay: bool, fingerprint: &str) -> Result<Option<Self>> {
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .with_context(|| format!("Failed to open cache {}", path.display()))?;
+        // Caches from before the fingerprint have no meta table and count as stale
+        if stored_fingerprint(&connection).ok().flatten().as_deref() != Some(fingerprint) {
+            return Ok(Some(FileCache {
+                connection: Mutex::new(connection),
+                hashes: HashMap::new(),
+                replay,
+                read_only: true,
+            }));
+        }
+        Self::with_hashes(connection, replay, true).map(Some)
+    }
+
+    fn with_hashes(connection: Connection, replay: bool, read_only: bool) -> Result<Self> {
+        let hashes = connection
+            .prepare("SELECT file_path, file_hash FROM cache")?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(FileCache {
+            connection: Mutex::new(connection),
+            hashes,
+            replay,
+            read_only,
+        })
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, Connection>> {
+        self.connection
+            .lock()
+            .map_err(|_| anyhow!("Cache connection poisoned by a failed write"))
+    }
+
+    pub fn is_unchanged(&self, file_path: &str, file_hash: &str) -> bool {
+        self.hashes.get(file_path).is_some_and(|hash| hash == file_hash)
+    }
+
+    pub fn replays(&self) -> bool {
+        self.replay
+    }
+
+    // Stored chunks of a file, in the order they were first sent
+    pub fn chunks(&self, file_path: &str) -> Result<Vec<ChunkData>> {
+        let connection = self.lock()?;
+        let mut select = connection
+            .prepare("SELECT record FROM chunks WHERE file_path = ?1 ORDER BY position")?;
+        let records = select
+            .query_map(params![file_path], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        records
+            .iter()
+            .map(|record| {
+                serde_json::from_str(record)
+                    .with_context(|| format!("Invalid chunk cached for {}", file_path))
+            })
+            .collect()
+    }
+
+    // Replaces the hash and the chunks stored for a file that was just chunked
+    pub fn store(&self, file_path: &str, file_hash: &str, chunks: &[ChunkData]) -> Result<()> {
+        if self.read_only {
+            return Ok(());
+        }
+        let processed_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs() as i64);
+        let mut connection = self.lock()?;
+        let transaction = connection.transaction()?;
+        transaction.execute("DELETE FROM chunks WHERE file_path = ?1", params![file_path])?;
+        {
+            let mut insert = transaction
+                .prepare("INSERT INTO chunks (file_path, position, record) VALUES (?1, ?2, ?3)")?;
+            for (position, chunk) in chunks.iter().enumerate() {
+                insert.execute(params![file_path, position as i64, serde_json::to_string(chunk)?])?;
+            }
+        }
+        transaction.execute(
+            "INSERT INTO cache (file_path, file_hash, processed_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT (file_path) DO UPDATE
+             SET file_hash = excluded.file_hash, processed_at = excluded.processed_at",
+            params![file_path, file_hash, processed_at],
+        )?;
+        transaction.commit()?;
+        Ok(())
+    }
+}
+
+fn stored_fingerprint(connection: &Connection) -> Result<Option<String>> {
+    Ok(connection
+        .query_row("SELECT value FROM meta WHERE key = 'fingerprint'", [], |row| row.get(0))
+        .optional()?)
+}
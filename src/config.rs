@@ -0,0 +1,137 @@
+use crate::lang_driver::supported_languages;
+use crate::patch::PATCH_LANGUAGE;
+use crate::types::{DEFAULT_MAX_CHUNK_TOKENS, TokenLimit};
+use anyhow::{Context, Result, anyhow};
+use serde::Deserialize;
+use serde::de::IgnoredAny;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+// Looked up in the working directory when --config isn't given
+const CONFIG_FILES: &[&str] = &[".chunker.toml", "chunker.toml"];
+
+// Settings read from chunker.toml. Keys this version doesn't use are ignored, so the
+// file `init` writes loads as is
+#[derive(Debug, Default, Deserialize)]
+pub struct Config {
+    // Used when the CLI flag of the same name isn't given
+    pub path: Option<String>,
+    pub output: Option<String>,
+    pub max_chunk_tokens: Option<usize>,
+    pub since: Option<String>,
+    // Driver names files are limited to, empty when every language is chunked
+    #[serde(default)]
+    pub languages: Vec<String>,
+    // `[language_settings.markdown]` tables, settings for one language that limit nothing
+    #[serde(default)]
+    pub language_settings: HashMap<String, LanguageSettings>,
+    // Used when --include / --exclude aren't given
+    #[serde(default)]
+    pub include_patterns: Vec<String>,
+    #[serde(default)]
+    pub exclude_patterns: Vec<String>,
+    // `[project."billing-service"]` tables, keyed by the package name a manifest declares
+    #[serde(default)]
+    pub project: HashMap<String, ProjectOverrides>,
+    // Only read to be rejected, it would look like --max-tokens, which skips items
+    // instead of splitting them
+    #[serde(default)]
+    max_tokens: Option<IgnoredAny>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LanguageSettings {
+    pub max_chunk_tokens: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectOverrides {
+    pub max_chunk_tokens: Option<usize>,
+}
+
+impl Config {
+    // The default and per-language token limits for the files of `project`. From the
+    // top: `-m` flags, the project's limit, the config's `[language_settings]` tables and its
+    // top-level limit. A default limit on one level replaces the per-language ones
+    // below it, so a project's limit applies to all of its languages
+    pub fn token_limits(
+        &self,
+        project: Option<&ProjectOverrides>,
+        cli: &[TokenLimit],
+    ) -> Result<(usize, HashMap<String, usize>)> {
+        let mut default = self.max_chunk_tokens.unwrap_or(DEFAULT_MAX_CHUNK_TOKENS);
+        let mut languages = HashMap::new();
+        for (language, settings) in &self.language_settings {
+            let language = checked_language(language, "config [language_settings]")?;
+            languages.extend(settings.max_chunk_tokens.map(|limit| (language, limit)));
+        }
+        if let Some(limit) = project.and_then(|project| project.max_chunk_tokens) {
+            default = limit;
+            languages.clear();
+        }
+        let cli_default = cli.iter().rev().find_map(|limit| match limit {
+            TokenLimit::Default(limit) => Some(*limit),
+            TokenLimit::Language(..) => None,
+        });
+        if let Some(limit) = cli_default {
+            default = limit;
+            languages.clear();
+        }
+        for limit in cli {
+            if let TokenLimit::Language(language, limit) = limit {
+                languages.insert(checked_language(language, "--max-chunk-tokens")?, *limit);
+            }
+        }
+        Ok((default, languages))
+    }
+}
+
+// Lowercased driver name, `place` names the setting in the error
+fn checked_language(language: &str, place: &str) -> Result<String> {
+    let mut valid = supported_languages();
+    valid.push(PATCH_LANGUAGE);
+    if !valid.iter().any(|name| name.eq_ignore_ascii_case(language)) {
+        return Err(anyhow!(
+            "Unknown language '{}' in {}, valid names: {}",
+            language,
+            place,
+            valid.join(", ")
+        ));
+    }
+    Ok(language.to_lowercase())
+}
+
+/// Reads the config at `path`, or the default file when there is one
+///
+/// ```
+/// let path = std::env::temp_dir().join("smart-code-chunker-doc.toml");
+/// std::fs::write(&path, "max_chunk_tokens = 256\nexclude_patterns = [\"vendor/**\"]\n").unwrap();
+/// let config = smart_code_chunker::config::load(Some(&path)).unwrap();
+/// assert_eq!(config.max_chunk_tokens, Some(256));
+/// assert_eq!(config.exclude_patterns, ["vendor/**"]);
+/// ```
+pub fn load(path: Option<&Path>) -> Result<Config> {
+    let path = match path {
+        Some(path) if !path.is_file() => {
+            return Err(anyhow!("Config file not found: {}", path.display()));
+        }
+        Some(path) => path,
+        None => match CONFIG_FILES.iter().map(Path::new).find(|p| p.is_file()) {
+            Some(path) => path,
+            None => return Ok(Config::default()),
+        },
+    };
+    let text = fs::read_to_string(path)?;
+    let config: Config =
+        toml::from_str(&text).with_context(|| format!("Invalid config file {}", path.display()))?;
+    if config.max_tokens.is_some() {
+        return Err(anyhow!(
+            "Invalid config file {}: the chunk limit is `max_chunk_tokens`, not `max_tokens`",
+            path.display()
+        ));
+    }
+    Ok(config)
+}
@@ -0,0 +1,169 @@
+use crate::files::{FileOutcome, language_of, process_file};
+use crate::safety;
+use crate::types::{ChunkData, ChunkOptions};
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tree_sitter::Parser;
+
+const EXPECTED_SUFFIX: &str = ".expected.yaml";
+
+// The parts of a chunk every driver has to agree on, one list entry per chunk in the
+// order they are written
+#[derive(Serialize, Deserialize, PartialEq)]
+struct ExpectedChunk {
+    name: String,
+    #[serde(rename = "type")]
+    chunk_type: String,
+    lines: [usize; 2],
+    context: String,
+}
+
+impl ExpectedChunk {
+    fn from_chunk(chunk: &ChunkData) -> Self {
+        ExpectedChunk {
+            name: chunk.chunk_name.clone(),
+            chunk_type: chunk.chunk_type.to_string(),
+            lines: [chunk.start_line, chunk.end_line],
+            context: chunk.context.to_string(),
+        }
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "{} {} lines {}-{} in {}",
+            self.chunk_type, self.name, self.lines[0], self.lines[1], self.context
+        )
+    }
+}
+
+// Fixture files next to their `<stem>.expected.yaml`, sorted by name
+fn fixtures(dir: &Path) -> Result<Vec<(PathBuf, PathBuf)>> {
+    let mut pairs = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| format!("can't read {}", dir.display()))? {
+        let path = entry?.path();
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if !path.is_file() || name.ends_with(EXPECTED_SUFFIX) {
+            continue;
+        }
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let expected = path.with_file_name(format!("{}{}", stem, EXPECTED_SUFFIX));
+        pairs.push((path, expected));
+    }
+    pairs.sort();
+    Ok(pairs)
+}
+
+fn chunk_fixture(path: &Path, options: &ChunkOptions) -> Result<Vec<ExpectedChunk>> {
+    let (tx, rx) = crossbeam_channel::unbounded();
+    let outcome = process_file(path, None, &mut Parser::new(), &tx, options)?;
+    if let FileOutcome::Skipped(reason) = outcome {
+        return Err(anyhow!(
+            "{} was skipped, {}",
+            path.display(),
+            reason.describe()
+        ));
+    }
+    drop(tx);
+    Ok(rx
+        .iter()
+        .map(|chunk| ExpectedChunk::from_chunk(&chunk))
+        .collect())
+}
+
+// Lines of a minimal edit script between the two lists, `-` only expected, `+` only
+// produced, two spaces in both
+fn diff_lines(expected: &[ExpectedChunk], actual: &[ExpectedChunk]) -> Vec<String> {
+    let (n, m) = (expected.len(), actual.len());
+    let mut common = vec![vec![0; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            common[i][j] = if expected[i] == actual[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::new();
+    while i < n || j < m {
+        if i < n && j < m && expected[i] == actual[j] {
+            lines.push(format!("  {}", expected[i].describe()));
+            (i, j) = (i + 1, j + 1);
+        } else if j == m || (i < n && common[i + 1][j] >= common[i][j + 1]) {
+            lines.push(format!("- {}", expected[i].describe()));
+            i += 1;
+        } else {
+            lines.push(format!("+ {}", actual[j].describe()));
+            j += 1;
+        }
+    }
+    lines
+}
+
+// Fixtures are never one whole-file chunk, and syntax errors show up as gaps
+pub fn fixture_options() -> ChunkOptions {
+    ChunkOptions {
+        small_file_threshold: 0,
+        emit_gaps: true,
+        ..ChunkOptions::default()
+    }
+}
+
+// Chunks every fixture under `dir` and compares the result with its expected file.
+// Fixtures without a compiled-in driver are skipped, `bless` rewrites the expected
+// files from the current output instead of comparing
+pub fn run_conformance(dir: &Path, bless: bool, options: &ChunkOptions) -> Result<()> {
+    let (mut passed, mut failed, mut skipped) = (0, 0, 0);
+    for (fixture, expected_path) in fixtures(dir)? {
+        let extension = fixture.extension().unwrap_or_default().to_string_lossy();
+        let Some(language) = language_of(&extension.to_lowercase()) else {
+            println!("skip {}: no driver for .{}", fixture.display(), extension);
+            skipped += 1;
+            continue;
+        };
+        let actual = chunk_fixture(&fixture, options)?;
+
+        if bless {
+            let yaml = serde_yaml::to_string(&actual)?;
+            safety::create_file("expected file", &expected_path)?.write_all(yaml.as_bytes())?;
+            println!(
+                "wrote {} ({} chunks)",
+                expected_path.display(),
+                actual.len()
+            );
+            continue;
+        }
+
+        let text = fs::read_to_string(&expected_path)
+            .with_context(|| format!("{} has no {}", fixture.display(), expected_path.display()))?;
+        let expected: Vec<ExpectedChunk> = serde_yaml::from_str(&text)
+            .with_context(|| format!("can't parse {}", expected_path.display()))?;
+        if expected == actual {
+            println!("ok   {} ({})", fixture.display(), language);
+            passed += 1;
+        } else {
+            println!("FAIL {} ({})", fixture.display(), language);
+            for line in diff_lines(&expected, &actual) {
+                println!("    {}", line);
+            }
+            failed += 1;
+        }
+    }
+
+    if bless {
+        return Ok(());
+    }
+    println!("{} passed, {} failed, {} skipped", passed, failed, skipped);
+    if failed > 0 {
+        return Err(anyhow!(
+            "{} fixtures don't match their expected chunks",
+            failed
+        ));
+    }
+    Ok(())
+}
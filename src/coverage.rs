@@ -0,0 +1,110 @@
+use crate::safety;
+use crate::types::ChunkData;
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+pub struct FileCoverage {
+    pub file_path: String,
+    pub language: &'static str,
+    pub covered_lines: usize,
+    pub total_lines: usize,
+}
+
+// Non-blank source lines that fall inside at least one chunk, gaps don't count as covered
+pub fn file_coverage(
+    file_path: String,
+    language: &'static str,
+    content: &str,
+    chunks: &[ChunkData],
+) -> FileCoverage {
+    let mut covered = vec![false; content.lines().count()];
+    for chunk in chunks.iter().filter(|c| &*c.chunk_type != "gap") {
+        let start = chunk.start_line.saturating_sub(1);
+        let end = chunk.end_line.min(covered.len());
+        if start < end {
+            covered[start..end].iter_mut().for_each(|line| *line = true);
+        }
+    }
+
+    let mut coverage = FileCoverage {
+        file_path,
+        language,
+        covered_lines: 0,
+        total_lines: 0,
+    };
+    for (line, is_covered) in content.lines().zip(covered) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        coverage.total_lines += 1;
+        coverage.covered_lines += is_covered as usize;
+    }
+    coverage
+}
+
+fn percent(covered: usize, total: usize) -> f64 {
+    if total == 0 {
+        100.0
+    } else {
+        covered as f64 * 100.0 / total as f64
+    }
+}
+
+pub fn print_summary(files: &[FileCoverage]) {
+    let mut per_language: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+    for file in files {
+        let (covered, total) = per_language.entry(file.language).or_default();
+        *covered += file.covered_lines;
+        *total += file.total_lines;
+    }
+
+    println!("Coverage (non-blank lines inside a chunk):");
+    for (language, (covered, total)) in &per_language {
+        println!(
+            "  {}: {:.1}% ({}/{})",
+            language,
+            percent(*covered, *total),
+            covered,
+            total
+        );
+    }
+    let covered: usize = files.iter().map(|f| f.covered_lines).sum();
+    let total: usize = files.iter().map(|f| f.total_lines).sum();
+    println!(
+        "  Overall: {:.1}% ({}/{})",
+        percent(covered, total),
+        covered,
+        total
+    );
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn write_report(path: &Path, files: &[FileCoverage]) -> Result<()> {
+    let mut writer = BufWriter::new(safety::create_file("--coverage-report", path)?);
+    writeln!(
+        writer,
+        "file_path,language,covered_lines,total_lines,coverage"
+    )?;
+    for file in files {
+        writeln!(
+            writer,
+            "{},{},{},{},{:.1}",
+            csv_field(&file.file_path),
+            file.language,
+            file.covered_lines,
+            file.total_lines,
+            percent(file.covered_lines, file.total_lines)
+        )?;
+    }
+    writer.flush()?;
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use crate::output::OutputMeta;
+use crate::stream::{Entry, SpillMap, hash_of, input_bytes, read_lines};
+use anyhow::Result;
+use serde_json::Value;
+use std::path::Path;
+
+const OLD: u8 = 0;
+const NEW: u8 = 1;
+
+#[derive(Default)]
+struct Summary {
+    records: [usize; 2],
+    without_id: usize,
+    added: usize,
+    removed: usize,
+    changed: usize,
+    unchanged: usize,
+}
+
+// Key on the id, value is a fingerprint of everything else in the record, so a chunk
+// that kept its id but moved lines or gained a field shows up as changed
+fn collect(path: &Path, source: u8, keys: &mut SpillMap, summary: &mut Summary) -> Result<()> {
+    for line in read_lines(path)? {
+        let line = line?;
+        let Ok(Value::Object(mut record)) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        summary.records[source as usize] += 1;
+        let Some(key) = record.remove("id").map(|id| id.to_string()) else {
+            summary.without_id += 1;
+            continue;
+        };
+        let value = hash_of(&Value::Object(record).to_string());
+        keys.push(Entry { key, source, value })?;
+    }
+    Ok(())
+}
+
+// Compares two outputs by chunk id, holding at most about `max_memory` bytes of keys
+pub fn run_diff(old: &Path, new: &Path, list: bool, max_memory: usize) -> Result<()> {
+    // Ids made from other fields or in another format never match, every chunk would
+    // show as removed and added again
+    if let (Some(old_meta), Some(new_meta)) = (OutputMeta::read(old)?, OutputMeta::read(new)?)
+        && (old_meta.id_format, &old_meta.id_fields) != (new_meta.id_format, &new_meta.id_fields)
+    {
+        eprintln!(
+            "WARNING: the ids of {} are {} and those of {} are {}, they can't match",
+            old.display(),
+            old_meta.describe(),
+            new.display(),
+            new_meta.describe()
+        );
+    }
+    let mut keys = SpillMap::new(max_memory, input_bytes(&[old, new]));
+    let mut summary = Summary::default();
+    collect(old, OLD, &mut keys, &mut summary)?;
+    collect(new, NEW, &mut keys, &mut summary)?;
+
+    // Entries of one id are next to each other, in the same order for every --max-memory
+    keys.finish(|entries| {
+        for same_id in entries.chunk_by(|a, b| a.key == b.key) {
+            let mut side = [None; 2];
+            for entry in same_id {
+                side[entry.source as usize] = Some(entry.value);
+            }
+            let id = &same_id[0].key;
+            let mark = match side {
+                [Some(_), None] => {
+                    summary.removed += 1;
+                    '-'
+                }
+                [None, Some(_)] => {
+                    summary.added += 1;
+                    '+'
+                }
+                [old, new] if old != new => {
+                    summary.changed += 1;
+                    '~'
+                }
+                _ => {
+                    summary.unchanged += 1;
+                    continue;
+                }
+            };
+            if list {
+                // Keys are the id's JSON text, string ids are printed without quotes
+                let id = serde_json::from_str::<String>(id).unwrap_or_else(|_| id.to_string());
+                println!("{} {}", mark, id);
+            }
+        }
+        Ok(())
+    })?;
+
+    println!(
+        "Records: {} old, {} new",
+        summary.records[OLD as usize], summary.records[NEW as usize]
+    );
+    if summary.without_id > 0 {
+        println!("Records without an id, ignored: {}", summary.without_id);
+    }
+    println!("Added: {}", summary.added);
+    println!("Removed: {}", summary.removed);
+    println!("Changed: {}", summary.changed);
+    println!("Unchanged: {}", summary.unchanged);
+    Ok(())
+}
@@ -0,0 +1,172 @@
+use crate::files::{FileFilter, get_files};
+use crate::lang_driver::{LanguageDriver, all_drivers, get_driver};
+use anyhow::{Result, anyhow};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsStr;
+use std::fs;
+use tree_sitter::{Parser, Query, QueryCursor};
+
+// Node kinds named in a query, `(function_item)` and `(impl_item type: (_))` style.
+// Predicates, wildcards and quoted anonymous nodes are skipped, and so is a `(` in a
+// string like the regex of a `#match?`
+fn query_node_kinds(query: &str) -> Vec<&str> {
+    let mut kinds = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    for (open, c) in query.char_indices() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        if c == '"' {
+            in_string = true;
+        }
+        if c != '(' {
+            continue;
+        }
+        let rest = &query[open + 1..];
+        let end = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        let kind = &rest[..end];
+        if !kind.is_empty() && kind != "_" && !kinds.contains(&kind) {
+            kinds.push(kind);
+        }
+    }
+    kinds
+}
+
+// Query node kinds the compiled grammar doesn't know, e.g. after a grammar renamed one
+fn missing_node_kinds(driver: &dyn LanguageDriver) -> Vec<&'static str> {
+    let language = driver.get_language();
+    query_node_kinds(driver.get_query())
+        .into_iter()
+        .filter(|kind| language.id_for_node_kind(kind, true) == 0)
+        .collect()
+}
+
+// Startup self-check of every compiled-in driver, fatal under --strict
+pub fn check_grammars(strict: bool) -> Result<()> {
+    let mut problems = Vec::new();
+    for driver in all_drivers() {
+        let missing = missing_node_kinds(driver.as_ref());
+        if !missing.is_empty() {
+            problems.push(format!(
+                "{} query names node kinds missing from its grammar: {}",
+                driver.get_name(),
+                missing.join(", ")
+            ));
+        }
+    }
+    if problems.is_empty() {
+        return Ok(());
+    }
+    if strict {
+        return Err(anyhow!(problems.join("\n")));
+    }
+    for problem in &problems {
+        eprintln!("WARNING: {}", problem);
+    }
+    Ok(())
+}
+
+// Source of every pattern in a query, by pattern index, whitespace collapsed and
+// comment lines left out
+fn query_patterns(query: &Query, source: &str) -> Vec<String> {
+    (0..query.pattern_count())
+        .map(|index| {
+            let start = query.start_byte_for_pattern(index);
+            let end = match index + 1 < query.pattern_count() {
+                true => query.start_byte_for_pattern(index + 1),
+                false => source.len(),
+            };
+            source[start..end]
+                .lines()
+                .filter(|line| !line.trim_start().starts_with(';'))
+                .flat_map(str::split_whitespace)
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect()
+}
+
+// Parses every file under `path` and reports, per language, the query patterns that
+// never matched anywhere in the corpus. Patterns, not node kinds: a kind that only
+// shows up as a field or parent of the captured node is matched along with its pattern
+pub fn run_doctor(path: &str) -> Result<()> {
+    println!("Grammar self-check:");
+    for driver in all_drivers() {
+        let missing = missing_node_kinds(driver.as_ref());
+        if missing.is_empty() {
+            println!("  {}: ok", driver.get_name());
+        } else {
+            println!(
+                "  {}: missing node kinds: {}",
+                driver.get_name(),
+                missing.join(", ")
+            );
+        }
+    }
+
+    let files = get_files(path, &None, &FileFilter::default())?;
+    let matched: Vec<(&'static str, HashMap<usize, usize>)> = files
+        .par_iter()
+        .map_init(Parser::new, |parser, file| {
+            let extension = file.extension().and_then(OsStr::to_str)?.to_lowercase();
+            let driver = get_driver(&extension)?;
+            let content = fs::read_to_string(file).ok()?;
+            parser.set_language(driver.get_language()).ok()?;
+            let tree = parser.parse(&content, None)?;
+            let query = Query::new(driver.get_language(), driver.get_query()).ok()?;
+
+            let mut counts = HashMap::new();
+            let mut cursor = QueryCursor::new();
+            for m in cursor.matches(&query, tree.root_node(), content.as_bytes()) {
+                *counts.entry(m.pattern_index).or_insert(0) += 1;
+            }
+            Some((driver.get_name(), counts))
+        })
+        .flatten()
+        .collect();
+
+    let mut per_language: BTreeMap<&str, (usize, HashMap<usize, usize>)> = BTreeMap::new();
+    for (language, counts) in matched {
+        let (file_count, totals) = per_language.entry(language).or_default();
+        *file_count += 1;
+        for (pattern, count) in counts {
+            *totals.entry(pattern).or_insert(0) += count;
+        }
+    }
+
+    println!("Query patterns that matched zero times:");
+    for driver in all_drivers() {
+        let Some((file_count, totals)) = per_language.get(driver.get_name()) else {
+            continue;
+        };
+        let Ok(query) = Query::new(driver.get_language(), driver.get_query()) else {
+            continue;
+        };
+        let unmatched: Vec<String> = query_patterns(&query, driver.get_query())
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| !totals.contains_key(index))
+            .map(|(_, pattern)| pattern)
+            .collect();
+        if unmatched.is_empty() {
+            println!("  {} ({} files): none", driver.get_name(), file_count);
+        } else {
+            println!("  {} ({} files):", driver.get_name(), file_count);
+            for pattern in unmatched {
+                println!("    {}", pattern);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,169 @@
+use crate::files::{FileFilter, driver_key, language_of};
+use crate::types::SkipReason;
+use anyhow::Result;
+use ignore::Match;
+use ignore::gitignore::Gitignore;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+enum Verdict {
+    Processed(String),
+    Skipped(SkipReason, String),
+}
+
+// Ignore files that apply to `path` the way the walker reads them, deepest first:
+// .ignore and .gitignore per directory up to the repository root, then the repo's
+// info/exclude and the user's global gitignore
+fn ignore_matchers(path: &Path) -> (Option<PathBuf>, Vec<Gitignore>) {
+    let dirs: Vec<&Path> = path.ancestors().skip(1).collect();
+    let repo_root = dirs.iter().find(|dir| dir.join(".git").exists()).copied();
+
+    let mut matchers = Vec::new();
+    for dir in &dirs {
+        matchers.push(Gitignore::new(dir.join(".ignore")).0);
+        if repo_root.is_some() {
+            matchers.push(Gitignore::new(dir.join(".gitignore")).0);
+        }
+        if Some(*dir) == repo_root {
+            break;
+        }
+    }
+    if let Some(root) = repo_root {
+        matchers.push(Gitignore::new(root.join(".git/info/exclude")).0);
+        matchers.push(Gitignore::global().0);
+    }
+    matchers.retain(|matcher| !matcher.is_empty());
+    (repo_root.map(Path::to_path_buf), matchers)
+}
+
+// Whether an ignore rule leaves an absolute `path` out of a scan, for files that only
+// show up after it like the ones --watch sees
+pub fn is_ignored(path: &Path) -> bool {
+    let (_, matchers) = ignore_matchers(path);
+    for matcher in &matchers {
+        match matcher.matched_path_or_any_parents(path, false) {
+            Match::Ignore(_) => return true,
+            Match::Whitelist(_) => return false,
+            Match::None => {}
+        }
+    }
+    false
+}
+
+// Runs the same checks a scan applies to one path and prints each of them. Globs in
+// `filter` are matched against the path relative to `root`, the --path it is under
+pub fn run_explain(path: &Path, filter: &FileFilter, root: &Path) -> Result<()> {
+    println!("Explaining {}", path.display());
+    let verdict = explain(path, filter, root);
+    match verdict {
+        Verdict::Processed(how) => println!("Verdict: processed {}", how),
+        Verdict::Skipped(reason, detail) => {
+            println!("Verdict: skipped, {} ({})", reason.describe(), detail)
+        }
+    }
+    Ok(())
+}
+
+fn explain(path: &Path, filter: &FileFilter, root: &Path) -> Verdict {
+    if !path.is_file() {
+        return Verdict::Skipped(SkipReason::NotAFile, "missing or a directory".to_string());
+    }
+    println!("  ok: is a regular file");
+    let filtered = filter.skip_reason(path, root);
+    let glob_path = path
+        .strip_prefix(root)
+        .unwrap_or(path)
+        .display()
+        .to_string();
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+
+    let (repo_root, matchers) = ignore_matchers(&path);
+    let relative = repo_root
+        .as_deref()
+        .and_then(|root| path.strip_prefix(root).ok())
+        .unwrap_or(&path);
+    let hidden = relative
+        .components()
+        .find(|c| c.as_os_str().to_string_lossy().starts_with('.'));
+    if let Some(component) = hidden {
+        return Verdict::Skipped(
+            SkipReason::Hidden,
+            format!(
+                "{} starts with a dot",
+                component.as_os_str().to_string_lossy()
+            ),
+        );
+    }
+    println!("  ok: no hidden path component");
+
+    for matcher in &matchers {
+        match matcher.matched_path_or_any_parents(&path, false) {
+            Match::Ignore(glob) => {
+                let source = glob.from().map_or_else(
+                    || matcher.path().display().to_string(),
+                    |f| f.display().to_string(),
+                );
+                return Verdict::Skipped(
+                    SkipReason::Ignored,
+                    format!("rule `{}` from {}", glob.original(), source),
+                );
+            }
+            Match::Whitelist(glob) => {
+                println!("  ok: re-included by rule `{}`", glob.original());
+                break;
+            }
+            Match::None => {}
+        }
+    }
+    println!(
+        "  ok: no ignore rule matches ({} ignore files checked)",
+        matchers.len()
+    );
+
+    let extension = driver_key(&path);
+    match filtered {
+        Some(SkipReason::LanguageNotSelected) => {
+            let language = language_of(&extension).unwrap_or("none");
+            return Verdict::Skipped(
+                SkipReason::LanguageNotSelected,
+                format!("its language is {}", language),
+            );
+        }
+        Some(reason) => {
+            return Verdict::Skipped(reason, format!("globs matched against {}", glob_path));
+        }
+        None => println!("  ok: kept by the languages, --include and --exclude settings"),
+    }
+    let Some(handler) = language_of(&extension) else {
+        return Verdict::Skipped(
+            SkipReason::UnsupportedExtension,
+            format!("extension \"{}\"", extension),
+        );
+    };
+    println!(
+        "  ok: extension \"{}\" is handled by the {} driver",
+        extension, handler
+    );
+
+    let Ok(bytes) = fs::read(&path) else {
+        return Verdict::Skipped(SkipReason::NotAFile, "could not be read".to_string());
+    };
+    let Ok(content) = String::from_utf8(bytes) else {
+        return Verdict::Skipped(
+            SkipReason::InvalidUtf8,
+            "--emit-gaps chunks it with gap records for the invalid lines".to_string(),
+        );
+    };
+    println!("  ok: valid UTF-8");
+
+    let longest_line = content
+        .lines()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+    println!(
+        "  info: longest line has {} characters, files over --wide-line-threshold are cut into token windows",
+        longest_line
+    );
+    Verdict::Processed(format!("with the {} driver", handler))
+}
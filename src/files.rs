@@ -1,16 +1,151 @@
+use crate::audit::add_content_hashes;
+use crate::coverage::{FileCoverage, file_coverage};
 use crate::git::get_git_changes;
-use crate::hash::compute_hash;
-use crate::lang_driver::get_driver;
-use crate::types::ChunkData;
-use anyhow::{Error, Result, anyhow};
+use crate::hash::{add_structural_hashes, compute_chunk_id, compute_id, disambiguate_ids};
+use crate::lang_driver::{Guard, LanguageDriver, get_driver, node_text, supported_languages};
+use crate::lsp::{LineIndex, add_lsp_ranges};
+use crate::patch::{PATCH_LANGUAGE, find_hunk_chunks, is_patch_extension};
+use crate::scan::{find_scanned_chunks, get_scan_driver};
+use crate::types::{ChunkData, ChunkOptions, ContextPart, Marker, SkipReason, SplitStrategy};
+use crate::wide::{find_window_chunks, is_wide};
+use anyhow::{Context, Error, Result, anyhow};
+use globset::{Glob, GlobSet, GlobSetBuilder};
 use ignore::WalkBuilder;
+use std::collections::{HashMap, HashSet};
 use std::ffi::OsStr;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::SystemTime;
+#[cfg(feature = "tokenize")]
 use tiktoken_rs::{CoreBPE, cl100k_base};
 use tree_sitter::{Node, Parser, Query, QueryCursor};
 
-pub fn get_files(path: &str, since: &Option<String>) -> Result<Vec<PathBuf>, Error> {
+// Which of the files found are chunked: globs over their path relative to --path, OR'd
+// within --include and within --exclude, and driver names. Without includes, every file
+// a driver handles is chunked unless an exclude matches it
+#[derive(Default)]
+pub struct FileFilter {
+    languages: Vec<String>,
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+    // Files `keeps` turned down so far, for the run summary
+    language_not_selected: AtomicUsize,
+    excluded: AtomicUsize,
+}
+
+impl FileFilter {
+    pub fn new(languages: &[String], include: &[String], exclude: &[String]) -> Result<Self> {
+        let supported = supported_languages();
+        for language in languages {
+            if !supported.iter().any(|l| l.eq_ignore_ascii_case(language)) {
+                return Err(anyhow!(
+                    "Unknown language '{}' in config languages, valid names: {}",
+                    language,
+                    supported.join(", ")
+                ));
+            }
+        }
+        Ok(FileFilter {
+            languages: languages.iter().map(|l| l.to_lowercase()).collect(),
+            include: glob_set(include)?,
+            exclude: glob_set(exclude)?,
+            ..FileFilter::default()
+        })
+    }
+
+    // Why the filter leaves a file out, None when it is chunked
+    pub fn skip_reason(&self, path: &Path, root: &Path) -> Option<SkipReason> {
+        if !self.languages.is_empty() {
+            let language = language_of(&driver_key(path)).map(str::to_lowercase);
+            if !language.is_some_and(|language| self.languages.contains(&language)) {
+                return Some(SkipReason::LanguageNotSelected);
+            }
+        }
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        let included = self
+            .include
+            .as_ref()
+            .is_none_or(|include| include.is_match(relative));
+        let excluded = self
+            .exclude
+            .as_ref()
+            .is_some_and(|exclude| exclude.is_match(relative));
+        (!included || excluded).then_some(SkipReason::Excluded)
+    }
+
+    pub fn keeps(&self, path: &Path, root: &Path) -> bool {
+        let counter = match self.skip_reason(path, root) {
+            None => return true,
+            Some(SkipReason::LanguageNotSelected) => &self.language_not_selected,
+            Some(_) => &self.excluded,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        false
+    }
+
+    // Files turned down per reason
+    pub fn skipped(&self) -> [(SkipReason, usize); 2] {
+        [
+            (SkipReason::LanguageNotSelected, self.language_not_selected.load(Ordering::Relaxed)),
+            (SkipReason::Excluded, self.excluded.load(Ordering::Relaxed)),
+        ]
+    }
+}
+
+// Lowercased extension a file's driver is looked up by. Files that go without one by
+// convention stand for theirs, `Dockerfile` and `Containerfile` for `dockerfile`
+pub fn driver_key(path: &Path) -> String {
+    match path.extension().and_then(OsStr::to_str) {
+        Some(extension) => extension.to_lowercase(),
+        None => match path.file_name().and_then(OsStr::to_str).map(str::to_lowercase) {
+            Some(name) if name == "dockerfile" || name == "containerfile" => "dockerfile".to_string(),
+            _ => String::new(),
+        },
+    }
+}
+
+// What chunks files with this lowercased extension: a tree-sitter driver, a scanner or
+// the diff chunker. None when nothing does
+pub fn language_of(extension: &str) -> Option<&'static str> {
+    get_driver(extension)
+        .map(|driver| driver.get_name())
+        .or_else(|| get_scan_driver(extension).map(|driver| driver.get_name()))
+        .or_else(|| is_patch_extension(extension).then_some(PATCH_LANGUAGE))
+}
+
+fn glob_set(patterns: &[String]) -> Result<Option<GlobSet>> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder
+            .add(Glob::new(pattern).with_context(|| format!("Invalid glob pattern: {}", pattern))?);
+    }
+    Ok(Some(builder.build()?))
+}
+
+/// Files under `path`, or those changed since a commit, that `filter` keeps
+///
+/// ```
+/// use smart_code_chunker::files::FileFilter;
+///
+/// let dir = std::env::temp_dir().join("smart-code-chunker-filter");
+/// std::fs::create_dir_all(dir.join("vendor")).unwrap();
+/// std::fs::write(dir.join("main.rs"), "fn main() {}\n").unwrap();
+/// std::fs::write(dir.join("vendor/lib.rs"), "fn lib() {}\n").unwrap();
+///
+/// let filter = FileFilter::new(&[], &["**/*.rs".to_string()], &["vendor/**".to_string()]).unwrap();
+/// let files = smart_code_chunker::get_files(dir.to_str().unwrap(), &None, &filter).unwrap();
+/// assert_eq!(files, [dir.join("main.rs")]);
+/// ```
+pub fn get_files(
+    path: &str,
+    since: &Option<String>,
+    filter: &FileFilter,
+) -> Result<Vec<PathBuf>, Error> {
     let files: Vec<PathBuf> = if let Some(commit_hash) = &since {
         println!("Smart chunker started with git hash: {}", commit_hash);
         get_git_changes(path, commit_hash)?
@@ -21,178 +156,1062 @@ pub fn get_files(path: &str, since: &Option<String>) -> Result<Vec<PathBuf>, Err
             .filter_map(|r| r.ok().map(|e| e.into_path()).filter(|p| p.is_file()))
             .collect()
     };
-    Ok(files)
+    let root = Path::new(path);
+    let files = files
+        .into_iter()
+        .filter(|file| filter.keeps(file, root))
+        .collect();
+    Ok(dedupe_files(files))
 }
 
+// Drops files that resolve to the same canonical path (symlinked roots, `.`/`..`,
+// absolute vs relative forms) and keeps the first form we saw for the output
+pub fn dedupe_files(files: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut seen = HashSet::new();
+    files
+        .into_iter()
+        .filter(|file| seen.insert(canonical_key(file)))
+        .collect()
+}
+
+fn canonical_key(path: &Path) -> PathBuf {
+    let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    // Default filesystems on Windows and macOS are case-insensitive
+    if cfg!(any(windows, target_os = "macos")) {
+        PathBuf::from(canonical.to_string_lossy().to_lowercase())
+    } else {
+        canonical
+    }
+}
+
+#[cfg(feature = "tokenize")]
 static TOKENIZER: once_cell::sync::Lazy<CoreBPE> =
     once_cell::sync::Lazy::new(|| cl100k_base().expect("Failed to load tokenizer"));
 
+// How chunk sizes are measured. A custom counter is handy to match the embedding model
+// the chunks are for
+#[derive(Clone)]
+pub enum Tokenizer {
+    #[cfg(feature = "tokenize")]
+    Cl100k,
+    // The usual ~4 characters per token estimate
+    Estimate,
+    Custom(Arc<dyn Fn(&str) -> usize + Send + Sync>),
+}
+
+impl Default for Tokenizer {
+    #[cfg(feature = "tokenize")]
+    fn default() -> Self {
+        Tokenizer::Cl100k
+    }
+
+    // Without tiktoken fall back to the estimate
+    #[cfg(not(feature = "tokenize"))]
+    fn default() -> Self {
+        Tokenizer::Estimate
+    }
+}
+
+impl Tokenizer {
+    pub fn count(&self, text: &str) -> usize {
+        match self {
+            #[cfg(feature = "tokenize")]
+            Tokenizer::Cl100k => TOKENIZER.encode_with_special_tokens(text).len(),
+            Tokenizer::Estimate => text.chars().count().div_ceil(4),
+            Tokenizer::Custom(count) => count(text),
+        }
+    }
+
+    // (tokens so far, byte offset) after every token that ends on a char boundary. A
+    // custom counter only gets to say how many tokens each word and its trailing
+    // whitespace take
+    pub fn boundaries(&self, text: &str) -> Vec<(usize, usize)> {
+        match self {
+            #[cfg(feature = "tokenize")]
+            Tokenizer::Cl100k => {
+                let mut offset = 0;
+                TOKENIZER
+                    .encode_with_special_tokens(text)
+                    .into_iter()
+                    .enumerate()
+                    .filter_map(|(index, token)| {
+                        offset += TOKENIZER._decode_native(&[token]).len();
+                        text.is_char_boundary(offset).then_some((index + 1, offset))
+                    })
+                    .collect()
+            }
+            Tokenizer::Estimate => {
+                let mut boundaries: Vec<(usize, usize)> = text
+                    .char_indices()
+                    .skip(4)
+                    .step_by(4)
+                    .enumerate()
+                    .map(|(index, (offset, _))| (index + 1, offset))
+                    .collect();
+                if !text.is_empty() {
+                    boundaries.push((self.count(text), text.len()));
+                }
+                boundaries
+            }
+            Tokenizer::Custom(count) => {
+                let (mut tokens, mut offset) = (0, 0);
+                text.split_inclusive(char::is_whitespace)
+                    .map(|word| {
+                        tokens += count(word);
+                        offset += word.len();
+                        (tokens, offset)
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+pub enum FileOutcome {
+    Chunked(Option<FileCoverage>),
+    Skipped(SkipReason),
+}
+
+/// Chunks one file and sends its chunks down `tx_sender`
+///
+/// ```
+/// use smart_code_chunker::{ChunkOptions, process_file};
+///
+/// # if smart_code_chunker::get_driver("rs").is_none() { return; }
+/// let dir = std::env::temp_dir().join("smart-code-chunker-doc");
+/// std::fs::create_dir_all(&dir).unwrap();
+/// let path = dir.join("add.rs");
+/// std::fs::write(&path, "// Adds two numbers\nfn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n").unwrap();
+///
+/// // Chunk even a file this small item by item
+/// let options = ChunkOptions { small_file_threshold: 0, ..ChunkOptions::default() };
+/// let (tx, rx) = crossbeam_channel::unbounded();
+/// let mut parser = tree_sitter::Parser::new();
+/// process_file(&path, None, &mut parser, &tx, &options).unwrap();
+/// drop(tx);
+///
+/// let chunks: Vec<_> = rx.iter().collect();
+/// assert_eq!(chunks.len(), 1);
+/// assert_eq!(chunks[0].chunk_name, "add");
+/// assert_eq!(chunks[0].comment, "// Adds two numbers");
+/// ```
 pub fn process_file(
     path: &Path,
+    project: Option<Arc<str>>,
     parser: &mut Parser,
     tx_sender: &crossbeam_channel::Sender<ChunkData>,
-    max_chunk_tokens: usize,
-) -> Result<()> {
-    let extension = path
-        .extension()
-        .and_then(OsStr::to_str)
-        .unwrap_or("")
-        .to_lowercase();
-    let driver = match get_driver(&extension) {
-        Some(driver) => driver,
-        None => {
-            println!("No driver found for file: {:?}", path);
-            return Ok(());
+    options: &ChunkOptions,
+) -> Result<FileOutcome> {
+    let extension = driver_key(path);
+    let shown = options.shown_path(path);
+    let driver = get_driver(&extension);
+    let scanner = get_scan_driver(&extension);
+    let Some(language) = language_of(&extension) else {
+        println!("No driver found for file: {:?}", shown);
+        return Ok(FileOutcome::Skipped(SkipReason::UnsupportedExtension));
+    };
+
+    let mut attempt = 0;
+    loop {
+        // Blobs of a commit can't change under us, only working tree files are stamped
+        let stamp = |path| match options.at_rev {
+            Some(_) => Ok(None),
+            None => file_stamp(path).map(Some),
+        };
+        let before = stamp(path)?;
+        let Some((content, invalid_rows)) = read_source(path, options)? else {
+            eprintln!("Skipping file that is not valid UTF-8: {:?}", shown);
+            return Ok(FileOutcome::Skipped(SkipReason::InvalidUtf8));
+        };
+        // What the cache holds for a file stands in for chunking it again
+        #[cfg(feature = "cache")]
+        let cached = options
+            .cache
+            .as_ref()
+            .map(|cache| (cache, shown.to_string_lossy(), crate::hash::compute_hash(&content)));
+        #[cfg(feature = "cache")]
+        if let Some((cache, cache_key, hash)) = &cached
+            && cache.is_unchanged(cache_key, hash)
+        {
+            if cache.replays() {
+                for chunk in cache.chunks(cache_key)? {
+                    if tx_sender.send(chunk).is_err() {
+                        break;
+                    }
+                }
+            }
+            return Ok(FileOutcome::Skipped(SkipReason::Unchanged));
+        }
+        let mut chunks = match (&driver, &scanner) {
+            _ if is_wide(&content, options.wide_line_threshold) => {
+                find_window_chunks(&shown, language, &content, options)
+            }
+            (Some(driver), _) => find_chunks(&shown, &content, driver.as_ref(), parser, options)?,
+            (None, Some(scanner)) => find_scanned_chunks(&shown, &content, scanner.as_ref(), options),
+            (None, None) => find_hunk_chunks(&shown.to_string_lossy(), &content, options),
+        };
+        disambiguate_ids(&mut chunks, options.id_format);
+        for (start_row, end_row) in invalid_rows {
+            chunks.push(gap_chunk(
+                &shown,
+                language,
+                start_row,
+                end_row,
+                "invalid UTF-8",
+                options,
+            ));
+        }
+        let mut chunks = clamp_gaps(chunks, options);
+
+        // The file was modified while we chunked it, line numbers may no longer match
+        let changed = stamp(path)? != before;
+        if changed && attempt < options.stale_retries {
+            attempt += 1;
+            continue;
+        }
+        if changed {
+            eprintln!(
+                "File changed while processing, marking chunks stale: {:?}",
+                shown
+            );
+            chunks.iter_mut().for_each(|chunk| chunk.stale = true);
+        }
+
+        for chunk in &mut chunks {
+            chunk.rev = options.at_rev.as_ref().map(|rev| rev.commit.clone());
+            chunk.project = project.clone();
+        }
+        if !options.context_filters.is_empty() {
+            chunks.retain(|chunk| matches_context_filters(chunk, &options.context_filters));
         }
+        // Gap records stand for lines that weren't chunked, they are kept whatever the filters
+        if !options.type_filters.is_empty() {
+            chunks.retain(|chunk| {
+                chunk.reason.is_some() || options.type_filters.iter().any(|t| **t == *chunk.chunk_type)
+            });
+        }
+        if options.min_tokens > 0 {
+            let before = chunks.len();
+            chunks.retain(|chunk| chunk.reason.is_some() || chunk.token_count >= options.min_tokens);
+            let dropped = before - chunks.len();
+            options.token_filtered.parts_under_min.fetch_add(dropped, Ordering::Relaxed);
+        }
+        cap_lengths(&mut chunks, options);
+        add_structural_hashes(&mut chunks);
+        if options.audit {
+            add_content_hashes(&mut chunks, &content);
+        }
+        if options.emit_normalized {
+            add_normalized_code(&mut chunks, &options.tokenizer);
+        }
+        if options.lsp_ranges {
+            add_lsp_ranges(&mut chunks, &content);
+        }
+        let coverage = options.coverage.then(|| {
+            file_coverage(
+                shown.to_string_lossy().into_owned(),
+                language,
+                &content,
+                &chunks,
+            )
+        });
+        #[cfg(feature = "cache")]
+        if let Some((cache, cache_key, hash)) = &cached {
+            cache.store(cache_key, hash, &chunks)?;
+        }
+        for chunk in chunks {
+            if tx_sender.send(chunk).is_err() {
+                break;
+            }
+        }
+        return Ok(FileOutcome::Chunked(coverage));
+    }
+}
+
+// Decoded text and the row ranges that held invalid UTF-8
+pub type Source = (String, Vec<(usize, usize)>);
+
+// Invalid UTF-8 is an error unless gaps are emitted, then it is decoded lossily.
+// None when the file isn't valid UTF-8 and gaps aren't emitted
+fn read_source(path: &Path, options: &ChunkOptions) -> Result<Option<Source>> {
+    let bytes = match &options.at_rev {
+        Some(rev) => rev.blobs.read(&rev.commit, path)?,
+        None => fs::read(path)?,
     };
+    match String::from_utf8(bytes) {
+        Ok(content) => Ok(Some((content, Vec::new()))),
+        Err(err) if options.emit_gaps => Ok(Some(decode_lossy(err.into_bytes()))),
+        Err(_) => Ok(None),
+    }
+}
+
+// Rows holding invalid bytes are returned as (start_row, end_row) ranges
+pub fn decode_lossy(bytes: Vec<u8>) -> Source {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    let mut row = 0;
+    for chunk in bytes.utf8_chunks() {
+        row += chunk.valid().matches('\n').count();
+        if chunk.invalid().is_empty() {
+            continue;
+        }
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 >= row => *end = row,
+            _ => ranges.push((row, row)),
+        }
+    }
+    (String::from_utf8_lossy(&bytes).into_owned(), ranges)
+}
+
+fn gap_chunk(
+    path: &Path,
+    language: &str,
+    start_row: usize,
+    end_row: usize,
+    reason: &str,
+    options: &ChunkOptions,
+) -> ChunkData {
+    let file_path = path.to_string_lossy();
+    let unique_content = format!("gap-{}-{}-{}", file_path, start_row, end_row);
+    ChunkData {
+        id: compute_id(&unique_content, options.id_format),
+        file_path: Arc::from(file_path.as_ref()),
+        language: Arc::from(language),
+        chunk_type: Arc::from("gap"),
+        chunk_name: "gap".to_string(),
+        context: Arc::from("root"),
+        start_line: start_row + 1,
+        end_line: end_row + 1,
+        reason: Some(reason.to_string()),
+        ..Default::default()
+    }
+}
+
+// Gap records only keep the lines no chunk covers, so a syntax error inside an item
+// that was chunked anyway leaves none. Each stretch left of a gap is a record of its
+// own, in the place of the gap
+fn clamp_gaps(chunks: Vec<ChunkData>, options: &ChunkOptions) -> Vec<ChunkData> {
+    let mut covered: Vec<(usize, usize)> = chunks
+        .iter()
+        .filter(|chunk| chunk.reason.is_none())
+        .map(|chunk| (chunk.start_line, chunk.end_line))
+        .collect();
+    covered.sort_unstable();
+    chunks
+        .into_iter()
+        .flat_map(|chunk| {
+            let Some(reason) = chunk.reason.as_deref().filter(|_| &*chunk.chunk_type == "gap") else {
+                return vec![chunk];
+            };
+            let path = Path::new(&*chunk.file_path);
+            let gap = |start: usize, end: usize| gap_chunk(path, &chunk.language, start - 1, end - 1, reason, options);
+            let mut pieces = Vec::new();
+            let mut start = chunk.start_line;
+            for &(from, to) in covered.iter().take_while(|(from, _)| *from <= chunk.end_line) {
+                if to < start {
+                    continue;
+                }
+                if from > start {
+                    pieces.push(gap(start, from - 1));
+                }
+                start = to + 1;
+            }
+            if start <= chunk.end_line {
+                pieces.push(gap(start, chunk.end_line));
+            }
+            pieces
+        })
+        .collect()
+}
+
+// Top-most ERROR nodes, the regions tree-sitter could not make sense of
+fn syntax_error_rows(root: Node) -> Vec<(usize, usize)> {
+    let mut rows = Vec::new();
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        if node.is_error() {
+            rows.push((node.start_position().row, node.end_position().row));
+        } else if node.has_error() {
+            let mut cursor = node.walk();
+            stack.extend(node.children(&mut cursor));
+        }
+    }
+    rows.sort_unstable();
+    rows
+}
 
-    let content = fs::read_to_string(path)?;
+// Modification time and size, compared before and after chunking a file
+fn file_stamp(path: &Path) -> Result<(Option<SystemTime>, u64)> {
+    let metadata = fs::metadata(path)?;
+    Ok((metadata.modified().ok(), metadata.len()))
+}
+
+pub fn find_chunks(
+    path: &Path,
+    content: &str,
+    driver: &dyn LanguageDriver,
+    parser: &mut Parser,
+    options: &ChunkOptions,
+) -> Result<Vec<ChunkData>> {
+    let mut chunks = Vec::new();
+    let max_tokens = options.max_tokens_for(driver.get_name());
     parser.set_language(driver.get_language())?;
     parser.reset();
-    let tree = parser
-        .parse(&content, None)
-        .ok_or_else(|| anyhow!("Failed to parse file"))?;
+    parser.set_timeout_micros(options.parse_timeout_ms * 1000);
+    let tree = match parser.parse(content, None) {
+        Some(tree) => tree,
+        None if options.emit_gaps => {
+            let end_row = content.lines().count().saturating_sub(1);
+            let reason = "parse failed or timed out";
+            return Ok(vec![gap_chunk(
+                path,
+                driver.get_name(),
+                0,
+                end_row,
+                reason,
+                options,
+            )]);
+        }
+        None => return Err(anyhow!("Failed to parse file")),
+    };
+    if options.emit_gaps {
+        for (start_row, end_row) in syntax_error_rows(tree.root_node()) {
+            let gap = gap_chunk(
+                path,
+                driver.get_name(),
+                start_row,
+                end_row,
+                "syntax error",
+                options,
+            );
+            chunks.push(gap);
+        }
+    }
+
+    let file_path: Arc<str> = Arc::from(path.to_string_lossy().as_ref());
+    let language: Arc<str> = Arc::from(driver.get_name());
+    let root_context: Arc<str> = Arc::from("root");
+    let mut chunk_types: HashMap<&'static str, Arc<str>> = HashMap::new();
+
+    let line_index = options.lsp_ranges.then(|| LineIndex::new(content));
+
     let mut cursor = QueryCursor::new();
     let query = Query::new(driver.get_language(), driver.get_query())?;
     let matches = cursor.matches(&query, tree.root_node(), content.as_bytes());
 
+    // Small files become one `file` chunk, the per-item captures only name its members.
+    // `--min-tokens` filters that chunk later like any other
+    let file_tokens = options.tokenizer.count(content);
+    let whole_file = file_tokens < options.small_file_threshold && file_tokens <= max_tokens;
+    let mut members = Vec::new();
+    // Captures that stand for the same wrapper, like a typedef and the struct it names,
+    // are chunked once
+    let mut chunked = HashSet::new();
+
     for m in matches {
-        for capture in m.captures {
-            let node = capture.node;
+        // Other captures only serve the query's predicates
+        for capture in m.captures.iter().filter(|capture| query.capture_names()[capture.index as usize] == "chunk") {
+            let node = driver.chunk_node(capture.node);
+            if !chunked.insert(node.id()) {
+                continue;
+            }
+            let end = driver.chunk_end(node, content);
+            let mut sibling = end.and_then(|_| node.next_sibling());
+            while let Some(next) = sibling {
+                chunked.insert(next.id());
+                sibling = next.next_sibling().filter(|_| Some(next) != end);
+            }
+            if whole_file {
+                let name = driver.chunk_name(&node, content);
+                members.push(name.unwrap_or_else(|| "anonymous".to_string()));
+                continue;
+            }
 
-            let mut context_parts = Vec::new();
+            let mut context_parts: Vec<_> =
+                driver.outer_context(&node, content).into_iter().collect();
+            let mut guards = vec![driver.extract_guards(&node, content)];
             let mut parent = node.parent();
             while let Some(p) = parent {
-                let kind = p.kind();
-                if kind.contains("class")
-                    || kind.contains("function")
-                    || kind.contains("method")
-                    || kind.contains("struct")
-                    || kind.contains("impl")
-                    || kind.contains("mod")
-                    || kind.contains("enum")
-                {
-                    let name = driver.extract_name(&p, &content).unwrap_or("?");
-                    let clean_kind = kind.replace("_item", "").replace("_definition", "");
-                    context_parts.push(format!("{}({})", clean_kind, name));
-                }
+                guards.push(driver.extract_guards(&p, content));
+                context_parts.extend(driver.context_part(&p, content));
+                context_parts.extend(driver.outer_context(&p, content));
                 parent = p.parent();
             }
 
             context_parts.reverse();
+            // Outermost guards first, keeping source order within one node
+            guards.reverse();
+            let mut cfg_conditions = Vec::new();
+            let mut platform_guards = Vec::new();
+            for guard in guards.into_iter().flatten() {
+                match guard {
+                    Guard::Cfg(condition) => cfg_conditions.push(condition),
+                    Guard::Platform(condition) => platform_guards.push(condition),
+                }
+            }
+            let is_extern = driver.is_extern(&node);
+            let attributes = driver.extract_attributes(&node, content);
+            let parameters = driver.extract_parameters(&node, content);
+            let return_type = driver.extract_return_type(&node, content);
+            let docstring = driver.extract_docstring(&node, content);
+            let is_public = driver.is_public(&node, content);
+            let is_async = driver.is_async(&node, content);
+            let is_test = driver.is_test(&node, content);
             let context = if context_parts.is_empty() {
-                "root".to_string()
+                root_context.clone()
             } else {
-                context_parts.join(" > ")
+                Arc::from(context_display(&context_parts))
             };
+            let context_parts: Arc<[ContextPart]> = Arc::from(context_parts);
+            let kind = driver.chunk_type(&node, content);
+            let chunk_type = chunk_types
+                .entry(kind)
+                .or_insert_with(|| Arc::from(kind))
+                .clone();
 
             let chunk_name = driver
-                .extract_name(&node, &content)
-                .unwrap_or("anonymous")
-                .to_string();
+                .chunk_name(&node, content)
+                .unwrap_or_else(|| "anonymous".to_string());
 
-            let raw_code_bytes = &content[node.start_byte()..node.end_byte()];
-            let comments = get_preceding_comments(&node, &content).unwrap_or_default();
+            // Indentation based grammars like Scala 3's end a block at the next line's
+            // dedent, that whitespace isn't part of the item
+            let end_byte = end.map_or(node.end_byte(), |end| end.end_byte());
+            let raw_code_bytes = content[node.start_byte()..end_byte].trim_end();
+            let (comments, comment_rows) =
+                get_preceding_comments(&node, content, driver, options.max_comment_gap)
+                    .unwrap_or((String::new(), vec![node.start_position().row]));
 
-            let signature = raw_code_bytes.lines().next().unwrap_or("").to_string();
+            let signature = driver
+                .extract_signature(&node, content)
+                .unwrap_or_else(|| raw_code_bytes.lines().next().unwrap_or(""))
+                .to_string();
 
             let full_text_for_ai = format!("{}\n{}", comments, raw_code_bytes);
+            if is_over_max_tokens(path, &chunk_name, &full_text_for_ai, options) {
+                continue;
+            }
 
-            let sub_chunks =
-                split_text_by_token_limit(&full_text_for_ai, &TOKENIZER, max_chunk_tokens);
+            // full_text_for_ai always starts with the comment block (an empty line if none)
+            let comment_lines = comments.lines().count().max(1);
+            let original_start_line = node.start_position().row + 1;
 
-            for (i, (sub_text, token_count, line_offset)) in sub_chunks.into_iter().enumerate() {
-                let unique_content = format!("{}-{}", sub_text, i);
-                let id = compute_hash(&unique_content);
+            let lookahead = match options.split_strategy {
+                SplitStrategy::Boundaries => options.split_lookahead,
+                SplitStrategy::LinesGreedy => 0,
+            };
+            let sub_chunks =
+                split_text_at_boundaries(&full_text_for_ai, max_tokens, options.overlap_tokens, &options.tokenizer, lookahead, || {
+                    let mut scores = vec![0; full_text_for_ai.lines().count()];
+                    add_boundary_scores(&node, driver, comment_lines, &mut scores);
+                    scores
+                });
+            let line_start = content[..node.start_byte()]
+                .rfind('\n')
+                .map_or(0, |i| i + 1);
+            let line_prefix = &content[line_start..node.start_byte()];
+            let item_indent = if line_prefix.trim_start().is_empty() {
+                line_prefix
+            } else {
+                ""
+            };
+            let last_code_line = comment_lines + raw_code_bytes.lines().count().max(1) - 1;
+            let to_file_line = |text_line: usize| {
+                if text_line < comment_lines {
+                    comment_rows[text_line] + 1
+                } else {
+                    original_start_line + text_line - comment_lines
+                }
+            };
 
-                let original_start_line = node.start_position().row + 1;
+            for (i, (sub_text, token_count, own_offset, overlap_lines)) in sub_chunks.into_iter().enumerate() {
+                // Lines of the text are counted from the overlap, the chunk starts after it
+                let line_offset = own_offset - overlap_lines;
+                let markers = find_markers(&sub_text, &options.markers, |l| {
+                    to_file_line(line_offset + l)
+                });
+                // Comment lines are trimmed and the item starts mid-line, everything
+                // else is a whole source line
+                let lsp_range = line_index.as_ref().map(|index| {
+                    let first = line_offset;
+                    let last = line_offset + sub_text.lines().count().max(1) - 1;
+                    let row = |t: usize| to_file_line(t) - 1;
+                    let start = match first {
+                        t if t < comment_lines && !comments.is_empty() => {
+                            index.line_text_start(row(t))
+                        }
+                        t if t <= comment_lines => node.start_byte(),
+                        t => index.line_start(row(t)),
+                    };
+                    let end = match last {
+                        t if t == last_code_line => node.start_byte() + raw_code_bytes.len(),
+                        t if t < comment_lines && comments.is_empty() => node.start_byte(),
+                        t => index.line_end(row(t)),
+                    };
+                    index.range(start, end.max(start))
+                });
+                // The comments and the item's first line lost their indent when the
+                // text was cut out of the file, put it back so dedenting stays relative
+                let code_normalized = options.emit_normalized.then(|| {
+                    let reindented: Vec<String> = sub_text
+                        .lines()
+                        .enumerate()
+                        .map(|(l, line)| match line_offset + l {
+                            t if t <= comment_lines && !line.is_empty() => {
+                                format!("{}{}", item_indent, line)
+                            }
+                            _ => line.to_string(),
+                        })
+                        .collect();
+                    normalize_code(&reindented.join("\n"))
+                });
 
-                let chunk = ChunkData {
-                    id,
-                    file_path: path.to_string_lossy().to_string(),
-                    language: driver.get_name().to_string(),
-                    chunk_type: node.kind().to_string(),
+                let mut chunk = ChunkData {
+                    file_path: file_path.clone(),
+                    language: language.clone(),
+                    chunk_type: chunk_type.clone(),
                     chunk_name: chunk_name.clone(),
                     context: context.clone(),
+                    context_parts: context_parts.clone(),
                     signature: signature.clone(),
                     comment: comments.clone(),
+                    docstring: docstring.clone(),
+                    start_line: to_file_line(own_offset),
+                    end_line: to_file_line(line_offset + sub_text.lines().count().max(1) - 1),
+                    overlap_start_line: (overlap_lines > 0).then(|| to_file_line(line_offset)),
                     code: sub_text,
-                    start_line: original_start_line + line_offset,
-                    end_line: original_start_line
-                        + line_offset
-                        + raw_code_bytes.lines().count().min(1),
+                    code_normalized,
+                    lsp_range,
                     token_count,
+                    max_tokens_applied: max_tokens,
+                    markers,
+                    cfg_conditions: cfg_conditions.clone(),
+                    is_extern,
+                    platform_guards: platform_guards.clone(),
+                    attributes: attributes.clone(),
+                    parameters: parameters.clone(),
+                    return_type: return_type.clone(),
+                    is_public,
+                    is_async,
+                    is_test,
+                    pattern_index: options.debug_fields.then_some(m.pattern_index),
+                    ..Default::default()
                 };
-
-                if tx_sender.send(chunk).is_err() {
-                    break;
-                }
+                chunk.id = compute_chunk_id(&chunk, i, options);
+                chunks.push(chunk);
             }
         }
     }
 
-    Ok(())
+    let without_items = chunked.is_empty() && driver.chunks_whole_file_without_items();
+    if (whole_file || without_items) && !content.trim().is_empty() {
+        chunks.extend(whole_file_chunks(path, content, driver.get_name(), options, members));
+    }
+
+    Ok(chunks)
 }
 
-fn split_text_by_token_limit(
-    text: &String,
-    tokenizer: &CoreBPE,
-    max_tokens: usize,
-) -> Vec<(String, usize, usize)> {
-    // (Text, TokenCount, LineOffset)
-    let encoded = tokenizer.encode_with_special_tokens(text);
-    if encoded.len() <= max_tokens {
-        return vec![(text.to_string(), encoded.len(), 0)];
+// One `file` chunk, split like any item when a file without items is over the limit
+pub fn whole_file_chunks(
+    path: &Path,
+    content: &str,
+    language: &str,
+    options: &ChunkOptions,
+    members: Vec<String>,
+) -> Vec<ChunkData> {
+    let file_path: Arc<str> = Arc::from(path.to_string_lossy().as_ref());
+    let chunk_name = path
+        .file_name()
+        .map_or(file_path.to_string(), |name| name.to_string_lossy().into_owned());
+    let max_tokens = options.max_tokens_for(language);
+    if is_over_max_tokens(path, &chunk_name, content, options) {
+        return Vec::new();
+    }
+    let parts = split_text_by_token_limit(content, max_tokens, options.overlap_tokens, &options.tokenizer);
+    parts
+        .into_iter()
+        .enumerate()
+        .map(|(i, (code, token_count, own_offset, overlap_lines))| {
+            let line_offset = own_offset - overlap_lines;
+            let mut chunk = ChunkData {
+                file_path: file_path.clone(),
+                language: Arc::from(language),
+                chunk_type: Arc::from("file"),
+                chunk_name: chunk_name.clone(),
+                context: Arc::from("root"),
+                start_line: own_offset + 1,
+                end_line: line_offset + code.lines().count().max(1),
+                overlap_start_line: (overlap_lines > 0).then_some(line_offset + 1),
+                token_count,
+                max_tokens_applied: max_tokens,
+                markers: find_markers(&code, &options.markers, |l| line_offset + l + 1),
+                code,
+                members: members.clone(),
+                ..Default::default()
+            };
+            chunk.id = compute_chunk_id(&chunk, i, options);
+            chunk
+        })
+        .collect()
+}
+
+// `--max-tokens`: an item over `--max-tokens-factor` times it is usually generated,
+// and its parts would only crowd the output. Warns and counts it when it is skipped
+pub fn is_over_max_tokens(path: &Path, name: &str, text: &str, options: &ChunkOptions) -> bool {
+    let Some(max_tokens) = options.max_tokens else {
+        return false;
+    };
+    let limit = max_tokens.saturating_mul(options.max_tokens_factor);
+    let tokens = options.tokenizer.count(text);
+    if tokens <= limit {
+        return false;
     }
+    eprintln!(
+        "WARNING: skipping {} in {} with {} tokens, over {} times --max-tokens {}",
+        name,
+        path.display(),
+        tokens,
+        options.max_tokens_factor,
+        max_tokens
+    );
+    options.token_filtered.items_over_max.fetch_add(1, Ordering::Relaxed);
+    true
+}
+
+// "kind(name) > kind(name)", with backslashes and `>` escaped in names so a `>`
+// only ever appears unescaped as the separator (`impl(Vec<Config\>)`)
+pub fn context_display(parts: &[ContextPart]) -> String {
+    parts
+        .iter()
+        .map(|part| {
+            let name = part.name.replace('\\', "\\\\").replace('>', "\\>");
+            format!("{}({})", part.kind, name)
+        })
+        .collect::<Vec<_>>()
+        .join(" > ")
+}
 
-    let mut chunks: Vec<(String, usize, usize)> = Vec::new();
-    let mut current_chunk_lines: Vec<&str> = Vec::new();
-    let mut current_tokens = 0;
-    let mut current_line_offset = 0;
+// Every `--context-filter` must match one of the chunk's ancestors
+fn matches_context_filters(chunk: &ChunkData, filters: &[ContextPart]) -> bool {
+    filters
+        .iter()
+        .all(|filter| chunk.context_parts.contains(filter))
+}
 
-    for line in text.lines() {
-        let line_len = tokenizer.encode_with_special_tokens(line).len();
-        if current_tokens + line_len + 1 > max_tokens {
-            if !current_chunk_lines.is_empty() {
-                let chunk_str = current_chunk_lines.join("\n");
-                chunks.push((chunk_str, current_tokens, current_line_offset));
-                current_line_offset += current_chunk_lines.len();
-                current_chunk_lines.clear();
-                current_tokens = 0;
+// Trailing whitespace stripped, LF line endings and the indent common to all
+// non-blank lines removed. `code` itself always stays byte-exact
+pub fn normalize_code(code: &str) -> String {
+    let lines: Vec<&str> = code.lines().map(str::trim_end).collect();
+    let mut indent: Option<&str> = None;
+    for line in lines.iter().filter(|line| !line.is_empty()) {
+        let leading = &line[..line.len() - line.trim_start().len()];
+        indent = Some(match indent {
+            None => leading,
+            Some(common) => {
+                let shared = common
+                    .char_indices()
+                    .zip(leading.chars())
+                    .find(|((_, a), b)| a != b)
+                    .map_or(common.len().min(leading.len()), |((i, _), _)| i);
+                &common[..shared]
             }
+        });
+    }
+    let indent_len = indent.map_or(0, str::len);
+    lines
+        .iter()
+        .map(|line| line.get(indent_len..).unwrap_or(""))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Fills in chunks that didn't get a normalized form while they were cut out
+pub fn add_normalized_code(chunks: &mut [ChunkData], tokenizer: &Tokenizer) {
+    for chunk in chunks {
+        let normalized = chunk
+            .code_normalized
+            .take()
+            .unwrap_or_else(|| normalize_code(&chunk.code));
+        chunk.token_count_normalized = Some(tokenizer.count(&normalized));
+        chunk.code_normalized = Some(normalized);
+    }
+}
+
+// Cuts the middle of `text` down to `max_chars` characters around a `…`, keeping at
+// least the first `keep_head` characters when they fit. None if it is short enough
+fn truncate_middle(text: &str, max_chars: usize, keep_head: usize) -> Option<String> {
+    let len = text.chars().count();
+    if max_chars == 0 || len <= max_chars {
+        return None;
+    }
+    let budget = max_chars - 1;
+    let head = keep_head.max(budget / 2).min(budget);
+    let tail = budget - head;
+    let head_text: String = text.chars().take(head).collect();
+    let tail_text: String = text.chars().skip(len - tail).collect();
+    Some(format!("{}…{}", head_text, tail_text))
+}
+
+// --max-context-chars and --max-signature-chars. The signature keeps everything up to
+// the chunk's name and its tail, where the closing paren and return type are
+pub fn cap_lengths(chunks: &mut [ChunkData], options: &ChunkOptions) {
+    for chunk in chunks {
+        if let Some(context) = truncate_middle(&chunk.context, options.max_context_chars, 0) {
+            chunk.context = Arc::from(context);
+            chunk.context_truncated = true;
+        }
+        let name_end = chunk
+            .signature
+            .find(chunk.chunk_name.as_str())
+            .map_or(0, |i| {
+                chunk.signature[..i + chunk.chunk_name.len()]
+                    .chars()
+                    .count()
+            });
+        if let Some(signature) =
+            truncate_middle(&chunk.signature, options.max_signature_chars, name_end)
+        {
+            chunk.signature = signature;
+            chunk.signature_truncated = true;
+        }
+    }
+}
+
+// Score of cutting right before each line of the chunk node: the fewer of its nodes
+// spanning the cut the higher, so cuts land between statements of the shallowest block
+// in reach and never inside a string. A comment starting the line scores one more, so
+// it stays with the code after it
+fn add_boundary_scores(
+    node: &Node,
+    driver: &dyn LanguageDriver,
+    comment_lines: usize,
+    scores: &mut [u32],
+) {
+    let first_row = node.start_position().row;
+    let rows = node.end_position().row - first_row + 1;
+    // spanning[r] - spanning[r - 1]: nodes starting before row r and ending on or after it
+    let mut spanning = vec![0i64; rows + 1];
+    let mut comment_rows = vec![false; rows];
+    let mut stack = vec![*node];
+    while let Some(current) = stack.pop() {
+        let mut cursor = current.walk();
+        for child in current.named_children(&mut cursor) {
+            let start = child.start_position().row - first_row;
+            let end = child.end_position().row - first_row;
+            if end > start {
+                spanning[start + 1] += 1;
+                spanning[end + 1] -= 1;
+            }
+            comment_rows[start] |= driver.is_comment(&child);
+            stack.push(child);
+        }
+    }
+    for row in 1..rows {
+        spanning[row] += spanning[row - 1];
+    }
+
+    let deepest = spanning[..rows].iter().copied().max().unwrap_or(0);
+    for row in 0..rows {
+        if let Some(score) = scores.get_mut(comment_lines + row) {
+            *score = 2 * (deepest - spanning[row]) as u32 + u32::from(comment_rows[row]);
         }
-        current_chunk_lines.push(line);
-        current_tokens += line_len + 1;
     }
+}
+
+pub fn split_text_by_token_limit(
+    text: &str,
+    max_tokens: usize,
+    overlap_tokens: usize,
+    tokenizer: &Tokenizer,
+) -> Vec<(String, usize, usize, usize)> {
+    split_text_at_boundaries(text, max_tokens, overlap_tokens, tokenizer, 0, Vec::new)
+}
+
+// (Text, TokenCount, LineOffset, OverlapLines) of each part. A part ends before the first
+// line over the limit, or up to `lookahead` lines earlier where cutting scores best:
+// `scores` has the score of cutting right before each line, and a blank line before the
+// cut adds one. Scores are only computed once the text turns out to be over the limit.
+// A part starts with the last lines of the previous one that fit in `overlap_tokens`,
+// counted in its tokens and OverlapLines; LineOffset is the line right after them
+pub fn split_text_at_boundaries(
+    text: &str,
+    max_tokens: usize,
+    overlap_tokens: usize,
+    tokenizer: &Tokenizer,
+    lookahead: usize,
+    scores: impl FnOnce() -> Vec<u32>,
+) -> Vec<(String, usize, usize, usize)> {
+    let total_tokens = tokenizer.count(text);
+    if total_tokens <= max_tokens {
+        return vec![(text.to_string(), total_tokens, 0, 0)];
+    }
+    let scores = if lookahead > 0 { scores() } else { Vec::new() };
+
+    // Lines keep their endings so the parts joined with `\n` are the text byte for byte,
+    // only the `\n` a part ends on is left out
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    let costs: Vec<usize> = lines
+        .iter()
+        .map(|line| tokenizer.count(line.trim_end_matches(['\n', '\r'])) + 1)
+        .collect();
+    let score_of = |cut: usize| {
+        scores.get(cut).copied().unwrap_or(0) + u32::from(lines[cut - 1].trim().is_empty())
+    };
+
+    let mut chunks: Vec<(String, usize, usize, usize)> = Vec::new();
+    let mut previous = 0;
+    let mut start = 0;
+    while start < lines.len() {
+        // The overlap never keeps the part's own first line out
+        let mut first = start;
+        let mut tokens = costs[start];
+        while first > previous && tokens + costs[first - 1] <= overlap_tokens + costs[start] {
+            first -= 1;
+            tokens += costs[first];
+        }
+        if tokens > max_tokens {
+            first = start;
+            tokens = costs[start];
+        }
 
-    if !current_chunk_lines.is_empty() {
-        chunks.push((
-            current_chunk_lines.join("\n"),
-            current_tokens,
-            current_line_offset,
-        ));
+        // First line that doesn't fit, a part always takes at least one line
+        let mut overflow = start + 1;
+        while overflow < lines.len() && tokens + costs[overflow] <= max_tokens {
+            tokens += costs[overflow];
+            overflow += 1;
+        }
+
+        // Ties go to the later cut, the fuller part
+        let mut cut = overflow;
+        if overflow < lines.len() {
+            let earliest = (start + 1).max(overflow.saturating_sub(lookahead));
+            cut = (earliest..=overflow)
+                .max_by_key(|&c| score_of(c))
+                .unwrap_or(overflow);
+        }
+        let tokens = costs[first..cut].iter().sum();
+        let mut part = lines[first..cut].concat();
+        if cut < lines.len() {
+            part.pop();
+        }
+        chunks.push((part, tokens, start, start - first));
+        previous = start;
+        start = cut;
     }
 
     chunks
 }
 
-fn get_preceding_comments(node: &Node, content: &str) -> Option<String> {
-    let mut comments: Vec<String> = Vec::new();
+pub fn find_markers(
+    text: &str,
+    markers: &[String],
+    to_file_line: impl Fn(usize) -> usize,
+) -> Vec<Marker> {
+    let mut found = Vec::new();
+    if markers.is_empty() {
+        return found;
+    }
+    for (i, line) in text.lines().enumerate() {
+        if let Some(kind) = markers.iter().find(|m| contains_word(line, m)) {
+            found.push(Marker {
+                kind: kind.clone(),
+                line: to_file_line(i),
+                text: line.trim().to_string(),
+            });
+        }
+    }
+    found
+}
+
+fn contains_word(line: &str, word: &str) -> bool {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    line.match_indices(word).any(|(start, _)| {
+        let before = line[..start].chars().next_back();
+        let after = line[start + word.len()..].chars().next();
+        !before.is_some_and(is_ident) && !after.is_some_and(is_ident)
+    })
+}
+
+// Returns the joined comment text and the file row of each of its lines, which
+// aren't consecutive when blank lines or attributes sit between the comments.
+// Walks back over comments and attributes, stopping at any other named node, at a
+// comment trailing the previous item, or at more than `max_blank_lines` blank lines.
+// The walk starts inside the driver's anchor, so a comment between a decorator and its
+// def counts too, and leaves it at any other part of it. A block at the top of the file that a blank line parts from the rest
+// is the file's header, not the item's
+fn get_preceding_comments(
+    node: &Node,
+    content: &str,
+    driver: &dyn LanguageDriver,
+    max_blank_lines: usize,
+) -> Option<(String, Vec<usize>)> {
+    let anchor = driver.comment_anchor(*node);
+    // Text, row and the row of whatever the walk came from below it
+    let mut comments: Vec<(&str, usize, usize)> = Vec::new();
+    let mut next_row = node.start_position().row;
+    let mut level = *node;
     let mut current = node.prev_sibling();
-    while let Some(sibling) = current {
-        let kind = sibling.kind();
-        if kind.contains("comment") {
-            let text = &content[sibling.start_byte()..sibling.end_byte()];
-            comments.push(text.trim().to_string());
-        } else if !kind.trim().is_empty() {
+    loop {
+        let Some(sibling) = current else {
+            match level.parent() {
+                Some(parent) if level != anchor => {
+                    level = parent;
+                    current = parent.prev_sibling();
+                    continue;
+                }
+                _ => break,
+            }
+        };
+        current = sibling.prev_sibling();
+        if !sibling.is_named() {
+            continue;
+        }
+        let blank_lines = next_row.saturating_sub(sibling.end_position().row + 1);
+        if blank_lines > max_blank_lines {
             break;
         }
-        current = sibling.prev_sibling();
+        if driver.is_comment(&sibling) {
+            let trailing = current.is_some_and(|prev| {
+                prev.is_named()
+                    && !driver.is_comment(&prev)
+                    && prev.end_position().row == sibling.start_position().row
+            });
+            if trailing {
+                break;
+            }
+            comments.push((node_text(&sibling, content).trim(), sibling.start_position().row, next_row));
+        } else if level != anchor && !driver.is_attribute(&sibling) {
+            // Past the wrapper's own parts, like the name a const binds, to what's above it
+            level = anchor;
+            current = anchor.prev_sibling();
+            next_row = anchor.start_position().row;
+            continue;
+        } else if !driver.is_attribute(&sibling) {
+            break;
+        }
+        next_row = sibling.start_position().row;
+    }
+    // Walked bottom up, the header is the end of the list and ends at its first blank line
+    if comments.last().is_some_and(|(_, row, _)| *row == 0) {
+        let mut top = comments.len() - 1;
+        loop {
+            let (text, row, below) = comments[top];
+            if below > row + text.lines().count().max(1) {
+                comments.truncate(top);
+                break;
+            }
+            if top == 0 || comments[top - 1].1 != below {
+                break;
+            }
+            top -= 1;
+        }
     }
     if comments.is_empty() {
-        None
-    } else {
-        comments.reverse();
-        Some(comments.join("\n"))
+        return None;
     }
+    comments.reverse();
+    let rows = comments
+        .iter()
+        .flat_map(|(text, row, _)| (0..text.lines().count().max(1)).map(move |line| row + line))
+        .collect();
+    let text: Vec<&str> = comments.iter().map(|(text, _, _)| *text).collect();
+    Some((text.join("\n"), rows))
 }
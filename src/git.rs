@@ -1,6 +1,8 @@
 use anyhow::{Context, Error, anyhow};
+use std::io::{self, BufRead, BufReader, Read, Write};
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::Mutex;
 
 pub fn get_git_changes(path: &str, since_commit: &str) -> anyhow::Result<Vec<PathBuf>, Error> {
     let output = Command::new("git")
@@ -29,3 +31,162 @@ pub fn get_git_changes(path: &str, since_commit: &str) -> anyhow::Result<Vec<Pat
 
     Ok(files)
 }
+
+// Runs git in `repo` and returns its stdout, stderr becomes the error
+fn run_git(repo: &Path, args: &[&str]) -> anyhow::Result<Vec<u8>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .output()
+        .context("Git command could not run. Check git is present?")?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Git error: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(output.stdout)
+}
+
+pub fn resolve_commit(repo: &Path, rev: &str) -> anyhow::Result<String> {
+    let commit = format!("{}^{{commit}}", rev);
+    let stdout = run_git(repo, &["rev-parse", "--verify", &commit])?;
+    Ok(String::from_utf8(stdout)?.trim().to_string())
+}
+
+// Files as they are at `commit`, relative to `repo`. With `since` only the ones that
+// changed between the two revisions and still exist at `commit`
+pub fn files_at_commit(
+    repo: &Path,
+    commit: &str,
+    since: Option<&str>,
+) -> anyhow::Result<Vec<PathBuf>> {
+    let stdout = match since {
+        Some(since) => run_git(
+            repo,
+            &[
+                "diff",
+                "--name-only",
+                "-z",
+                "--relative",
+                "--diff-filter=d",
+                since,
+                commit,
+            ],
+        )?,
+        None => run_git(repo, &["ls-tree", "-r", "-z", "--name-only", commit])?,
+    };
+    Ok(String::from_utf8(stdout)?
+        .split('\0')
+        .filter(|name| !name.is_empty())
+        .map(PathBuf::from)
+        .collect())
+}
+
+fn blob_object(commit: &str, path: &Path) -> String {
+    format!("{}:./{}", commit, path.to_string_lossy())
+}
+
+// Contents straight from the object database, whatever the working tree holds
+pub fn read_blob(repo: &Path, commit: &str, path: &Path) -> anyhow::Result<Vec<u8>> {
+    run_git(repo, &["cat-file", "blob", &blob_object(commit, path)])
+}
+
+// `git cat-file --batch`: object names go in on stdin, each comes back as a
+// `<oid> <type> <size>` line, the contents and a newline
+struct Batch {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl Batch {
+    fn spawn(repo: &Path) -> anyhow::Result<Self> {
+        let mut child = Command::new("git")
+            .arg("-C")
+            .arg(repo)
+            .args(["cat-file", "--batch"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .context("Git command could not run. Check git is present?")?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+        Ok(Batch { child, stdin, stdout })
+    }
+
+    // The outer error leaves the reply half read, the inner one is git's answer, like a
+    // missing file, and the next object can follow it
+    fn read(&mut self, object: &str) -> io::Result<Result<Vec<u8>, String>> {
+        writeln!(self.stdin, "{}", object)?;
+        self.stdin.flush()?;
+        let mut header = String::new();
+        if self.stdout.read_line(&mut header)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "cat-file --batch exited"));
+        }
+        let header = header.trim_end();
+        // `<object> missing` and `<object> ambiguous` have no contents, the name can
+        // hold spaces
+        if header.ends_with(" missing") || header.ends_with(" ambiguous") {
+            return Ok(Err(header.to_string()));
+        }
+        let [_, kind, size] = header.split(' ').collect::<Vec<_>>()[..] else {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, header));
+        };
+        let size: usize = size.parse().map_err(|_| io::Error::new(io::ErrorKind::InvalidData, header))?;
+        let mut contents = vec![0; size + 1];
+        self.stdout.read_exact(&mut contents)?;
+        contents.pop();
+        match kind {
+            "blob" => Ok(Ok(contents)),
+            _ => Ok(Err(format!("{} is a {}", object, kind))),
+        }
+    }
+}
+
+impl Drop for Batch {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+// Reads the blobs of a `--at-rev` run through one `git cat-file --batch` process instead
+// of a process per file. Workers take turns on it, it starts on the first read and again
+// after an error left it out of step
+pub struct BlobReader {
+    repo: PathBuf,
+    batch: Mutex<Option<Batch>>,
+}
+
+impl BlobReader {
+    pub fn new(repo: &Path) -> Self {
+        BlobReader {
+            repo: repo.to_path_buf(),
+            batch: Mutex::new(None),
+        }
+    }
+
+    pub fn read(&self, commit: &str, path: &Path) -> anyhow::Result<Vec<u8>> {
+        let object = blob_object(commit, path);
+        // Names are sent a line each, one with a newline needs a process of its own
+        if object.contains('\n') {
+            return read_blob(&self.repo, commit, path);
+        }
+        let mut batch = self.batch.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let reply = match &mut *batch {
+            Some(running) => running.read(&object),
+            None => batch.insert(Batch::spawn(&self.repo)?).read(&object),
+        };
+        match reply {
+            Ok(answer) => answer.map_err(|answer| anyhow!("Git error: {}", answer)),
+            Err(err) => {
+                *batch = None;
+                Err(anyhow!("Git error reading {}: {}", object, err))
+            }
+        }
+    }
+}
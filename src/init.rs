@@ -0,0 +1,281 @@
+use crate::safety;
+use anyhow::{Result, anyhow};
+use clap::ValueEnum;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Target {
+    /// Qdrant collection, points keyed by UUID
+    Qdrant,
+    /// Postgres table with a pgvector column
+    Pgvector,
+    /// Plain JSONL for any other pipeline
+    Jsonl,
+}
+
+// One option in a preset, keyed by the CLI flag name without dashes (`id-format`)
+struct Setting {
+    flag: &'static str,
+    value: &'static str,
+    comment: &'static str,
+}
+
+struct Preset {
+    settings: &'static [Setting],
+    script_name: &'static str,
+    script: &'static str,
+}
+
+const OUTPUT: Setting = Setting {
+    flag: "output",
+    value: "chunks.jsonl",
+    comment: "Where chunks are written, the ingest script reads this file",
+};
+
+const QDRANT: Preset = Preset {
+    settings: &[
+        OUTPUT,
+        Setting {
+            flag: "id-format",
+            value: "uuid",
+            comment: "Qdrant point ids must be UUIDs or unsigned integers",
+        },
+        Setting {
+            flag: "max-chunk-tokens",
+            value: "512",
+            comment: "Fits the input window of most sentence embedding models",
+        },
+        Setting {
+            flag: "small-file-threshold",
+            value: "300",
+            comment: "Tiny files become one chunk instead of many near-empty ones",
+        },
+    ],
+    script_name: "ingest.py",
+    script: QDRANT_SCRIPT,
+};
+
+const PGVECTOR: Preset = Preset {
+    settings: &[
+        OUTPUT,
+        Setting {
+            flag: "id-format",
+            value: "hex",
+            comment: "Stored in a text primary key, re-runs upsert unchanged chunks in place",
+        },
+        Setting {
+            flag: "max-chunk-tokens",
+            value: "512",
+            comment: "Fits the input window of most sentence embedding models",
+        },
+        Setting {
+            flag: "small-file-threshold",
+            value: "300",
+            comment: "Tiny files become one chunk instead of many near-empty ones",
+        },
+    ],
+    script_name: "ingest.py",
+    script: PGVECTOR_SCRIPT,
+};
+
+const JSONL: Preset = Preset {
+    settings: &[
+        OUTPUT,
+        Setting {
+            flag: "id-format",
+            value: "hex",
+            comment: "sha256 of the chunk, the most collision resistant format",
+        },
+        Setting {
+            flag: "max-chunk-tokens",
+            value: "800",
+            comment: "Default budget per chunk, override per language with LANG=N",
+        },
+    ],
+    script_name: "ingest.sh",
+    script: JSONL_SCRIPT,
+};
+
+fn preset(target: Target) -> &'static Preset {
+    match target {
+        Target::Qdrant => &QDRANT,
+        Target::Pgvector => &PGVECTOR,
+        Target::Jsonl => &JSONL,
+    }
+}
+
+// The CLI flags a preset stands for, used both in the scripts and to validate it
+fn preset_flags(preset: &Preset) -> Vec<String> {
+    preset
+        .settings
+        .iter()
+        .flat_map(|setting| [format!("--{}", setting.flag), setting.value.to_string()])
+        .collect()
+}
+
+// TOML keys mirror the CLI flags with underscores, numbers stay bare
+fn render_config(target: Target, preset: &Preset) -> String {
+    let mut config = format!(
+        "# smart-chunker settings for {:?}, generated by `smart-chunker init`\n# Every key mirrors the CLI flag of the same name\n",
+        target
+    );
+    for setting in preset.settings {
+        let value = if setting.value.parse::<u64>().is_ok() {
+            setting.value.to_string()
+        } else {
+            format!("\"{}\"", setting.value)
+        };
+        config.push_str(&format!(
+            "\n# {}\n{} = {}\n",
+            setting.comment,
+            setting.flag.replace('-', "_"),
+            value
+        ));
+    }
+    config
+}
+
+// `validate` parses the preset as a real command line, so a renamed flag or an
+// invalid value breaks `init` instead of the user's first run
+pub fn run_init(
+    target: Target,
+    dir: &Path,
+    force: bool,
+    validate: impl Fn(&[String]) -> Result<()>,
+) -> Result<()> {
+    let preset = preset(target);
+    let flags = preset_flags(preset);
+    validate(&flags).map_err(|err| anyhow!("The {:?} preset is out of date: {}", target, err))?;
+
+    let files = [
+        ("chunker.toml", render_config(target, preset)),
+        (
+            preset.script_name,
+            preset.script.replace("{flags}", &flags.join(" ")),
+        ),
+    ];
+    for (name, _) in &files {
+        let path = dir.join(name);
+        if path.exists() && !force {
+            return Err(anyhow!(
+                "{} already exists, pass --force to overwrite it",
+                path.display()
+            ));
+        }
+    }
+
+    safety::create_dir_all("--dir", dir)?;
+    for (name, content) in &files {
+        let path = dir.join(name);
+        safety::create_file("--dir", &path)?.write_all(content.as_bytes())?;
+        println!("Wrote {}", path.display());
+    }
+    if preset.script_name.ends_with(".sh") {
+        make_executable(&dir.join(preset.script_name))?;
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+const QDRANT_SCRIPT: &str = r#"#!/usr/bin/env python3
+# Chunks a project and loads the chunks into a Qdrant collection.
+# pip install qdrant-client sentence-transformers
+import json
+import subprocess
+import sys
+
+from qdrant_client import QdrantClient
+from qdrant_client.models import Distance, PointStruct, VectorParams
+from sentence_transformers import SentenceTransformer
+
+PROJECT = sys.argv[1] if len(sys.argv) > 1 else "."
+COLLECTION = "code_chunks"
+
+subprocess.run(["smart-chunker", "--path", PROJECT, *"{flags}".split()], check=True)
+
+model = SentenceTransformer("all-MiniLM-L6-v2")
+client = QdrantClient(url="http://localhost:6333")
+if not client.collection_exists(COLLECTION):
+    client.create_collection(
+        COLLECTION,
+        vectors_config=VectorParams(size=model.get_sentence_embedding_dimension(), distance=Distance.COSINE),
+    )
+
+with open("chunks.jsonl") as f:
+    chunks = [json.loads(line) for line in f]
+
+for start in range(0, len(chunks), 256):
+    batch = chunks[start:start + 256]
+    vectors = model.encode([c["context"] + "\n" + c["code"] for c in batch])
+    client.upsert(
+        COLLECTION,
+        points=[
+            PointStruct(id=c["id"], vector=v.tolist(), payload={k: c[k] for k in ("file_path", "language", "chunk_type", "chunk_name", "context", "start_line", "end_line", "code")})
+            for c, v in zip(batch, vectors)
+        ],
+    )
+print(f"Upserted {len(chunks)} chunks into {COLLECTION}")
+"#;
+
+const PGVECTOR_SCRIPT: &str = r#"#!/usr/bin/env python3
+# Chunks a project and loads the chunks into a Postgres table with pgvector.
+# pip install "psycopg[binary]" pgvector sentence-transformers
+import json
+import subprocess
+import sys
+
+import psycopg
+from pgvector.psycopg import register_vector
+from sentence_transformers import SentenceTransformer
+
+PROJECT = sys.argv[1] if len(sys.argv) > 1 else "."
+DSN = "postgresql://localhost/postgres"
+
+subprocess.run(["smart-chunker", "--path", PROJECT, *"{flags}".split()], check=True)
+
+model = SentenceTransformer("all-MiniLM-L6-v2")
+with psycopg.connect(DSN, autocommit=True) as conn:
+    conn.execute("CREATE EXTENSION IF NOT EXISTS vector")
+    register_vector(conn)
+    conn.execute(
+        f"""CREATE TABLE IF NOT EXISTS code_chunks (
+            id text PRIMARY KEY,
+            file_path text, language text, chunk_type text, chunk_name text,
+            context text, start_line int, end_line int, code text,
+            embedding vector({model.get_sentence_embedding_dimension()}))"""
+    )
+
+    with open("chunks.jsonl") as f:
+        chunks = [json.loads(line) for line in f]
+    vectors = model.encode([c["context"] + "\n" + c["code"] for c in chunks])
+    with conn.cursor() as cur:
+        for c, v in zip(chunks, vectors):
+            cur.execute(
+                """INSERT INTO code_chunks VALUES (%s, %s, %s, %s, %s, %s, %s, %s, %s, %s)
+                   ON CONFLICT (id) DO UPDATE SET file_path = EXCLUDED.file_path,
+                   start_line = EXCLUDED.start_line, end_line = EXCLUDED.end_line""",
+                (c["id"], c["file_path"], c["language"], c["chunk_type"], c["chunk_name"],
+                 c["context"], c["start_line"], c["end_line"], c["code"], v),
+            )
+print(f"Upserted {len(chunks)} chunks into code_chunks")
+"#;
+
+const JSONL_SCRIPT: &str = r#"#!/bin/sh
+# Chunks a project into chunks.jsonl and checks the output before handing it on.
+set -e
+smart-chunker --path "${1:-.}" {flags}
+smart-chunker lint chunks.jsonl
+"#;
@@ -0,0 +1,178 @@
+use crate::files::{Source, Tokenizer};
+use crate::types::{ChunkData, ChunkOptions, IdField, IdFormat, SplitStrategy};
+use anyhow::{Result, anyhow};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+// Properties every output has to hold whatever the input, checked by the fuzz targets
+// in `fuzz/`
+
+fn check(holds: bool, violation: impl FnOnce() -> String) -> Result<()> {
+    if holds { Ok(()) } else { Err(anyhow!(violation())) }
+}
+
+// Options the pipeline is checked with: items are always chunked on their own and every
+// optional field that holds a range is filled in, so its offsets get checked too
+pub fn check_options(max_chunk_tokens: usize) -> ChunkOptions {
+    ChunkOptions {
+        max_chunk_tokens,
+        language_max_tokens: HashMap::new(),
+        id_format: IdFormat::Hex,
+        id_fields: vec![IdField::Content, IdField::Path, IdField::Part],
+        markers: vec!["TODO".to_string()],
+        stale_retries: 0,
+        emit_gaps: true,
+        // Some grammars never finish on some garbage, that file is a gap then
+        parse_timeout_ms: 1000,
+        max_comment_gap: 1,
+        coverage: false,
+        context_filters: Vec::new(),
+        small_file_threshold: 0,
+        emit_normalized: true,
+        debug_fields: false,
+        wide_line_threshold: 0,
+        lsp_ranges: true,
+        max_context_chars: 512,
+        max_signature_chars: 512,
+        split_strategy: SplitStrategy::Boundaries,
+        split_lookahead: 8,
+        overlap_tokens: 0,
+        at_rev: None,
+        print_paths: None,
+        audit: false,
+        path_base: PathBuf::new(),
+        tokenizer: Tokenizer::default(),
+        #[cfg(feature = "cache")]
+        cache: None,
+        min_tokens: 0,
+        max_tokens: None,
+        max_tokens_factor: 10,
+        token_filtered: Arc::default(),
+        type_filters: Vec::new(),
+    }
+}
+
+// Parts of `split_text_by_token_limit`: without their overlap and joined with `\n` they
+// are the text, their line offsets follow each other, and only a part of a single line
+// goes over the limit. The overlap is the end of the previous part and fits in
+// `overlap_tokens`. A text within the limit is one part with the counter's count, a split
+// one is counted per line plus the newline
+pub fn check_split(
+    text: &str,
+    max_tokens: usize,
+    overlap_tokens: usize,
+    tokenizer: &Tokenizer,
+    parts: &[(String, usize, usize, usize)],
+) -> Result<()> {
+    let total = tokenizer.count(text);
+    if total <= max_tokens {
+        let whole = matches!(parts, [(part, tokens, 0, 0)] if part == text && *tokens == total);
+        return check(whole, || format!("text of {} tokens within the limit came out as {:?}", total, parts));
+    }
+
+    let cost = |line: &str| tokenizer.count(line.trim_end_matches(['\n', '\r'])) + 1;
+    let mut joined = String::new();
+    let mut previous: Vec<String> = Vec::new();
+    let mut expected_offset = 0;
+    for (index, (part, tokens, offset, overlap)) in parts.iter().enumerate() {
+        check(*offset == expected_offset, || {
+            format!("part {} starts at line {}, expected {}", index, offset, expected_offset)
+        })?;
+        // With the `\n` joining it to the next part back on
+        let part = if index + 1 < parts.len() { format!("{}\n", part) } else { part.clone() };
+        let lines: Vec<&str> = part.split_inclusive('\n').collect();
+        check(*overlap < lines.len() && *overlap <= previous.len(), || {
+            format!("part {} overlaps {} lines, it has {} and the previous one {}", index, overlap, lines.len(), previous.len())
+        })?;
+        let (carried, own) = lines.split_at(*overlap);
+        let tail = &previous[previous.len() - carried.len()..];
+        check(tail.iter().zip(carried).all(|(a, b)| a == b), || format!("part {} overlap isn't the end of the previous part", index))?;
+        let carried_tokens: usize = carried.iter().map(|line| cost(line)).sum();
+        check(carried_tokens <= overlap_tokens, || {
+            format!("part {} overlap of {} tokens is over {}", index, carried_tokens, overlap_tokens)
+        })?;
+
+        let counted: usize = lines.iter().map(|line| cost(line)).sum();
+        check(*tokens == counted, || format!("part {} counted {} tokens, lines add up to {}", index, tokens, counted))?;
+        check(*tokens <= max_tokens || lines.len() == 1, || {
+            format!("part {} has {} lines and {} tokens over the limit of {}", index, lines.len(), tokens, max_tokens)
+        })?;
+        joined.extend(own.iter().copied());
+        expected_offset += own.len();
+        previous = own.iter().map(|line| line.to_string()).collect();
+    }
+    check(joined == text, || format!("parts joined are {:?}, not {:?}", joined, text))
+}
+
+// Invalid rows of `decode_lossy` are ascending, apart and within the decoded text
+pub fn check_decoded(source: &Source) -> Result<()> {
+    let (content, ranges) = source;
+    let rows = content.split('\n').count();
+    let mut next_row = 0;
+    for &(start, end) in ranges {
+        check(start >= next_row && start <= end && end < rows, || {
+            format!("invalid rows {}-{} out of order or past row {}", start, end, rows - 1)
+        })?;
+        next_row = end + 2;
+    }
+    Ok(())
+}
+
+// Chunks of one file as `find_chunks` returns them
+pub fn check_chunks(content: &str, chunks: &[ChunkData]) -> Result<()> {
+    // Rows as tree-sitter counts them, a trailing newline starts one more
+    let rows = content.split('\n').count();
+    for chunk in chunks {
+        let name = format!("{} {} lines {}-{}", chunk.chunk_type, chunk.chunk_name, chunk.start_line, chunk.end_line);
+        check(1 <= chunk.start_line && chunk.start_line <= chunk.end_line && chunk.end_line <= rows, || {
+            format!("{} out of the file's {} lines", name, rows)
+        })?;
+        check(chunk.token_count <= chunk.max_tokens_applied || chunk.code.lines().count() <= 1, || {
+            format!("{} has {} tokens over the limit of {}", name, chunk.token_count, chunk.max_tokens_applied)
+        })?;
+        if let Some(range) = &chunk.lsp_range {
+            let ordered = (range.start.line, range.start.character) <= (range.end.line, range.end.character);
+            check(ordered && range.end.line < rows, || format!("{} has LSP range {:?}", name, range))?;
+        }
+        for marker in &chunk.markers {
+            check(chunk.start_line <= marker.line && marker.line <= chunk.end_line, || {
+                format!("{} has a marker on line {}", name, marker.line)
+            })?;
+        }
+    }
+
+    // Parts of one item are written in a row and share all but their code and lines
+    let items = chunks.chunk_by(|a, b| {
+        (&a.chunk_type, &a.chunk_name, &a.context, &a.signature)
+            == (&b.chunk_type, &b.chunk_name, &b.context, &b.signature)
+    });
+    let is_slice = |parts: &[ChunkData]| {
+        let joined = parts.iter().map(|part| part.code.as_str()).collect::<Vec<_>>().join("\n");
+        let source = match &*parts[0].chunk_type {
+            "file" => Some(joined.as_str()),
+            _ => joined.strip_prefix(parts[0].comment.as_str()).and_then(|rest| rest.strip_prefix('\n')),
+        };
+        source.is_some_and(|source| content.contains(source))
+    };
+    for parts in items.filter(|parts| parts[0].reason.is_none()) {
+        let name = format!("{} {}", parts[0].chunk_type, parts[0].chunk_name);
+        for pair in parts.windows(2) {
+            check(pair[1].start_line >= pair[0].end_line, || {
+                format!("{} part at line {} starts before the previous one ends", name, pair[1].start_line)
+            })?;
+        }
+        // Items that look the same, like two anonymous declarations in a row, can't be
+        // told from the parts of one. Each part starting with the comment block, or
+        // holding just that, starts an item of its own then
+        let comment = &parts[0].comment;
+        let starts_item = |part: &ChunkData| {
+            part.code == *comment || part.code.strip_prefix(comment.as_str()).is_some_and(|rest| rest.starts_with('\n'))
+        };
+        let separate = || parts.chunk_by(|_, next| !starts_item(next)).all(is_slice);
+        check(is_slice(parts) || separate(), || {
+            format!("{} parts joined aren't its comment and a slice of the file", name)
+        })?;
+    }
+    Ok(())
+}
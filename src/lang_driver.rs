@@ -1,18 +1,108 @@
+use crate::types::ContextPart;
 use tree_sitter::{Language, Node};
 
+// A condition that decides whether a chunk is compiled or run at all
+pub enum Guard {
+    Cfg(String),
+    Platform(String),
+}
+
 pub trait LanguageDriver: Send + Sync {
     fn get_language(&self) -> Language;
     fn get_query(&self) -> &'static str;
     fn get_name(&self) -> &'static str;
     fn extract_name<'a>(&self, node: &Node, content: &'a str) -> Option<&'a str>;
+
+    // `chunk_name` of the node's own chunk, where it may say more than the name the node
+    // goes by in other chunks' context
+    fn chunk_name(&self, node: &Node, content: &str) -> Option<String> {
+        self.extract_name(node, content).map(str::to_string)
+    }
+
+    // Called for the chunk node and each of its ancestors during the context walk
+    fn extract_guards(&self, _node: &Node, _content: &str) -> Vec<Guard> { Vec::new() }
+    fn is_extern(&self, _node: &Node) -> bool { false }
+    fn extract_attributes(&self, _node: &Node, _content: &str) -> Vec<String> { Vec::new() }
+    // Each parameter of a function as written, type and default included
+    fn extract_parameters(&self, _node: &Node, _content: &str) -> Vec<String> { Vec::new() }
+    fn extract_return_type(&self, _node: &Node, _content: &str) -> Option<String> { None }
+    // Documentation written inside the item, like a Python docstring, apart from `comment`
+    fn extract_docstring(&self, _node: &Node, _content: &str) -> Option<String> { None }
+    // None for drivers that don't tell, the fields are left out of their chunks then
+    fn is_public(&self, _node: &Node, _content: &str) -> Option<bool> { None }
+    fn is_async(&self, _node: &Node, _content: &str) -> Option<bool> { None }
+    fn is_test(&self, _node: &Node, _content: &str) -> Option<bool> { None }
+
+    // Preceding comment attachment: comments are collected walking back from the anchor,
+    // skipping attributes, and any other named node ends the walk
+    fn is_comment(&self, node: &Node) -> bool { node.kind().contains("comment") }
+    fn is_attribute(&self, _node: &Node) -> bool { false }
+    fn comment_anchor<'t>(&self, node: Node<'t>) -> Node<'t> { node }
+
+    // Label of an ancestor in the chunk's context, None when it isn't a named scope. The
+    // root is never one, Python's is a `module`
+    fn context_kind(&self, node: &Node) -> Option<String> {
+        let kind = node.kind();
+        let scope = ["class", "function", "method", "struct", "impl", "mod", "enum"]
+            .iter()
+            .any(|scope| kind.contains(scope));
+        let scope = scope && node.parent().is_some();
+        scope.then(|| kind.replace("_item", "").replace("_definition", ""))
+    }
+
+    // Part an ancestor adds to the context, for grammars where telling scopes apart or
+    // naming them takes more than the node kind and a single name
+    fn context_part(&self, node: &Node, content: &str) -> Option<ContextPart> {
+        let kind = self.context_kind(node)?;
+        let name = self.extract_name(node, content).unwrap_or("?");
+        Some(ContextPart { kind, name: name.to_string() })
+    }
+
+    // Scope a node belongs to without being nested in it, placed right outside the
+    // node in the context, like the receiver type of a Go method
+    fn outer_context(&self, _node: &Node, _content: &str) -> Option<ContextPart> { None }
+
+    // Node a captured item is chunked as, a wrapper that holds part of its text like the
+    // `template <...>` line of a C++ template
+    fn chunk_node<'t>(&self, node: Node<'t>) -> Node<'t> { node }
+
+    // Last of the siblings after the node that belong to the same item, like the other
+    // equations of a Haskell function. Those siblings aren't chunked on their own
+    fn chunk_end<'t>(&self, _node: Node<'t>, _content: &str) -> Option<Node<'t>> { None }
+
+    // Whether a file the query captures nothing in is chunked whole rather than left out,
+    // for languages mostly written as plain top-level commands
+    fn chunks_whole_file_without_items(&self) -> bool { false }
+
+    // `chunk_type` of the node's chunk, its kind unless a modifier changes what it is
+    fn chunk_type(&self, node: &Node, _content: &str) -> &'static str { node.kind() }
+
+    // Declaration part of the chunk's code, None for its first line
+    fn extract_signature<'a>(&self, _node: &Node, _content: &'a str) -> Option<&'a str> { None }
 }
 
+pub fn node_text<'a>(node: &Node, content: &'a str) -> &'a str {
+    &content[node.start_byte()..node.end_byte()]
+}
 
+// Named children of a parameter list, without comments and the `skipped` kinds
+#[cfg(any(feature = "lang-rust", feature = "lang-python"))]
+fn parameter_texts(parameters: &Node, content: &str, skipped: &[&str]) -> Vec<String> {
+    let mut cursor = parameters.walk();
+    parameters
+        .named_children(&mut cursor)
+        .filter(|p| !p.kind().contains("comment") && !skipped.contains(&p.kind()))
+        .map(|p| node_text(&p, content).to_string())
+        .collect()
+}
+
+#[cfg(feature = "lang-rust")]
 struct RustDriver;
+#[cfg(feature = "lang-rust")]
 impl LanguageDriver for RustDriver {
     fn get_language(&self) -> Language { tree_sitter_rust::language() }
     fn get_query(&self) -> &'static str {
-        r#"[ (function_item) (struct_item) (impl_item) (mod_item) ] @chunk"#
+        r#"[ (function_item) (struct_item) (enum_item) (trait_item) (type_item) (const_item) (static_item) (impl_item) (mod_item) (foreign_mod_item) (macro_definition) ] @chunk"#
     }
     fn get_name(&self) -> &'static str { "Rust" }
 
@@ -21,16 +111,174 @@ impl LanguageDriver for RustDriver {
             return Some(&content[name_node.start_byte()..name_node.end_byte()]);
         }
 
-        if node.kind() == "impl_item" {
-            if let Some(type_node) = node.child_by_field_name("type") {
-                return Some(&content[type_node.start_byte()..type_node.end_byte()]);
-            }
+        if node.kind() == "impl_item"
+            && let Some(type_node) = node.child_by_field_name("type")
+        {
+            return Some(&content[type_node.start_byte()..type_node.end_byte()]);
+        }
+
+        if node.kind() == "foreign_mod_item" {
+            return first_child_of_kind(node, "extern_modifier").map(|abi| node_text(&abi, content));
         }
         None
     }
+
+    fn extract_guards(&self, node: &Node, content: &str) -> Vec<Guard> {
+        let mut attributes = rust_outer_attributes(node);
+
+        // Inner `#![cfg(...)]` attributes apply to the enclosing module or file
+        let body = match node.kind() {
+            "mod_item" => node.child_by_field_name("body"),
+            "source_file" => Some(*node),
+            _ => None,
+        };
+        if let Some(body) = body {
+            let mut cursor = body.walk();
+            attributes.extend(body.children(&mut cursor).filter(|c| c.kind() == "inner_attribute_item"));
+        }
+
+        attributes
+            .iter()
+            .filter_map(|item| first_child_of_kind(item, "attribute"))
+            .filter(|attribute| {
+                let name = attribute.child(0).map(|ident| node_text(&ident, content));
+                matches!(name, Some("cfg") | Some("cfg_attr"))
+            })
+            .map(|attribute| Guard::Cfg(node_text(&attribute, content).to_string()))
+            .collect()
+    }
+
+    fn is_attribute(&self, node: &Node) -> bool { node.kind() == "attribute_item" }
+
+    // Default methods of a trait read `trait(Name)`
+    fn context_kind(&self, node: &Node) -> Option<String> {
+        let kind = match node.kind() {
+            "function_item" => "function",
+            "struct_item" => "struct",
+            "enum_item" => "enum",
+            "trait_item" => "trait",
+            "impl_item" => "impl",
+            "mod_item" => "mod",
+            "foreign_mod_item" => "foreign_mod",
+            _ => return None,
+        };
+        Some(kind.to_string())
+    }
+
+    // `self` included, attributes on a parameter are left out
+    fn extract_parameters(&self, node: &Node, content: &str) -> Vec<String> {
+        node.child_by_field_name("parameters")
+            .map(|parameters| parameter_texts(&parameters, content, &["attribute_item"]))
+            .unwrap_or_default()
+    }
+
+    fn extract_return_type(&self, node: &Node, content: &str) -> Option<String> {
+        node.child_by_field_name("return_type").map(|ty| node_text(&ty, content).to_string())
+    }
+
+    // `#[derive(Debug)]`, `#[cfg(...)]` and the rest, as written
+    fn extract_attributes(&self, node: &Node, content: &str) -> Vec<String> {
+        rust_outer_attributes(node)
+            .iter()
+            .map(|item| node_text(item, content).to_string())
+            .collect()
+    }
+
+    // A plain `pub` only, `pub(crate)` and `pub(super)` items aren't visible outside the
+    // crate
+    fn is_public(&self, node: &Node, content: &str) -> Option<bool> {
+        let visibility = first_child_of_kind(node, "visibility_modifier");
+        Some(visibility.is_some_and(|visibility| node_text(&visibility, content) == "pub"))
+    }
+
+    fn is_async(&self, node: &Node, content: &str) -> Option<bool> {
+        let modifiers = first_child_of_kind(node, "function_modifiers");
+        Some(
+            node.kind() == "function_item"
+                && modifiers.is_some_and(|modifiers| node_text(&modifiers, content).split_whitespace().any(|m| m == "async")),
+        )
+    }
+
+    // `#[test]` and runtime test macros like `#[tokio::test]`
+    fn is_test(&self, node: &Node, content: &str) -> Option<bool> {
+        Some(
+            rust_attribute_paths(node, content)
+                .iter()
+                .any(|path| *path == "test" || path.ends_with("::test")),
+        )
+    }
+
+    // Functions exported as procedural macros are `proc_macro`
+    fn chunk_type(&self, node: &Node, content: &str) -> &'static str {
+        let proc_macro = node.kind() == "function_item"
+            && rust_attribute_paths(node, content)
+                .iter()
+                .any(|path| matches!(*path, "proc_macro" | "proc_macro_derive" | "proc_macro_attribute"));
+        if proc_macro { "proc_macro" } else { node.kind() }
+    }
+
+    fn is_extern(&self, node: &Node) -> bool {
+        let extern_fn = node.kind() == "function_item"
+            && first_child_of_kind(node, "function_modifiers")
+                .is_some_and(|modifiers| first_child_of_kind(&modifiers, "extern_modifier").is_some());
+        let mut in_extern_block = node.kind() == "foreign_mod_item";
+        let mut parent = node.parent();
+        while let Some(p) = parent {
+            in_extern_block |= p.kind() == "foreign_mod_item";
+            parent = p.parent();
+        }
+        extern_fn || in_extern_block
+    }
+}
+
+// Outer attributes are the siblings right before the item, comments may sit between them
+#[cfg(feature = "lang-rust")]
+fn rust_outer_attributes<'t>(node: &Node<'t>) -> Vec<Node<'t>> {
+    let mut attributes = Vec::new();
+    let mut current = node.prev_sibling();
+    while let Some(sibling) = current {
+        match sibling.kind() {
+            "attribute_item" => attributes.push(sibling),
+            "line_comment" | "block_comment" => {}
+            _ => break,
+        }
+        current = sibling.prev_sibling();
+    }
+    attributes.reverse();
+    attributes
 }
 
+// Paths of the outer attributes, like `test` for `#[test]` or `derive` for `#[derive(Debug)]`
+#[cfg(feature = "lang-rust")]
+fn rust_attribute_paths<'a>(node: &Node, content: &'a str) -> Vec<&'a str> {
+    rust_outer_attributes(node)
+        .iter()
+        .filter_map(|item| first_child_of_kind(item, "attribute"))
+        .filter_map(|attribute| attribute.child(0))
+        .map(|path| node_text(&path, content))
+        .collect()
+}
+
+#[cfg(any(
+    feature = "lang-rust",
+    feature = "lang-java",
+    feature = "lang-kotlin",
+    feature = "lang-swift",
+    feature = "lang-haskell",
+    feature = "lang-elixir",
+    feature = "lang-erlang",
+    feature = "lang-ocaml",
+    feature = "lang-dart",
+    feature = "lang-solidity"
+))]
+fn first_child_of_kind<'t>(node: &Node<'t>, kind: &str) -> Option<Node<'t>> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor).find(|c| c.kind() == kind)
+}
+
+#[cfg(feature = "lang-python")]
 struct PythonDriver;
+#[cfg(feature = "lang-python")]
 impl LanguageDriver for PythonDriver {
     fn get_language(&self) -> Language { tree_sitter_python::language() }
     fn get_query(&self) -> &'static str {
@@ -44,14 +292,1503 @@ impl LanguageDriver for PythonDriver {
         }
         None
     }
+
+    fn is_attribute(&self, node: &Node) -> bool { node.kind() == "decorator" }
+
+    // `*args`, `**kwargs` and the bare `*` and `/` separators are kept as written
+    fn extract_parameters(&self, node: &Node, content: &str) -> Vec<String> {
+        node.child_by_field_name("parameters")
+            .map(|parameters| parameter_texts(&parameters, content, &[]))
+            .unwrap_or_default()
+    }
+
+    // The `-> ...` annotation
+    fn extract_return_type(&self, node: &Node, content: &str) -> Option<String> {
+        node.child_by_field_name("return_type").map(|ty| node_text(&ty, content).to_string())
+    }
+
+    // `@property`, `@app.route("/")`, held by the decorated_definition around the item
+    fn extract_attributes(&self, node: &Node, content: &str) -> Vec<String> {
+        let Some(definition) = node.parent().filter(|p| p.kind() == "decorated_definition") else {
+            return Vec::new();
+        };
+        let mut cursor = definition.walk();
+        definition
+            .children(&mut cursor)
+            .filter(|c| c.kind() == "decorator")
+            .map(|decorator| node_text(&decorator, content).to_string())
+            .collect()
+    }
+
+    // The string the body starts with, its quotes removed and indentation cleaned the way
+    // `inspect.cleandoc` does
+    fn extract_docstring(&self, node: &Node, content: &str) -> Option<String> {
+        let statement = node.child_by_field_name("body")?.named_child(0)?;
+        let string = statement.named_child(0).filter(|s| statement.kind() == "expression_statement" && s.kind() == "string")?;
+        let text = node_text(&string, content);
+        let text = text.trim_start_matches(|c: char| "rRuU".contains(c));
+        let quote = ["\"\"\"", "'''", "\"", "'"].into_iter().find(|q| text.starts_with(q))?;
+        let inner = text.strip_prefix(quote)?.strip_suffix(quote)?;
+        let mut lines = inner.lines();
+        let first = lines.next().unwrap_or("").trim();
+        let rest: Vec<&str> = lines.collect();
+        let indent = rest
+            .iter()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.len() - line.trim_start().len())
+            .min()
+            .unwrap_or(0);
+        let mut cleaned = vec![first];
+        cleaned.extend(rest.iter().map(|line| line.get(indent..).unwrap_or("").trim_end()));
+        Some(cleaned.join("\n").trim().to_string())
+    }
+
+    // By convention, a leading underscore marks a private name, dunder methods aside
+    fn is_public(&self, node: &Node, content: &str) -> Option<bool> {
+        let name = self.extract_name(node, content)?;
+        Some(!name.starts_with('_') || (name.starts_with("__") && name.ends_with("__")))
+    }
+
+    fn is_async(&self, node: &Node, _content: &str) -> Option<bool> {
+        Some(node.kind() == "function_definition" && node.child(0).is_some_and(|first| first.kind() == "async"))
+    }
+
+    // What pytest collects, or anything under a `@pytest.mark` decorator
+    fn is_test(&self, node: &Node, content: &str) -> Option<bool> {
+        let named_test = node.kind() == "function_definition"
+            && self.extract_name(node, content).is_some_and(|name| name.starts_with("test_"));
+        let marked = self
+            .extract_attributes(node, content)
+            .iter()
+            .any(|decorator| decorator.starts_with("@pytest.mark"));
+        Some(named_test || marked)
+    }
+
+    // Decorators live inside decorated_definition, comments above them are its siblings
+    fn comment_anchor<'t>(&self, node: Node<'t>) -> Node<'t> {
+        match node.parent() {
+            Some(parent) if parent.kind() == "decorated_definition" => parent,
+            _ => node,
+        }
+    }
+
+    // `if sys.platform == ...` style branches, only blocks and else clauses can hold a chunk.
+    // Under elif or else the earlier branches were not taken, their conditions come negated
+    fn extract_guards(&self, node: &Node, content: &str) -> Vec<Guard> {
+        let Some(branch) = node.parent() else { return Vec::new() };
+        let (statement, taken) = match (node.kind(), branch.kind()) {
+            ("block", "if_statement") => (branch, branch),
+            ("block", "elif_clause") => match branch.parent() {
+                Some(statement) => (statement, branch),
+                None => return Vec::new(),
+            },
+            ("else_clause", "if_statement") => (branch, *node),
+            _ => return Vec::new(),
+        };
+        let condition = |branch: Node| {
+            branch
+                .child_by_field_name("condition")
+                .map(|c| node_text(&c, content).to_string())
+        };
+        let mut cursor = statement.walk();
+        let elifs = statement.children(&mut cursor).filter(|c| c.kind() == "elif_clause");
+        let mut conditions: Vec<String> = std::iter::once(statement)
+            .chain(elifs)
+            .take_while(|earlier| earlier.id() != taken.id())
+            .filter_map(condition)
+            .map(|c| format!("not ({})", c))
+            .collect();
+        conditions.extend(condition(taken));
+        conditions
+            .into_iter()
+            .filter(|c| c.contains("sys.platform") || c.contains("platform.system") || c.contains("os.name"))
+            .map(Guard::Platform)
+            .collect()
+    }
+}
+
+// Shared by the JavaScript and TypeScript drivers, the TypeScript grammar extends the
+// JavaScript one and keeps its node kinds
+
+// Function and class expressions without a name of their own take the one they are
+// bound to, `const foo = () => {}` is `foo`, and `export default () => {}` is `default`
+#[cfg(any(feature = "lang-javascript", feature = "lang-typescript"))]
+fn js_extract_name<'a>(node: &Node, content: &'a str) -> Option<&'a str> {
+    if let Some(name_node) = node.child_by_field_name("name") {
+        return Some(node_text(&name_node, content));
+    }
+    let parent = node.parent()?;
+    match parent.kind() {
+        "variable_declarator" => parent.child_by_field_name("name").map(|name| node_text(&name, content)),
+        "export_statement" => Some("default"),
+        _ => None,
+    }
+}
+
+// Comments sit above the whole `const foo = ...` statement, and above `export`
+#[cfg(any(feature = "lang-javascript", feature = "lang-typescript"))]
+fn js_comment_anchor(node: Node) -> Node {
+    let mut anchor = node;
+    if let Some(declarator) = node.parent().filter(|p| p.kind() == "variable_declarator") {
+        anchor = declarator.parent().unwrap_or(declarator);
+    }
+    match anchor.parent() {
+        Some(parent) if parent.kind() == "export_statement" => parent,
+        _ => anchor,
+    }
+}
+
+// Anonymous callbacks aren't scopes anyone would search for, only named or bound ones
+#[cfg(any(feature = "lang-javascript", feature = "lang-typescript"))]
+fn js_context_kind(node: &Node) -> Option<String> {
+    let bound = node.child_by_field_name("name").is_some()
+        || node.parent().is_some_and(|p| matches!(p.kind(), "variable_declarator" | "export_statement"));
+    let kind = match node.kind() {
+        "class_declaration" | "abstract_class_declaration" => "class",
+        "class" if bound => "class",
+        "method_definition" => "method",
+        "function_declaration" | "generator_function_declaration" => "function",
+        "arrow_function" | "function_expression" | "generator_function" if bound => "function",
+        // TypeScript only, `namespace Api {}` and `module Api {}`
+        "internal_module" => "namespace",
+        "module" => "module",
+        _ => return None,
+    };
+    Some(kind.to_string())
+}
+
+#[cfg(feature = "lang-javascript")]
+struct JavaScriptDriver;
+#[cfg(feature = "lang-javascript")]
+impl LanguageDriver for JavaScriptDriver {
+    fn get_language(&self) -> Language { tree_sitter_javascript::language() }
+    fn get_query(&self) -> &'static str {
+        r#"
+        [ (function_declaration) (generator_function_declaration) (class_declaration) (method_definition) ] @chunk
+        (variable_declarator value: [ (arrow_function) (function_expression) (generator_function) (class) ] @chunk)
+        (export_statement value: [ (arrow_function) (function_expression) (generator_function) (class) ] @chunk)
+        "#
+    }
+    fn get_name(&self) -> &'static str { "JavaScript" }
+
+    fn extract_name<'a>(&self, node: &Node, content: &'a str) -> Option<&'a str> { js_extract_name(node, content) }
+    fn is_attribute(&self, node: &Node) -> bool { node.kind() == "decorator" }
+    fn comment_anchor<'t>(&self, node: Node<'t>) -> Node<'t> { js_comment_anchor(node) }
+    fn context_kind(&self, node: &Node) -> Option<String> { js_context_kind(node) }
+}
+
+// `.tsx` files need the grammar variant that also parses JSX
+#[cfg(feature = "lang-typescript")]
+struct TypeScriptDriver {
+    tsx: bool,
+}
+#[cfg(feature = "lang-typescript")]
+impl LanguageDriver for TypeScriptDriver {
+    fn get_language(&self) -> Language {
+        if self.tsx { tree_sitter_typescript::language_tsx() } else { tree_sitter_typescript::language_typescript() }
+    }
+    fn get_query(&self) -> &'static str {
+        r#"
+        [ (function_declaration) (generator_function_declaration) (class_declaration) (abstract_class_declaration) (method_definition) ] @chunk
+        [ (interface_declaration) (type_alias_declaration) (enum_declaration) ] @chunk
+        (variable_declarator value: [ (arrow_function) (function_expression) (generator_function) (class) ] @chunk)
+        (export_statement value: [ (arrow_function) (function_expression) (generator_function) (class) ] @chunk)
+        "#
+    }
+    fn get_name(&self) -> &'static str { "TypeScript" }
+
+    fn extract_name<'a>(&self, node: &Node, content: &'a str) -> Option<&'a str> { js_extract_name(node, content) }
+    fn is_attribute(&self, node: &Node) -> bool { node.kind() == "decorator" }
+    fn comment_anchor<'t>(&self, node: Node<'t>) -> Node<'t> { js_comment_anchor(node) }
+    fn context_kind(&self, node: &Node) -> Option<String> { js_context_kind(node) }
+}
+
+#[cfg(feature = "lang-go")]
+struct GoDriver;
+#[cfg(feature = "lang-go")]
+impl LanguageDriver for GoDriver {
+    fn get_language(&self) -> Language { tree_sitter_go::language() }
+    // `:=` isn't allowed outside a function, package-scope variables are `var` declarations
+    fn get_query(&self) -> &'static str {
+        r#"
+        [ (function_declaration) (method_declaration) (type_declaration) ] @chunk
+        (source_file [ (var_declaration) (const_declaration) ] @chunk)
+        "#
+    }
+    fn get_name(&self) -> &'static str { "Go" }
+
+    // Grouped `type ( ... )`, `var ( ... )` and `const ( ... )` declarations go by their
+    // first name
+    fn extract_name<'a>(&self, node: &Node, content: &'a str) -> Option<&'a str> {
+        if let Some(name_node) = node.child_by_field_name("name") {
+            return Some(node_text(&name_node, content));
+        }
+        let grouped = matches!(node.kind(), "type_declaration" | "var_declaration" | "const_declaration");
+        let spec = node.named_child(0).filter(|_| grouped)?;
+        spec.child_by_field_name("name").map(|name| node_text(&name, content))
+    }
+
+    // Methods are named the way Go spells a method expression, `(*Server).Handle` for
+    // pointer receivers and `Server.Handle` otherwise
+    fn chunk_name(&self, node: &Node, content: &str) -> Option<String> {
+        let name = self.extract_name(node, content)?;
+        let receiver_type = node
+            .child_by_field_name("receiver")
+            .and_then(|receiver| receiver.named_child(0))
+            .and_then(|parameter| parameter.child_by_field_name("type"));
+        Some(match receiver_type {
+            Some(receiver) if receiver.kind() == "pointer_type" => format!("({}).{}", node_text(&receiver, content), name),
+            Some(receiver) => format!("{}.{}", node_text(&receiver, content), name),
+            None => name.to_string(),
+        })
+    }
+
+    fn context_kind(&self, node: &Node) -> Option<String> {
+        let kind = match node.kind() {
+            "function_declaration" => "function",
+            "method_declaration" => "method",
+            _ => return None,
+        };
+        Some(kind.to_string())
+    }
+
+    // Methods are declared next to their type, not in it. The receiver type is labeled
+    // by its declaration when that is in the same file and `type` otherwise
+    fn outer_context(&self, node: &Node, content: &str) -> Option<ContextPart> {
+        if node.kind() != "method_declaration" {
+            return None;
+        }
+        let receiver = node.child_by_field_name("receiver")?.named_child(0)?;
+        let mut type_node = receiver.child_by_field_name("type")?;
+        while matches!(type_node.kind(), "pointer_type" | "generic_type") {
+            type_node = match type_node.kind() {
+                "pointer_type" => type_node.named_child(0)?,
+                _ => type_node.child_by_field_name("type")?,
+            };
+        }
+        let name = node_text(&type_node, content);
+
+        let root = node.parent()?;
+        let mut cursor = root.walk();
+        let kind = root
+            .children(&mut cursor)
+            .filter(|c| c.kind() == "type_declaration")
+            .flat_map(|declaration| {
+                let mut cursor = declaration.walk();
+                declaration.named_children(&mut cursor).collect::<Vec<_>>()
+            })
+            .find(|spec| spec.child_by_field_name("name").is_some_and(|n| node_text(&n, content) == name))
+            .and_then(|spec| spec.child_by_field_name("type"))
+            .map(|declared| match declared.kind() {
+                "struct_type" => "struct",
+                "interface_type" => "interface",
+                _ => "type",
+            })
+            .unwrap_or("type");
+        Some(ContextPart { kind: kind.to_string(), name: name.to_string() })
+    }
+}
+
+#[cfg(feature = "lang-java")]
+struct JavaDriver;
+#[cfg(feature = "lang-java")]
+impl LanguageDriver for JavaDriver {
+    fn get_language(&self) -> Language { tree_sitter_java::language() }
+    fn get_query(&self) -> &'static str {
+        r#"
+        [ (class_declaration) (interface_declaration) (enum_declaration) (record_declaration) ] @chunk
+        [ (method_declaration) (constructor_declaration) (compact_constructor_declaration) ] @chunk
+        "#
+    }
+    fn get_name(&self) -> &'static str { "Java" }
+
+    // Constructors take the class's name, read off their identifier when the grammar
+    // leaves out the `name` field
+    fn extract_name<'a>(&self, node: &Node, content: &'a str) -> Option<&'a str> {
+        let name = node.child_by_field_name("name").or_else(|| first_child_of_kind(node, "identifier"));
+        name.map(|name| node_text(&name, content))
+    }
+
+    fn context_kind(&self, node: &Node) -> Option<String> {
+        let kind = match node.kind() {
+            "class_declaration" => "class",
+            "interface_declaration" => "interface",
+            "enum_declaration" => "enum",
+            "record_declaration" => "record",
+            "method_declaration" => "method",
+            "constructor_declaration" | "compact_constructor_declaration" => "constructor",
+            _ => return None,
+        };
+        Some(kind.to_string())
+    }
+
+    // `@Override`, `@Test(timeout = 10)`, all sitting in the item's modifiers
+    fn extract_attributes(&self, node: &Node, content: &str) -> Vec<String> {
+        let mut cursor = node.walk();
+        let Some(modifiers) = node.children(&mut cursor).find(|c| c.kind() == "modifiers") else {
+            return Vec::new();
+        };
+        let mut cursor = modifiers.walk();
+        modifiers
+            .children(&mut cursor)
+            .filter(|c| matches!(c.kind(), "annotation" | "marker_annotation"))
+            .map(|annotation| node_text(&annotation, content).to_string())
+            .collect()
+    }
+}
+
+// Shared by the C and C++ drivers, the C++ grammar extends the C one
+
+// Declarations outside any function: at file scope, in `extern "C" {}` and namespace
+// bodies, and under header guards and other conditionals
+#[cfg(any(feature = "lang-c", feature = "lang-cpp"))]
+macro_rules! c_file_scope_declarations {
+    () => {
+        r#"
+        [ (translation_unit (declaration) @chunk) (declaration_list (declaration) @chunk)
+          (preproc_if (declaration) @chunk) (preproc_ifdef (declaration) @chunk)
+          (preproc_else (declaration) @chunk) (preproc_elif (declaration) @chunk) ]
+        "#
+    };
+}
+
+// Declarators nest around the name: `*(*handlers[4])(int)` ends in `handlers`, and C++
+// names may be qualified like `MyClass::myMethod`, which is kept whole
+#[cfg(any(feature = "lang-c", feature = "lang-cpp"))]
+fn c_declarator_name<'a>(node: &Node, content: &'a str) -> Option<&'a str> {
+    let mut current = *node;
+    loop {
+        match current.kind() {
+            "identifier" | "field_identifier" | "type_identifier" | "qualified_identifier" | "destructor_name"
+            | "operator_name" | "template_function" => return Some(node_text(&current, content)),
+            _ => current = current.child_by_field_name("declarator").or_else(|| current.named_child(0))?,
+        }
+    }
+}
+
+// Anonymous namespaces are `anonymous`, nested ones keep their path like `app::detail`
+#[cfg(any(feature = "lang-c", feature = "lang-cpp"))]
+fn c_extract_name<'a>(node: &Node, content: &'a str) -> Option<&'a str> {
+    if let Some(name_node) = node.child_by_field_name("name") {
+        return Some(node_text(&name_node, content));
+    }
+    match node.kind() {
+        "namespace_definition" => Some("anonymous"),
+        "template_declaration" => {
+            let mut cursor = node.walk();
+            let item = node.named_children(&mut cursor).last()?;
+            c_extract_name(&item, content)
+        }
+        _ => c_declarator_name(&node.child_by_field_name("declarator")?, content),
+    }
+}
+
+#[cfg(any(feature = "lang-c", feature = "lang-cpp"))]
+fn c_context_kind(node: &Node) -> Option<String> {
+    let kind = match node.kind() {
+        "function_definition" => "function",
+        "struct_specifier" => "struct",
+        "enum_specifier" => "enum",
+        "union_specifier" => "union",
+        "class_specifier" => "class",
+        "namespace_definition" => "namespace",
+        _ => return None,
+    };
+    Some(kind.to_string())
+}
+
+// `typedef struct { ... } Point;` is chunked as the typedef, named `Point`, and a C++
+// template as the whole `template <...>` declaration
+#[cfg(any(feature = "lang-c", feature = "lang-cpp"))]
+fn c_chunk_node(node: Node) -> Node {
+    let mut chunk = node;
+    while let Some(parent) = chunk.parent() {
+        match parent.kind() {
+            "template_declaration" => chunk = parent,
+            "type_definition" if chunk.id() == node.id() => chunk = parent,
+            _ => break,
+        }
+    }
+    chunk
+}
+
+#[cfg(feature = "lang-c")]
+struct CDriver;
+#[cfg(feature = "lang-c")]
+impl LanguageDriver for CDriver {
+    fn get_language(&self) -> Language { tree_sitter_c::language() }
+    fn get_query(&self) -> &'static str {
+        concat!(
+            r#"
+            [ (function_definition) (struct_specifier body: (_)) (enum_specifier body: (_)) (union_specifier body: (_))
+              (type_definition) ] @chunk
+            "#,
+            c_file_scope_declarations!()
+        )
+    }
+    fn get_name(&self) -> &'static str { "C" }
+
+    fn extract_name<'a>(&self, node: &Node, content: &'a str) -> Option<&'a str> { c_extract_name(node, content) }
+    fn context_kind(&self, node: &Node) -> Option<String> { c_context_kind(node) }
+    fn chunk_node<'t>(&self, node: Node<'t>) -> Node<'t> { c_chunk_node(node) }
+}
+
+#[cfg(feature = "lang-cpp")]
+struct CppDriver;
+#[cfg(feature = "lang-cpp")]
+impl LanguageDriver for CppDriver {
+    fn get_language(&self) -> Language { tree_sitter_cpp::language() }
+    fn get_query(&self) -> &'static str {
+        concat!(
+            r#"
+            [ (function_definition) (class_specifier body: (_)) (struct_specifier body: (_)) (enum_specifier body: (_))
+              (union_specifier body: (_)) (namespace_definition) ] @chunk
+            (template_declaration (declaration) @chunk)
+            "#,
+            c_file_scope_declarations!()
+        )
+    }
+    fn get_name(&self) -> &'static str { "C++" }
+
+    fn extract_name<'a>(&self, node: &Node, content: &'a str) -> Option<&'a str> { c_extract_name(node, content) }
+    fn context_kind(&self, node: &Node) -> Option<String> { c_context_kind(node) }
+    fn chunk_node<'t>(&self, node: Node<'t>) -> Node<'t> { c_chunk_node(node) }
+}
+
+#[cfg(feature = "lang-ruby")]
+struct RubyDriver;
+#[cfg(feature = "lang-ruby")]
+impl LanguageDriver for RubyDriver {
+    fn get_language(&self) -> Language { tree_sitter_ruby::language() }
+    fn get_query(&self) -> &'static str {
+        r#"[ (method) (singleton_method) (class) (module) (do_block) ] @chunk"#
+    }
+    fn get_name(&self) -> &'static str { "Ruby" }
+
+    // Blocks go by the call they are passed to, `describe "User"` or `items.each`
+    fn extract_name<'a>(&self, node: &Node, content: &'a str) -> Option<&'a str> {
+        if let Some(name_node) = node.child_by_field_name("name") {
+            return Some(node_text(&name_node, content));
+        }
+        let call = node.parent().filter(|_| node.kind() == "do_block")?;
+        Some(content[call.start_byte()..node.start_byte()].trim_end())
+    }
+
+    // Singleton methods are named with their object, `self.find`, also when they are
+    // defined in a `class << self` block
+    fn chunk_name(&self, node: &Node, content: &str) -> Option<String> {
+        let name = self.extract_name(node, content)?;
+        let object = match node.kind() {
+            "singleton_method" => node.child_by_field_name("object"),
+            "method" => node
+                .parent()
+                .and_then(|body| body.parent())
+                .filter(|block| block.kind() == "singleton_class")
+                .and_then(|block| block.child_by_field_name("value")),
+            _ => None,
+        };
+        Some(match object {
+            Some(object) => format!("{}.{}", node_text(&object, content), name),
+            None => name.to_string(),
+        })
+    }
+
+    fn context_kind(&self, node: &Node) -> Option<String> {
+        let kind = match node.kind() {
+            "class" => "class",
+            "module" => "module",
+            "method" | "singleton_method" => "method",
+            "do_block" => "block",
+            _ => return None,
+        };
+        Some(kind.to_string())
+    }
+
+    // `def` through the parameter list, which may span lines, Ruby has no return types.
+    // Blocks start at their call, `parts.each do |part|`
+    fn extract_signature<'a>(&self, node: &Node, content: &'a str) -> Option<&'a str> {
+        let (start, end) = match node.kind() {
+            "method" | "singleton_method" => {
+                let end = node.child_by_field_name("parameters").or_else(|| node.child_by_field_name("name"))?;
+                (node.start_byte(), end.end_byte())
+            }
+            "do_block" => {
+                let end = node.child_by_field_name("parameters").map_or(node.start_byte() + "do".len(), |p| p.end_byte());
+                (node.parent()?.start_byte(), end)
+            }
+            _ => return None,
+        };
+        Some(&content[start..end])
+    }
+}
+
+#[cfg(feature = "lang-kotlin")]
+struct KotlinDriver;
+#[cfg(feature = "lang-kotlin")]
+impl LanguageDriver for KotlinDriver {
+    fn get_language(&self) -> Language { tree_sitter_kotlin::language() }
+    fn get_query(&self) -> &'static str {
+        r#"
+        [ (function_declaration) (class_declaration) (object_declaration) (companion_object) ] @chunk
+        (source_file (property_declaration) @chunk)
+        (anonymous_initializer) @chunk
+        (class_body (property_declaration [ (property_delegate) (lambda_literal) ]) @chunk)
+        "#
+    }
+    fn get_name(&self) -> &'static str { "Kotlin" }
+
+    // Extension functions keep their receiver, `String.toSlug`, a companion object
+    // without a name of its own is `Companion` and `init { ... }` blocks are `init`
+    fn extract_name<'a>(&self, node: &Node, content: &'a str) -> Option<&'a str> {
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.named_children(&mut cursor).collect();
+        let find = |kind: &str| children.iter().find(|c| c.kind() == kind).copied();
+        match node.kind() {
+            "function_declaration" => {
+                let name = find("simple_identifier")?;
+                let receiver = find("user_type").filter(|receiver| receiver.end_byte() <= name.start_byte());
+                let start = receiver.map_or(name.start_byte(), |receiver| receiver.start_byte());
+                Some(&content[start..name.end_byte()])
+            }
+            "property_declaration" => {
+                let declaration = find("variable_declaration").or_else(|| find("multi_variable_declaration"))?;
+                let name = first_child_of_kind(&declaration, "simple_identifier").unwrap_or(declaration);
+                Some(node_text(&name, content))
+            }
+            "companion_object" => Some(find("type_identifier").map_or("Companion", |name| node_text(&name, content))),
+            "anonymous_initializer" => Some("init"),
+            _ => find("type_identifier").map(|name| node_text(&name, content)),
+        }
+    }
+
+    fn context_kind(&self, node: &Node) -> Option<String> {
+        let kind = match node.kind() {
+            "class_declaration" if node.child(0).is_some_and(|keyword| keyword.kind() == "interface") => "interface",
+            "class_declaration" => "class",
+            "object_declaration" | "companion_object" => "object",
+            "function_declaration" => "function",
+            _ => return None,
+        };
+        Some(kind.to_string())
+    }
+
+    // `suspend fun` is a `suspend_function_declaration`
+    fn chunk_type(&self, node: &Node, _content: &str) -> &'static str {
+        let suspend = first_child_of_kind(node, "modifiers").is_some_and(|modifiers| {
+            let mut cursor = modifiers.walk();
+            modifiers.children(&mut cursor).any(|modifier| first_child_of_kind(&modifier, "suspend").is_some())
+        });
+        if suspend && node.kind() == "function_declaration" { "suspend_function_declaration" } else { node.kind() }
+    }
+}
+
+// Classes, structs, enums, extensions and actors are all a `class_declaration`, told
+// apart by their keyword
+#[cfg(feature = "lang-swift")]
+struct SwiftDriver;
+#[cfg(feature = "lang-swift")]
+impl LanguageDriver for SwiftDriver {
+    fn get_language(&self) -> Language { tree_sitter_swift::language() }
+    fn get_query(&self) -> &'static str {
+        r#"
+        [ (function_declaration) (init_declaration) (class_declaration) (protocol_declaration) ] @chunk
+        (property_declaration (computed_property)) @chunk
+        "#
+    }
+    fn get_name(&self) -> &'static str { "Swift" }
+
+    // An extension's name is the type it extends, an initializer's is `init` whatever
+    // its argument labels
+    fn extract_name<'a>(&self, node: &Node, content: &'a str) -> Option<&'a str> {
+        if node.kind() == "property_declaration" {
+            return first_child_of_kind(node, "pattern").map(|pattern| node_text(&pattern, content));
+        }
+        node.child_by_field_name("name").map(|name| node_text(&name, content))
+    }
+
+    fn context_kind(&self, node: &Node) -> Option<String> {
+        let kind = match node.kind() {
+            "class_declaration" => node.child_by_field_name("declaration_kind")?.kind(),
+            "protocol_declaration" => "protocol",
+            "function_declaration" => "function",
+            "init_declaration" => "init",
+            _ => return None,
+        };
+        Some(kind.to_string())
+    }
+
+    fn chunk_type(&self, node: &Node, _content: &str) -> &'static str {
+        let keyword = node.child_by_field_name("declaration_kind").map(|keyword| keyword.kind());
+        match (node.kind(), keyword) {
+            ("class_declaration", Some("struct")) => "struct_declaration",
+            ("class_declaration", Some("enum")) => "enum_declaration",
+            ("class_declaration", Some("extension")) => "extension_declaration",
+            ("class_declaration", Some("actor")) => "actor_declaration",
+            (kind, _) => kind,
+        }
+    }
+}
+
+#[cfg(feature = "lang-csharp")]
+struct CSharpDriver;
+#[cfg(feature = "lang-csharp")]
+impl LanguageDriver for CSharpDriver {
+    fn get_language(&self) -> Language { tree_sitter_c_sharp::language() }
+    fn get_query(&self) -> &'static str {
+        r#"
+        [ (class_declaration) (struct_declaration) (interface_declaration) (record_declaration) (record_struct_declaration)
+          (enum_declaration) ] @chunk
+        [ (method_declaration) (constructor_declaration) (property_declaration) ] @chunk
+        "#
+    }
+    fn get_name(&self) -> &'static str { "C#" }
+
+    fn extract_name<'a>(&self, node: &Node, content: &'a str) -> Option<&'a str> {
+        node.child_by_field_name("name").map(|name| node_text(&name, content))
+    }
+
+    fn context_kind(&self, node: &Node) -> Option<String> {
+        let kind = match node.kind() {
+            // The grammar nests everything after `namespace MyApp.Services;` in it
+            "namespace_declaration" | "file_scoped_namespace_declaration" => "namespace",
+            "class_declaration" => "class",
+            "struct_declaration" | "record_struct_declaration" => "struct",
+            "interface_declaration" => "interface",
+            "record_declaration" => "record",
+            "enum_declaration" => "enum",
+            "method_declaration" => "method",
+            "constructor_declaration" => "constructor",
+            "property_declaration" => "property",
+            _ => return None,
+        };
+        Some(kind.to_string())
+    }
+
+    // Attributes are part of the item, the signature starts after them and runs up to the
+    // body, so it holds the whole parameter list and any `where` constraints. Without a
+    // body, like properties, it's the first line
+    fn extract_signature<'a>(&self, node: &Node, content: &'a str) -> Option<&'a str> {
+        let mut cursor = node.walk();
+        let start = node.children(&mut cursor).find(|c| c.kind() != "attribute_list")?.start_byte();
+        match node.child_by_field_name("body") {
+            Some(body) if body.kind() != ";" => Some(content[start..body.start_byte()].trim_end()),
+            _ => content[start..node.end_byte()].lines().next(),
+        }
+    }
+
+    // `[HttpGet("{id}")]`, each attribute list as written
+    fn extract_attributes(&self, node: &Node, content: &str) -> Vec<String> {
+        let mut cursor = node.walk();
+        node.children(&mut cursor)
+            .filter(|c| c.kind() == "attribute_list")
+            .map(|list| node_text(&list, content).to_string())
+            .collect()
+    }
+}
+
+#[cfg(feature = "lang-php")]
+struct PhpDriver;
+#[cfg(feature = "lang-php")]
+impl LanguageDriver for PhpDriver {
+    fn get_language(&self) -> Language { tree_sitter_php::language_php() }
+    fn get_query(&self) -> &'static str {
+        r#"
+        [ (class_declaration) (interface_declaration) (trait_declaration) (enum_declaration) ] @chunk
+        [ (function_definition) (method_declaration) ] @chunk
+        "#
+    }
+    fn get_name(&self) -> &'static str { "PHP" }
+
+    fn extract_name<'a>(&self, node: &Node, content: &'a str) -> Option<&'a str> {
+        node.child_by_field_name("name").map(|name| node_text(&name, content))
+    }
+
+    fn context_kind(&self, node: &Node) -> Option<String> {
+        let kind = match node.kind() {
+            // `namespace { ... }` is the global one, no scope at all
+            "namespace_definition" if node.child_by_field_name("name").is_some() => "namespace",
+            "class_declaration" => "class",
+            "interface_declaration" => "interface",
+            "trait_declaration" => "trait",
+            "enum_declaration" => "enum",
+            "method_declaration" => "method",
+            "function_definition" => "function",
+            _ => return None,
+        };
+        Some(kind.to_string())
+    }
+
+    // `namespace App\Models;` without braces holds everything after it up to the next
+    // one, but the grammar leaves those items as its siblings
+    fn outer_context(&self, node: &Node, content: &str) -> Option<ContextPart> {
+        node.parent().filter(|parent| parent.kind() == "program")?;
+        let mut current = node.prev_named_sibling();
+        while let Some(sibling) = current {
+            if sibling.kind() == "namespace_definition" && sibling.child_by_field_name("body").is_none() {
+                return Some(ContextPart {
+                    kind: "namespace".to_string(),
+                    name: self.extract_name(&sibling, content)?.to_string(),
+                });
+            }
+            current = sibling.prev_named_sibling();
+        }
+        None
+    }
+
+    // Like C#, from after the attributes up to the body, or the first line of an abstract
+    // or interface method
+    fn extract_signature<'a>(&self, node: &Node, content: &'a str) -> Option<&'a str> {
+        let mut cursor = node.walk();
+        let start = node.children(&mut cursor).find(|c| c.kind() != "attribute_list")?.start_byte();
+        match node.child_by_field_name("body") {
+            Some(body) => Some(content[start..body.start_byte()].trim_end()),
+            None => content[start..node.end_byte()].lines().next(),
+        }
+    }
+
+    // `#[Route('/users')]`, each attribute list as written
+    fn extract_attributes(&self, node: &Node, content: &str) -> Vec<String> {
+        let mut cursor = node.walk();
+        node.children(&mut cursor)
+            .filter(|c| c.kind() == "attribute_list")
+            .map(|list| node_text(&list, content).to_string())
+            .collect()
+    }
+}
+
+#[cfg(feature = "lang-scala")]
+struct ScalaDriver;
+#[cfg(feature = "lang-scala")]
+impl LanguageDriver for ScalaDriver {
+    fn get_language(&self) -> Language { tree_sitter_scala::language() }
+    fn get_query(&self) -> &'static str {
+        r#"
+        [ (function_definition) (class_definition) (object_definition) (trait_definition) (enum_definition) ] @chunk
+        (compilation_unit (val_definition) @chunk)
+        "#
+    }
+    fn get_name(&self) -> &'static str { "Scala" }
+
+    // A `val` by its pattern, `(host, port)` when it destructures
+    fn extract_name<'a>(&self, node: &Node, content: &'a str) -> Option<&'a str> {
+        let field = if node.kind() == "val_definition" { "pattern" } else { "name" };
+        node.child_by_field_name(field).map(|name| node_text(&name, content))
+    }
+
+    // A companion object shares its class's name, the kind tells them apart
+    fn context_kind(&self, node: &Node) -> Option<String> {
+        let kind = match node.kind() {
+            "class_definition" => "class",
+            "object_definition" => "object",
+            "trait_definition" => "trait",
+            "enum_definition" => "enum",
+            "function_definition" => "def",
+            _ => return None,
+        };
+        Some(kind.to_string())
+    }
+
+    fn chunk_type(&self, node: &Node, _content: &str) -> &'static str {
+        // After any modifiers, `final case class`
+        let mut cursor = node.walk();
+        let case = node.children(&mut cursor).any(|keyword| keyword.kind() == "case");
+        match node.kind() {
+            "class_definition" if case => "case_class_definition",
+            "object_definition" if case => "case_object_definition",
+            kind => kind,
+        }
+    }
+}
+
+#[cfg(feature = "lang-haskell")]
+struct HaskellDriver;
+#[cfg(feature = "lang-haskell")]
+impl LanguageDriver for HaskellDriver {
+    fn get_language(&self) -> Language { tree_sitter_haskell::language() }
+    fn get_query(&self) -> &'static str {
+        r#"(haskell [ (signature) (function) (adt) (newtype) (class) (instance) ] @chunk)"#
+    }
+    fn get_name(&self) -> &'static str { "Haskell" }
+
+    // A typeclass by its name, an instance by its head, `Show Area`
+    fn extract_name<'a>(&self, node: &Node, content: &'a str) -> Option<&'a str> {
+        match node.kind() {
+            "class" => {
+                let name = first_child_of_kind(node, "class_head")?.child_by_field_name("class")?;
+                Some(node_text(&name, content))
+            }
+            "instance" => first_child_of_kind(node, "instance_head").map(|head| node_text(&head, content)),
+            _ => node.child_by_field_name("name").map(|name| node_text(&name, content)),
+        }
+    }
+
+    fn chunk_name(&self, node: &Node, content: &str) -> Option<String> {
+        let name = self.extract_name(node, content)?;
+        Some(match node.kind() {
+            "instance" => format!("instance {}", name),
+            _ => name.to_string(),
+        })
+    }
+
+    // A function is its type signature and every equation right after it, or the
+    // equations alone without one
+    fn chunk_end<'t>(&self, node: Node<'t>, content: &str) -> Option<Node<'t>> {
+        if !matches!(node.kind(), "signature" | "function") {
+            return None;
+        }
+        let name = self.extract_name(&node, content)?;
+        let same_name = |sibling: &Node| {
+            sibling.kind() == "function" && self.extract_name(sibling, content) == Some(name)
+        };
+        let mut end = None;
+        let mut sibling = node.next_named_sibling();
+        while let Some(next) = sibling.filter(same_name) {
+            end = Some(next);
+            sibling = next.next_named_sibling();
+        }
+        end
+    }
+
+    fn chunk_type(&self, node: &Node, _content: &str) -> &'static str {
+        match node.kind() {
+            "signature" => "function",
+            kind => kind,
+        }
+    }
+
+    // A signature may run over several lines
+    fn extract_signature<'a>(&self, node: &Node, content: &'a str) -> Option<&'a str> {
+        (node.kind() == "signature").then(|| node_text(node, content))
+    }
 }
 
+#[cfg(feature = "lang-lua")]
+struct LuaDriver;
+#[cfg(feature = "lang-lua")]
+impl LanguageDriver for LuaDriver {
+    fn get_language(&self) -> Language { tree_sitter_lua::language() }
+    fn get_query(&self) -> &'static str {
+        r#"(function_declaration) @chunk"#
+    }
+    fn get_name(&self) -> &'static str { "Lua" }
+
+    // The last part of `M.util.clamp` or `Invoice:total`
+    fn extract_name<'a>(&self, node: &Node, content: &'a str) -> Option<&'a str> {
+        let name = node.child_by_field_name("name")?;
+        let name = match name.kind() {
+            "dot_index_expression" => name.child_by_field_name("field")?,
+            "method_index_expression" => name.child_by_field_name("method")?,
+            _ => name,
+        };
+        Some(node_text(&name, content))
+    }
+
+    // Table functions are named with their table, `M.tax`, and methods with `:`
+    fn chunk_name(&self, node: &Node, content: &str) -> Option<String> {
+        let name = self.extract_name(node, content)?;
+        let target = node.child_by_field_name("name")?;
+        let separator = match target.kind() {
+            "dot_index_expression" => ".",
+            "method_index_expression" => ":",
+            _ => return Some(name.to_string()),
+        };
+        let table = target.child_by_field_name("table")?;
+        Some(format!("{}{}{}", node_text(&table, content), separator, name))
+    }
+
+    fn context_kind(&self, node: &Node) -> Option<String> {
+        (node.kind() == "function_declaration").then(|| "function".to_string())
+    }
+
+    // The grammar has no node of its own for `local function`
+    fn chunk_type(&self, node: &Node, _content: &str) -> &'static str {
+        let mut cursor = node.walk();
+        match node.children(&mut cursor).any(|keyword| keyword.kind() == "local") {
+            true => "local_function",
+            false => node.kind(),
+        }
+    }
+}
+
+#[cfg(feature = "lang-bash")]
+struct BashDriver;
+#[cfg(feature = "lang-bash")]
+impl LanguageDriver for BashDriver {
+    fn get_language(&self) -> Language { tree_sitter_bash::language() }
+    fn get_query(&self) -> &'static str {
+        r#"(function_definition) @chunk"#
+    }
+    fn get_name(&self) -> &'static str { "Bash" }
+
+    // The same field for `foo() { ... }` and `function foo { ... }`
+    fn extract_name<'a>(&self, node: &Node, content: &'a str) -> Option<&'a str> {
+        node.child_by_field_name("name").map(|name| node_text(&name, content))
+    }
+
+    fn context_kind(&self, node: &Node) -> Option<String> {
+        (node.kind() == "function_definition").then(|| "function".to_string())
+    }
+
+    fn chunks_whole_file_without_items(&self) -> bool { true }
+}
+
+#[cfg(feature = "lang-elixir")]
+struct ElixirDriver;
+#[cfg(feature = "lang-elixir")]
+impl ElixirDriver {
+    // `def`, `defmodule`, ... that the call is
+    fn keyword<'a>(node: &Node, content: &'a str) -> &'a str {
+        node.child_by_field_name("target").map_or("", |target| node_text(&target, content))
+    }
+
+    fn first_argument<'t>(node: &Node<'t>) -> Option<Node<'t>> {
+        first_child_of_kind(node, "arguments")?.named_child(0)
+    }
+
+    // `name(args)` of a def, without its `when` guard. A function without
+    // parameters is a bare identifier
+    fn function_head<'t>(node: &Node<'t>) -> Option<Node<'t>> {
+        let head = Self::first_argument(node)?;
+        match head.kind() {
+            "binary_operator" => head.child_by_field_name("left"),
+            _ => Some(head),
+        }
+    }
+
+    // A nested module's full name, `MyApp.Worker.State` for `defmodule State` in
+    // `defmodule MyApp.Worker`
+    fn module_name(&self, node: &Node, content: &str) -> Option<String> {
+        let mut names = vec![self.extract_name(node, content)?];
+        let mut parent = node.parent();
+        while let Some(p) = parent {
+            if p.kind() == "call" && Self::keyword(&p, content) == "defmodule" {
+                names.push(self.extract_name(&p, content)?);
+            }
+            parent = p.parent();
+        }
+        names.reverse();
+        Some(names.join("."))
+    }
+}
+#[cfg(feature = "lang-elixir")]
+impl LanguageDriver for ElixirDriver {
+    fn get_language(&self) -> Language { tree_sitter_elixir::language() }
+    fn get_query(&self) -> &'static str {
+        r#"(call target: (identifier) @keyword (#match? @keyword "^(defmodule|def|defp|defmacro|defimpl)$")) @chunk"#
+    }
+    fn get_name(&self) -> &'static str { "Elixir" }
+
+    // A module or protocol by its alias, a function by its name without the arity
+    fn extract_name<'a>(&self, node: &Node, content: &'a str) -> Option<&'a str> {
+        let name = match Self::keyword(node, content) {
+            "def" | "defp" | "defmacro" => {
+                let head = Self::function_head(node)?;
+                match head.kind() {
+                    "call" => head.child_by_field_name("target")?,
+                    _ => head,
+                }
+            }
+            _ => Self::first_argument(node)?,
+        };
+        Some(node_text(&name, content))
+    }
+
+    // Functions as Elixir refers to them, `handle_call/3`, and `defimpl`s with the type
+    // they are for
+    fn chunk_name(&self, node: &Node, content: &str) -> Option<String> {
+        let name = self.extract_name(node, content)?;
+        match Self::keyword(node, content) {
+            "defmodule" => self.module_name(node, content),
+            "defprotocol" => Some(name.to_string()),
+            "defimpl" => {
+                let arguments = first_child_of_kind(node, "arguments")?;
+                let target = first_child_of_kind(&arguments, "keywords")
+                    .and_then(|keywords| keywords.named_child(0))
+                    .filter(|pair| pair.child_by_field_name("key").is_some_and(|key| node_text(&key, content).starts_with("for:")))
+                    .and_then(|pair| pair.child_by_field_name("value"));
+                Some(match target {
+                    Some(target) => format!("{} for {}", name, node_text(&target, content)),
+                    None => name.to_string(),
+                })
+            }
+            _ => {
+                let arity = Self::function_head(node)
+                    .filter(|head| head.kind() == "call")
+                    .and_then(|head| first_child_of_kind(&head, "arguments"))
+                    .map_or(0, |arguments| arguments.named_child_count());
+                Some(format!("{}/{}", name, arity))
+            }
+        }
+    }
+
+    fn context_part(&self, node: &Node, content: &str) -> Option<ContextPart> {
+        if node.kind() != "call" {
+            return None;
+        }
+        let kind = match Self::keyword(node, content) {
+            "defmodule" => "module",
+            "defimpl" => "impl",
+            "defprotocol" => "protocol",
+            keyword @ ("def" | "defp" | "defmacro") => keyword,
+            _ => return None,
+        };
+        Some(ContextPart {
+            kind: kind.to_string(),
+            name: self.chunk_name(node, content)?,
+        })
+    }
+
+    // The clauses of a multi-clause function follow each other, each its own call
+    fn chunk_end<'t>(&self, node: Node<'t>, content: &str) -> Option<Node<'t>> {
+        let keyword = Self::keyword(&node, content);
+        if !matches!(keyword, "def" | "defp" | "defmacro") {
+            return None;
+        }
+        let name = self.chunk_name(&node, content)?;
+        let same_function = |sibling: &Node| {
+            sibling.kind() == "call"
+                && Self::keyword(sibling, content) == keyword
+                && self.chunk_name(sibling, content).as_ref() == Some(&name)
+        };
+        let mut end = None;
+        let mut sibling = node.next_named_sibling();
+        while let Some(next) = sibling.filter(same_function) {
+            end = Some(next);
+            sibling = next.next_named_sibling();
+        }
+        end
+    }
+
+    fn chunk_type(&self, node: &Node, content: &str) -> &'static str {
+        match Self::keyword(node, content) {
+            "defmodule" => "defmodule",
+            "defp" => "defp",
+            "defmacro" => "defmacro",
+            "defimpl" => "defimpl",
+            _ => "def",
+        }
+    }
+}
+
+#[cfg(feature = "lang-erlang")]
+struct ErlangDriver;
+#[cfg(feature = "lang-erlang")]
+impl ErlangDriver {
+    // Argument count of a clause, or of the first type of a spec
+    fn arity(node: &Node) -> Option<usize> {
+        let head = match node.kind() {
+            "spec" => first_child_of_kind(node, "type_sig")?,
+            _ => node.child_by_field_name("clause")?,
+        };
+        Some(head.child_by_field_name("args")?.named_child_count())
+    }
+}
+#[cfg(feature = "lang-erlang")]
+impl LanguageDriver for ErlangDriver {
+    fn get_language(&self) -> Language { tree_sitter_erlang::language() }
+    fn get_query(&self) -> &'static str {
+        r#"(source_file [ (spec) (fun_decl) ] @chunk)"#
+    }
+    fn get_name(&self) -> &'static str { "Erlang" }
+
+    // Every clause is its own `fun_decl`, the name is on the clause
+    fn extract_name<'a>(&self, node: &Node, content: &'a str) -> Option<&'a str> {
+        let name = match node.kind() {
+            "spec" => node.child_by_field_name("fun")?,
+            _ => node.child_by_field_name("clause")?.child_by_field_name("name")?,
+        };
+        Some(node_text(&name, content))
+    }
+
+    // Functions of the same name but another arity are different functions, `init/1`
+    fn chunk_name(&self, node: &Node, content: &str) -> Option<String> {
+        Some(format!("{}/{}", self.extract_name(node, content)?, Self::arity(node)?))
+    }
+
+    // Everything in a file belongs to the module its `-module(...)` attribute names
+    fn outer_context(&self, node: &Node, content: &str) -> Option<ContextPart> {
+        let root = node.parent()?;
+        let mut cursor = root.walk();
+        let module = root.children(&mut cursor).find(|c| c.kind() == "module_attribute")?;
+        Some(ContextPart {
+            kind: "module".to_string(),
+            name: node_text(&module.child_by_field_name("name")?, content).to_string(),
+        })
+    }
+
+    // A function is its spec and every clause of the same name and arity after it,
+    // comments between clauses included
+    fn chunk_end<'t>(&self, node: Node<'t>, content: &str) -> Option<Node<'t>> {
+        let name = self.chunk_name(&node, content)?;
+        let mut end = None;
+        let mut sibling = node.next_named_sibling();
+        while let Some(next) = sibling {
+            match next.kind() {
+                "comment" => {}
+                "fun_decl" if self.chunk_name(&next, content).as_ref() == Some(&name) => end = Some(next),
+                _ => break,
+            }
+            sibling = next.next_named_sibling();
+        }
+        end
+    }
+
+    fn chunk_type(&self, _node: &Node, _content: &str) -> &'static str { "function" }
+
+    // A spec may run over several lines and list a type per clause
+    fn extract_signature<'a>(&self, node: &Node, content: &'a str) -> Option<&'a str> {
+        (node.kind() == "spec").then(|| node_text(node, content))
+    }
+}
+
+// `.mli` interfaces have a grammar of their own, they declare values with `val`
+#[cfg(feature = "lang-ocaml")]
+struct OCamlDriver {
+    interface: bool,
+}
+#[cfg(feature = "lang-ocaml")]
+impl LanguageDriver for OCamlDriver {
+    fn get_language(&self) -> Language {
+        match self.interface {
+            true => tree_sitter_ocaml::language_ocaml_interface(),
+            false => tree_sitter_ocaml::language_ocaml(),
+        }
+    }
+    // Only items of the file and of module bodies and signatures, the `let ... in` inside
+    // a function belongs to it
+    fn get_query(&self) -> &'static str {
+        match self.interface {
+            true => r#"
+            (compilation_unit [ (type_definition) (module_definition) (module_type_definition) (value_specification) (external) ] @chunk)
+            (signature [ (type_definition) (module_definition) (module_type_definition) (value_specification) (external) ] @chunk)
+            "#,
+            false => r#"
+            (compilation_unit [ (value_definition) (type_definition) (module_definition) (module_type_definition) (class_definition) (external) ] @chunk)
+            (structure [ (value_definition) (type_definition) (module_definition) (module_type_definition) (class_definition) (external) ] @chunk)
+            (signature [ (type_definition) (module_definition) (module_type_definition) (value_specification) (external) ] @chunk)
+            "#,
+        }
+    }
+    fn get_name(&self) -> &'static str { "OCaml" }
+
+    // Definitions joined with `and` share a node, it is named after the first one. A `let`
+    // binding a pattern like `let () = ...` is named after the pattern
+    fn extract_name<'a>(&self, node: &Node, content: &'a str) -> Option<&'a str> {
+        let name = match node.kind() {
+            "value_definition" => first_child_of_kind(node, "let_binding")?.child_by_field_name("pattern")?,
+            "type_definition" => first_child_of_kind(node, "type_binding")?.child_by_field_name("name")?,
+            "module_definition" => first_child_of_kind(node, "module_binding")?.child_by_field_name("name")?,
+            "class_definition" => first_child_of_kind(node, "class_binding")?.child_by_field_name("name")?,
+            "value_specification" | "external" => {
+                first_child_of_kind(node, "value_name").or_else(|| first_child_of_kind(node, "parenthesized_operator"))?
+            }
+            _ => node.child_by_field_name("name")?,
+        };
+        Some(node_text(&name, content))
+    }
+
+    // A module taking parameters is a functor
+    fn context_kind(&self, node: &Node) -> Option<String> {
+        let kind = match node.kind() {
+            "module_definition" => {
+                let functor = first_child_of_kind(node, "module_binding")
+                    .is_some_and(|binding| first_child_of_kind(&binding, "module_parameter").is_some());
+                if functor { "functor" } else { "module" }
+            }
+            "module_type_definition" => "module_type",
+            "class_definition" => "class",
+            _ => return None,
+        };
+        Some(kind.to_string())
+    }
+
+    // A `val` or `external` type may run over several lines
+    fn extract_signature<'a>(&self, node: &Node, content: &'a str) -> Option<&'a str> {
+        matches!(node.kind(), "value_specification" | "external").then(|| node_text(node, content))
+    }
+}
+
+// Top-level functions parse as a `lambda_expression` holding the signature, or as a
+// signature with its body as the next sibling. Class members are chunked whole, a
+// `class_member_definition` holds the signature and body both
+#[cfg(feature = "lang-dart")]
+struct DartDriver;
+#[cfg(feature = "lang-dart")]
+impl DartDriver {
+    // `*_signature` node of a function or member chunk
+    fn signature_node<'t>(node: &Node<'t>) -> Option<Node<'t>> {
+        match node.kind() {
+            "lambda_expression" => node.child_by_field_name("parameters"),
+            "class_member_definition" => {
+                let holder = node.named_child(0)?;
+                let mut cursor = holder.walk();
+                holder.named_children(&mut cursor).find(|c| c.kind().ends_with("_signature"))
+            }
+            kind if kind.ends_with("_signature") => Some(*node),
+            _ => None,
+        }
+    }
+}
+#[cfg(feature = "lang-dart")]
+impl LanguageDriver for DartDriver {
+    fn get_language(&self) -> Language { tree_sitter_dart::language() }
+    fn get_query(&self) -> &'static str {
+        r#"
+        (program [ (lambda_expression) (function_signature) (getter_signature) (setter_signature) ] @chunk)
+        [ (class_definition) (enum_declaration) (extension_declaration) (mixin_declaration) ] @chunk
+        (class_member_definition (method_signature)) @chunk
+        (class_member_definition (declaration [ (function_signature) (getter_signature) (setter_signature) (constructor_signature) (constant_constructor_signature) (factory_constructor_signature) (redirecting_factory_constructor_signature) ])) @chunk
+        "#
+    }
+    fn get_name(&self) -> &'static str { "Dart" }
+
+    // Named constructors keep their class, `User.fromJson`, and operators their symbol,
+    // `operator ==`. An extension without a name goes by the type it extends
+    fn extract_name<'a>(&self, node: &Node, content: &'a str) -> Option<&'a str> {
+        let Some(signature) = Self::signature_node(node) else {
+            let name = node
+                .child_by_field_name("name")
+                .or_else(|| first_child_of_kind(node, "identifier"))
+                .or_else(|| node.child_by_field_name("class"))?;
+            return Some(node_text(&name, content));
+        };
+        let mut cursor = signature.walk();
+        let children: Vec<Node> = signature
+            .children(&mut cursor)
+            .take_while(|c| c.kind() != "formal_parameter_list")
+            .collect();
+        let identifiers: Vec<&Node> = children.iter().filter(|c| c.kind() == "identifier").collect();
+        let (first, last) = match signature.kind() {
+            "constructor_signature" | "factory_constructor_signature" | "redirecting_factory_constructor_signature" => {
+                (*identifiers.first()?, *identifiers.last()?)
+            }
+            "constant_constructor_signature" => {
+                let name = children.iter().find(|c| c.kind() == "qualified")?;
+                (name, name)
+            }
+            "operator_signature" => (children.iter().find(|c| c.kind() == "operator")?, children.last()?),
+            // The return type's `<...>` may be mistaken for the name, the name is the last
+            // identifier before the parameters
+            _ => (*identifiers.last()?, *identifiers.last()?),
+        };
+        Some(&content[first.start_byte()..last.end_byte()])
+    }
+
+    fn is_attribute(&self, node: &Node) -> bool { matches!(node.kind(), "annotation" | "marker_annotation") }
+
+    // Annotations are the siblings before the member, like `@override`
+    fn extract_attributes(&self, node: &Node, content: &str) -> Vec<String> {
+        let mut attributes = Vec::new();
+        let mut current = node.prev_named_sibling();
+        while let Some(sibling) = current.filter(|s| self.is_attribute(s)) {
+            attributes.push(node_text(&sibling, content).to_string());
+            current = sibling.prev_named_sibling();
+        }
+        attributes.reverse();
+        attributes
+    }
+
+    fn context_kind(&self, node: &Node) -> Option<String> {
+        let kind = match node.kind() {
+            "class_definition" => "class",
+            "enum_declaration" => "enum",
+            "extension_declaration" => "extension",
+            "mixin_declaration" => "mixin",
+            _ => return None,
+        };
+        Some(kind.to_string())
+    }
+
+    // A top-level signature's body is the next sibling
+    fn chunk_end<'t>(&self, node: Node<'t>, _content: &str) -> Option<Node<'t>> {
+        let body = node.next_named_sibling().filter(|next| next.kind() == "function_body")?;
+        node.kind().ends_with("_signature").then_some(body)
+    }
+
+    // Methods are `method_signature`, constructors, getters, setters and operators keep
+    // the kind of their signature, top-level functions are `function_signature`
+    fn chunk_type(&self, node: &Node, _content: &str) -> &'static str {
+        match Self::signature_node(node) {
+            Some(signature) if node.kind() == "class_member_definition" && signature.kind() == "function_signature" => {
+                "method_signature"
+            }
+            Some(signature) => signature.kind(),
+            None => node.kind(),
+        }
+    }
+
+    // Modifiers like `static` and `factory` included, the body left out
+    fn extract_signature<'a>(&self, node: &Node, content: &'a str) -> Option<&'a str> {
+        let signature = match node.kind() {
+            "class_member_definition" => {
+                let holder = node.named_child(0)?;
+                match holder.kind() {
+                    "method_signature" => holder,
+                    _ => Self::signature_node(node)?,
+                }
+            }
+            _ => Self::signature_node(node)?,
+        };
+        Some(node_text(&signature, content))
+    }
+}
+
+#[cfg(feature = "lang-solidity")]
+struct SolidityDriver;
+#[cfg(feature = "lang-solidity")]
+impl LanguageDriver for SolidityDriver {
+    fn get_language(&self) -> Language { tree_sitter_solidity::language() }
+    fn get_query(&self) -> &'static str {
+        r#"[ (contract_declaration) (interface_declaration) (library_declaration) (function_definition) (constructor_definition) (fallback_receive_definition) (modifier_definition) (event_definition) ] @chunk"#
+    }
+    fn get_name(&self) -> &'static str { "Solidity" }
+
+    // Constructors, `fallback` and `receive` have no name, they go by their keyword. The
+    // grammar puts some field names on the wrong child, only an identifier is a name
+    fn extract_name<'a>(&self, node: &Node, content: &'a str) -> Option<&'a str> {
+        match node.kind() {
+            "constructor_definition" => return Some("constructor"),
+            "fallback_receive_definition" => {
+                let keyword = node.child(0).map(|first| node_text(&first, content));
+                return Some(if keyword == Some("receive") { "receive" } else { "fallback" });
+            }
+            _ => {}
+        }
+        let name = node
+            .child_by_field_name("name")
+            .filter(|name| name.kind() == "identifier")
+            .or_else(|| first_child_of_kind(node, "identifier"))?;
+        Some(node_text(&name, content))
+    }
+
+    fn context_kind(&self, node: &Node) -> Option<String> {
+        let kind = match node.kind() {
+            "contract_declaration" => "contract",
+            "interface_declaration" => "interface",
+            "library_declaration" => "library",
+            _ => return None,
+        };
+        Some(kind.to_string())
+    }
+
+    // Up to the body, so it holds the visibility, modifiers and `returns (...)`, and the
+    // parents a contract inherits from
+    fn extract_signature<'a>(&self, node: &Node, content: &'a str) -> Option<&'a str> {
+        let body = first_child_of_kind(node, "function_body").or_else(|| first_child_of_kind(node, "contract_body"))?;
+        Some(content[node.start_byte()..body.start_byte()].trim_end())
+    }
+}
+
+// Only drivers compiled in through their `lang-*` feature are listed
+pub fn all_drivers() -> Vec<Box<dyn LanguageDriver>> {
+    vec![
+        #[cfg(feature = "lang-rust")]
+        Box::new(RustDriver),
+        #[cfg(feature = "lang-python")]
+        Box::new(PythonDriver),
+        #[cfg(feature = "lang-javascript")]
+        Box::new(JavaScriptDriver),
+        #[cfg(feature = "lang-typescript")]
+        Box::new(TypeScriptDriver { tsx: false }),
+        #[cfg(feature = "lang-go")]
+        Box::new(GoDriver),
+        #[cfg(feature = "lang-java")]
+        Box::new(JavaDriver),
+        #[cfg(feature = "lang-c")]
+        Box::new(CDriver),
+        #[cfg(feature = "lang-cpp")]
+        Box::new(CppDriver),
+        #[cfg(feature = "lang-ruby")]
+        Box::new(RubyDriver),
+        #[cfg(feature = "lang-kotlin")]
+        Box::new(KotlinDriver),
+        #[cfg(feature = "lang-swift")]
+        Box::new(SwiftDriver),
+        #[cfg(feature = "lang-csharp")]
+        Box::new(CSharpDriver),
+        #[cfg(feature = "lang-php")]
+        Box::new(PhpDriver),
+        #[cfg(feature = "lang-scala")]
+        Box::new(ScalaDriver),
+        #[cfg(feature = "lang-haskell")]
+        Box::new(HaskellDriver),
+        #[cfg(feature = "lang-lua")]
+        Box::new(LuaDriver),
+        #[cfg(feature = "lang-bash")]
+        Box::new(BashDriver),
+        #[cfg(feature = "lang-elixir")]
+        Box::new(ElixirDriver),
+        #[cfg(feature = "lang-erlang")]
+        Box::new(ErlangDriver),
+        #[cfg(feature = "lang-ocaml")]
+        Box::new(OCamlDriver { interface: false }),
+        #[cfg(feature = "lang-dart")]
+        Box::new(DartDriver),
+        #[cfg(feature = "lang-solidity")]
+        Box::new(SolidityDriver),
+    ]
+}
+
+pub fn supported_languages() -> Vec<&'static str> {
+    let scanned = crate::scan::all_scan_drivers();
+    all_drivers()
+        .iter()
+        .map(|driver| driver.get_name())
+        .chain(scanned.iter().map(|driver| driver.get_name()))
+        .collect()
+}
 
 pub fn get_driver(extension: &str) -> Option<Box<dyn LanguageDriver>> {
     match extension {
+        #[cfg(feature = "lang-rust")]
         "rs" => Some(Box::new(RustDriver)),
+        #[cfg(feature = "lang-python")]
         "py" => Some(Box::new(PythonDriver)),
+        #[cfg(feature = "lang-javascript")]
+        "js" | "mjs" | "cjs" | "jsx" => Some(Box::new(JavaScriptDriver)),
+        #[cfg(feature = "lang-typescript")]
+        "ts" | "mts" | "cts" => Some(Box::new(TypeScriptDriver { tsx: false })),
+        #[cfg(feature = "lang-typescript")]
+        "tsx" => Some(Box::new(TypeScriptDriver { tsx: true })),
+        #[cfg(feature = "lang-go")]
+        "go" => Some(Box::new(GoDriver)),
+        #[cfg(feature = "lang-java")]
+        "java" => Some(Box::new(JavaDriver)),
+        // Plain `.h` headers are most often C, C++ projects tend to use the other ones
+        #[cfg(feature = "lang-c")]
+        "c" | "h" => Some(Box::new(CDriver)),
+        #[cfg(feature = "lang-cpp")]
+        "cc" | "cpp" | "cxx" | "c++" | "hh" | "hpp" | "hxx" => Some(Box::new(CppDriver)),
+        #[cfg(feature = "lang-ruby")]
+        "rb" | "rake" | "gemspec" => Some(Box::new(RubyDriver)),
+        #[cfg(feature = "lang-kotlin")]
+        "kt" | "kts" => Some(Box::new(KotlinDriver)),
+        #[cfg(feature = "lang-swift")]
+        "swift" => Some(Box::new(SwiftDriver)),
+        #[cfg(feature = "lang-csharp")]
+        "cs" => Some(Box::new(CSharpDriver)),
+        #[cfg(feature = "lang-php")]
+        "php" => Some(Box::new(PhpDriver)),
+        #[cfg(feature = "lang-scala")]
+        "scala" | "sc" => Some(Box::new(ScalaDriver)),
+        #[cfg(feature = "lang-haskell")]
+        "hs" => Some(Box::new(HaskellDriver)),
+        #[cfg(feature = "lang-lua")]
+        "lua" => Some(Box::new(LuaDriver)),
+        #[cfg(feature = "lang-bash")]
+        "sh" | "bash" => Some(Box::new(BashDriver)),
+        #[cfg(feature = "lang-elixir")]
+        "ex" | "exs" => Some(Box::new(ElixirDriver)),
+        #[cfg(feature = "lang-erlang")]
+        "erl" | "hrl" => Some(Box::new(ErlangDriver)),
+        #[cfg(feature = "lang-ocaml")]
+        "ml" => Some(Box::new(OCamlDriver { interface: false })),
+        #[cfg(feature = "lang-ocaml")]
+        "mli" => Some(Box::new(OCamlDriver { interface: true })),
+        #[cfg(feature = "lang-dart")]
+        "dart" => Some(Box::new(DartDriver)),
+        #[cfg(feature = "lang-solidity")]
+        "sol" => Some(Box::new(SolidityDriver)),
         _ => None,
     }
-
-}
\ No newline at end of file
+}
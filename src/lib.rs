@@ -0,0 +1,36 @@
+pub mod audit;
+#[cfg(feature = "cache")]
+pub mod cache;
+pub mod config;
+pub mod conformance;
+pub mod coverage;
+pub mod diff;
+pub mod doctor;
+pub mod explain;
+pub mod files;
+pub mod git;
+pub mod hash;
+pub mod init;
+pub mod invariants;
+pub mod lang_driver;
+pub mod lint;
+pub mod lsp;
+pub mod output;
+pub mod pairing;
+pub mod patch;
+pub mod post_process;
+pub mod safety;
+pub mod scan;
+pub mod stats;
+pub mod stream;
+pub mod types;
+#[cfg(feature = "watch")]
+pub mod watch;
+pub mod wide;
+pub mod workspace;
+
+// What library callers need to chunk files on their own
+pub use files::{FileFilter, Tokenizer, get_files, process_file, split_text_by_token_limit};
+pub use hash::{compute_hash, compute_structural_hash};
+pub use lang_driver::{LanguageDriver, get_driver};
+pub use types::{ChunkData, ChunkOptions};
@@ -0,0 +1,241 @@
+use crate::output::{OutputMeta, value_name};
+use crate::stream::{Entry, SpillMap, input_bytes, read_lines};
+use crate::types::IdFormat;
+use anyhow::{Result, anyhow};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use uuid::Uuid;
+
+// Offending line numbers kept per violation type
+const MAX_EXAMPLES: usize = 5;
+
+enum FieldType {
+    Id,
+    Text,
+    Count,
+}
+
+const REQUIRED_FIELDS: &[(&str, FieldType)] = &[
+    ("id", FieldType::Id),
+    ("file_path", FieldType::Text),
+    ("language", FieldType::Text),
+    ("chunk_type", FieldType::Text),
+    ("chunk_name", FieldType::Text),
+    ("context", FieldType::Text),
+    ("signature", FieldType::Text),
+    ("comment", FieldType::Text),
+    ("code", FieldType::Text),
+    ("start_line", FieldType::Count),
+    ("end_line", FieldType::Count),
+    ("token_count", FieldType::Count),
+];
+
+// Written by newer versions, allowed to be missing in older outputs
+const OPTIONAL_FIELDS: &[&str] = &[
+    "context_parts",
+    "max_tokens_applied",
+    "markers",
+    "stale",
+    "rev",
+    "hunk",
+    "cfg_conditions",
+    "is_extern",
+    "platform_guards",
+    "members",
+    "code_normalized",
+    "token_count_normalized",
+    "pattern_index",
+    "start_col",
+    "end_col",
+    "overlap_start_line",
+    "wide_file",
+    "lsp_range",
+    "reason",
+    "header_comment",
+    "declared_in",
+    "context_truncated",
+    "signature_truncated",
+    "content_hash",
+    "structural_hash",
+    "project",
+    "attributes",
+    "deleted",
+    "parameters",
+    "return_type",
+    "is_public",
+    "is_async",
+    "is_test",
+    "docstring",
+];
+
+#[derive(Default)]
+struct Report {
+    records: usize,
+    violations: BTreeMap<String, (usize, Vec<usize>)>,
+    unknown_fields: BTreeMap<String, usize>,
+}
+
+impl Report {
+    fn violation(&mut self, kind: impl Into<String>, line: usize) {
+        let (count, examples) = self.violations.entry(kind.into()).or_default();
+        *count += 1;
+        if examples.len() < MAX_EXAMPLES {
+            examples.push(line);
+        }
+    }
+}
+
+fn type_matches(value: &Value, field_type: &FieldType) -> bool {
+    match field_type {
+        FieldType::Id => value.is_string() || value.is_i64(),
+        FieldType::Text => value.is_string(),
+        FieldType::Count => value.is_u64(),
+    }
+}
+
+// Whether an id has the shape `--id-format` gives it
+fn id_has_format(id: &Value, format: IdFormat) -> bool {
+    match format {
+        IdFormat::Hex => id.as_str().is_some_and(|id| id.len() == 64 && id.bytes().all(|b| b.is_ascii_hexdigit())),
+        IdFormat::Uuid => id.as_str().is_some_and(|id| Uuid::parse_str(id).is_ok()),
+        IdFormat::Int64 => id.is_i64(),
+    }
+}
+
+// Sub-chunks of one logical chunk share everything but code, lines and counts
+fn logical_key(record: &Map<String, Value>) -> String {
+    [
+        "file_path",
+        "chunk_type",
+        "chunk_name",
+        "context",
+        "signature",
+    ]
+    .iter()
+    .map(|field| record.get(*field).map(Value::to_string).unwrap_or_default())
+    .collect::<Vec<_>>()
+    .join("\u{1f}")
+}
+
+// Ids are the only state that grows with the file, they go through a SpillMap
+// bounded by `max_memory` and are checked for duplicates after the pass
+pub fn lint_output(path: &Path, max_memory: usize) -> Result<()> {
+    let mut report = Report::default();
+    let mut ids = SpillMap::new(max_memory, input_bytes(&[path]));
+    let mut previous: Option<(String, u64)> = None;
+    // Ids are checked against the format the run wrote them in, when its meta file is there
+    let meta = OutputMeta::read(path)?;
+    if let Some(meta) = &meta {
+        println!("Ids: {}", meta.describe());
+    }
+
+    for (index, line) in read_lines(path)?.enumerate() {
+        let line_number = index + 1;
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        report.records += 1;
+
+        let record = match serde_json::from_str::<Value>(&line) {
+            Ok(Value::Object(record)) => record,
+            Ok(_) => {
+                report.violation("record is not a JSON object", line_number);
+                continue;
+            }
+            Err(_) => {
+                report.violation("invalid JSON", line_number);
+                continue;
+            }
+        };
+
+        for (field, field_type) in REQUIRED_FIELDS {
+            match record.get(*field) {
+                None => report.violation(format!("missing field `{}`", field), line_number),
+                Some(value) if !type_matches(value, field_type) => {
+                    report.violation(format!("wrong type for `{}`", field), line_number)
+                }
+                _ => {}
+            }
+        }
+        for field in record.keys() {
+            let known = REQUIRED_FIELDS.iter().any(|(name, _)| name == field)
+                || OPTIONAL_FIELDS.contains(&field.as_str());
+            if !known {
+                *report.unknown_fields.entry(field.clone()).or_default() += 1;
+            }
+        }
+
+        if let Some(id) = record.get("id") {
+            if let Some(meta) = &meta
+                && !id_has_format(id, meta.id_format)
+            {
+                report.violation(format!("id not in the {} format of the meta file", value_name(&meta.id_format)), line_number);
+            }
+            ids.push(Entry {
+                key: id.to_string(),
+                source: 0,
+                value: line_number as u64,
+            })?;
+        }
+
+        let start_line = record.get("start_line").and_then(Value::as_u64);
+        let end_line = record.get("end_line").and_then(Value::as_u64);
+        // Tombstones stand for a whole file and have no lines
+        let deleted = record.get("deleted").and_then(Value::as_bool) == Some(true);
+        if start_line == Some(0) && !deleted {
+            report.violation("start_line is 0 (lines are 1-based)", line_number);
+        }
+        if let (Some(start), Some(end)) = (start_line, end_line)
+            && end < start
+        {
+            report.violation("end_line < start_line", line_number);
+        }
+
+        let code = record.get("code").and_then(Value::as_str).unwrap_or("");
+        let token_count = record.get("token_count").and_then(Value::as_u64);
+        if token_count == Some(0) && !code.trim().is_empty() {
+            report.violation("token_count 0 with non-empty code", line_number);
+        }
+
+        // Parts of one logical chunk are written back to back in source order
+        let key = logical_key(&record);
+        if let (Some((previous_key, previous_start)), Some(start)) = (&previous, start_line)
+            && *previous_key == key
+            && start < *previous_start
+        {
+            report.violation("parts of a logical chunk out of order", line_number);
+        }
+        previous = start_line.map(|start| (key, start));
+    }
+
+    ids.finish(|entries| {
+        let mut seen = HashSet::new();
+        for entry in &entries {
+            if !seen.insert(entry.key.as_str()) {
+                report.violation("duplicate id", entry.value as usize);
+            }
+        }
+        Ok(())
+    })?;
+
+    println!("Records checked: {}", report.records);
+    for (field, count) in &report.unknown_fields {
+        println!("note: unknown field `{}` in {} records", field, count);
+    }
+    if report.violations.is_empty() {
+        println!("No violations found.");
+        return Ok(());
+    }
+
+    let mut total = 0;
+    for (kind, (count, examples)) in &mut report.violations {
+        total += *count;
+        // Duplicates are found per spill partition, not in file order
+        examples.sort_unstable();
+        let lines: Vec<String> = examples.iter().map(usize::to_string).collect();
+        println!("{}: {} (lines {})", kind, count, lines.join(", "));
+    }
+    Err(anyhow!("{} violations found in {}", total, path.display()))
+}
@@ -0,0 +1,80 @@
+use crate::types::{ChunkData, LspPosition, LspRange};
+
+// Byte offsets of line starts, to turn offsets into zero-based LSP positions whose
+// character is counted in UTF-16 code units
+pub struct LineIndex<'a> {
+    content: &'a str,
+    starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(content: &'a str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        LineIndex { content, starts }
+    }
+
+    pub fn line_start(&self, row: usize) -> usize {
+        self.starts.get(row).copied().unwrap_or(self.content.len())
+    }
+
+    // Offset right after the last character of the row, before its line ending
+    pub fn line_end(&self, row: usize) -> usize {
+        let end = self
+            .starts
+            .get(row + 1)
+            .map_or(self.content.len(), |next| next - 1);
+        let line = &self.content[self.line_start(row).min(end)..end];
+        end - (line.len() - line.trim_end_matches('\r').len())
+    }
+
+    // First non-whitespace offset of the row, where trimmed text such as comments starts
+    pub fn line_text_start(&self, row: usize) -> usize {
+        let start = self.line_start(row);
+        let line = &self.content[start..self.line_end(row).max(start)];
+        start + (line.len() - line.trim_start().len())
+    }
+
+    pub fn position(&self, offset: usize) -> LspPosition {
+        let line = self.starts.partition_point(|start| *start <= offset) - 1;
+        let character = self.content[self.starts[line]..offset]
+            .chars()
+            .map(char::len_utf16)
+            .sum();
+        LspPosition { line, character }
+    }
+
+    pub fn range(&self, start: usize, end: usize) -> LspRange {
+        LspRange {
+            start: self.position(start),
+            end: self.position(end),
+        }
+    }
+}
+
+// Whole-line ranges for chunks that weren't given a precise one where they were cut,
+// windows of wide files use their character columns
+pub fn add_lsp_ranges(chunks: &mut [ChunkData], content: &str) {
+    let index = LineIndex::new(content);
+    for chunk in chunks.iter_mut().filter(|c| c.lsp_range.is_none()) {
+        let start_row = chunk.start_line.saturating_sub(1);
+        let end_row = chunk.end_line.saturating_sub(1);
+        let start = match chunk.start_col {
+            Some(col) => char_offset(content, index.line_start(start_row), col - 1),
+            None => index.line_start(start_row),
+        };
+        let end = match chunk.end_col {
+            Some(col) => char_offset(content, index.line_start(end_row), col),
+            None => index.line_end(end_row),
+        };
+        chunk.lsp_range = Some(index.range(start, end.max(start)));
+    }
+}
+
+fn char_offset(content: &str, line_start: usize, chars: usize) -> usize {
+    content[line_start..]
+        .char_indices()
+        .nth(chars)
+        .map_or(content.len(), |(i, _)| line_start + i)
+}
@@ -1,75 +1,882 @@
-mod files;
-mod git;
-mod hash;
-mod lang_driver;
-mod types;
-
-use crate::files::process_file;
-use crate::types::ChunkData;
 use anyhow::{Result, anyhow};
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use crossbeam_channel::bounded;
+use indicatif::{ProgressBar, ProgressStyle};
 use rayon::iter::ParallelIterator;
 use rayon::prelude::IntoParallelRefIterator;
-use std::fs::OpenOptions;
-use std::io::BufWriter;
-use std::io::Write;
-use std::path::PathBuf;
+use smart_code_chunker::{
+    audit, config, conformance, coverage, diff, doctor, explain, files, git, hash, init, lang_driver,
+    lint, lsp, pairing, safety, stats, types,
+};
+#[cfg(feature = "watch")]
+use smart_code_chunker::watch;
+#[cfg(feature = "cache")]
+use smart_code_chunker::cache::FileCache;
+use smart_code_chunker::coverage::FileCoverage;
+use smart_code_chunker::files::{FileFilter, FileOutcome, Tokenizer, process_file};
+use smart_code_chunker::patch::find_hunk_chunks;
+use smart_code_chunker::output::{OutputMeta, OutputWriter, create_writer};
+use smart_code_chunker::post_process::{PostProcessWriter, PostProcessor};
+use smart_code_chunker::stats::RunStats;
+use smart_code_chunker::types::{
+    ChunkData, ChunkOptions, ContextPart, GitRevision, IdField, IdFormat, OutputFormat, PrintPaths, Report,
+    SkipReason, SplitStrategy, TokenLimit,
+};
+use smart_code_chunker::workspace::Workspace;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{self, BufWriter, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
 use std::thread;
+use std::time::Duration;
 use tree_sitter::Parser as TreeParser;
 
 #[derive(Parser, Debug)]
 #[command(
     name = "smart-chunker",
-    about = "High-performance semantic code chunker for RAG"
+    about = "High-performance semantic code chunker for RAG",
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
 )]
 struct Args {
-    #[arg(short, long, help = "File path for search folder")]
-    path: String,
+    #[command(subcommand)]
+    command: Option<Command>,
 
-    #[arg(short, long, default_value = "output.jsonl", help = "Output file name")]
-    output: String,
+    #[arg(
+        short,
+        long,
+        num_args = 1..,
+        help = "Folders or files to chunk, several may follow one --path or it may be repeated. Required unless set in the config or --from-patch is given"
+    )]
+    path: Vec<String>,
+
+    #[arg(short, long, help = "Output file name, default is output.jsonl")]
+    output: Option<String>,
+
+    #[arg(long, value_enum, default_value_t = OutputFormat::Jsonl, help = "Format of the output file")]
+    format: OutputFormat,
 
     #[arg(long, help = "Scan the folder since this commit (Example: HEAD~1)")]
     since: Option<String>,
 
+    #[arg(
+        long,
+        help = "SQLite file remembering each file's hash and chunks, files unchanged since the last run are skipped. Not used with --since"
+    )]
+    cache: Option<PathBuf>,
+
+    #[arg(
+        long,
+        requires = "cache",
+        help = "Write the cached chunks of files skipped by --cache too, so the output stays complete"
+    )]
+    cache_replay: bool,
+
+    #[arg(
+        long,
+        help = "Only chunk files matching this glob, relative to the path (Example: \"**/*.rs\"), repeatable"
+    )]
+    include: Vec<String>,
+
+    #[arg(
+        long,
+        help = "Skip files matching this glob, relative to the path (Example: \"**/tests/**\"), repeatable"
+    )]
+    exclude: Vec<String>,
+
+    #[arg(
+        long,
+        help = "Read files from this git revision instead of the working tree, combine with --since to only take the files changed up to it"
+    )]
+    at_rev: Option<String>,
+
+    #[arg(long, help = "Read a unified diff from stdin and chunk it per hunk")]
+    from_patch: bool,
+
+    #[arg(
+        long,
+        default_value_t = 0,
+        help = "Drop chunks and split parts under this many tokens, such as one-line getters"
+    )]
+    min_tokens: usize,
+
+    #[arg(
+        long,
+        help = "Skip items over --max-tokens-factor times this many tokens with a warning instead of splitting them, such as generated tables. Unlimited by default"
+    )]
+    max_tokens: Option<usize>,
+
+    #[arg(
+        long,
+        default_value_t = 10,
+        requires = "max_tokens",
+        help = "How many times --max-tokens an item may have before it is skipped"
+    )]
+    max_tokens_factor: usize,
+
     #[arg(
         short,
         long,
-        default_value_t = 800,
-        help = "Max tokens per chunk, default is 800 for GPT-4"
+        value_parser = parse_token_limit,
+        help = "Max tokens per chunk, default is 800 for GPT-4. Repeat as LANG=N for per-language limits (Example: -m 512 -m python=1024)"
+    )]
+    max_chunk_tokens: Vec<TokenLimit>,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = IdFormat::Hex,
+        help = "Chunk id format: hex (sha256), uuid (UUIDv5) or int64"
+    )]
+    id_format: IdFormat,
+
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_value = "content,path,part",
+        help = "Comma separated components hashed into chunk ids: content, path, name, type, part"
+    )]
+    id_fields: Vec<IdField>,
+
+    #[arg(
+        long,
+        value_delimiter = ',',
+        default_value = "TODO,FIXME,HACK,XXX",
+        help = "Comma separated markers to record per chunk"
+    )]
+    markers: Vec<String>,
+
+    #[arg(
+        long,
+        default_value_t = 2,
+        help = "Times to re-process a file that changes while being chunked before marking it stale"
+    )]
+    stale_retries: usize,
+
+    #[arg(
+        long,
+        help = "Emit `gap` records for regions that could not be chunked (invalid UTF-8, syntax errors, parse timeouts)"
+    )]
+    emit_gaps: bool,
+
+    #[arg(
+        long,
+        default_value_t = 0,
+        help = "Give up parsing a file after this many milliseconds, 0 disables the timeout"
+    )]
+    parse_timeout_ms: u64,
+
+    #[arg(
+        long,
+        default_value_t = 1,
+        help = "Max blank lines allowed between a comment and the item it documents"
+    )]
+    max_comment_gap: usize,
+
+    #[arg(
+        long,
+        help = "Print the share of non-blank source lines covered by chunks, per language and overall"
+    )]
+    coverage: bool,
+
+    #[arg(
+        long,
+        help = "Write per-file coverage as CSV to this path, implies --coverage"
+    )]
+    coverage_report: Option<PathBuf>,
+
+    #[arg(
+        long,
+        default_value_t = 300,
+        help = "Emit files under this many tokens as a single `file` chunk listing its items as members, 0 disables"
+    )]
+    small_file_threshold: usize,
+
+    #[arg(
+        long,
+        help = "Also emit code_normalized (trailing whitespace stripped, LF endings, common indent removed) and token_count_normalized"
+    )]
+    emit_normalized: bool,
+
+    #[arg(
+        long,
+        help = "Add debugging fields to chunks, such as the query pattern_index that captured them"
+    )]
+    debug_fields: bool,
+
+    #[arg(
+        long,
+        default_value_t = 5000,
+        help = "Files with a line longer than this many characters are cut into token windows instead of parsed, 0 disables"
+    )]
+    wide_line_threshold: usize,
+
+    #[arg(
+        long,
+        help = "Add lsp_range with zero-based lines and UTF-16 characters, as the Language Server Protocol uses"
+    )]
+    lsp_ranges: bool,
+
+    #[arg(
+        long,
+        default_value_t = 512,
+        help = "Cut longer context breadcrumbs in the middle with an ellipsis, 0 disables"
+    )]
+    max_context_chars: usize,
+
+    #[arg(
+        long,
+        default_value_t = 512,
+        help = "Cut longer signatures in the middle with an ellipsis, keeping the name and the end, 0 disables"
+    )]
+    max_signature_chars: usize,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = SplitStrategy::Boundaries,
+        help = "Where an item over the token limit is cut into parts"
+    )]
+    split_strategy: SplitStrategy,
+
+    #[arg(
+        long,
+        default_value_t = 8,
+        help = "Lines before the token limit a boundaries split may move its cut back to"
+    )]
+    split_lookahead: usize,
+
+    #[arg(
+        long = "overlap",
+        default_value_t = 0,
+        help = "Tokens of a split item's last lines repeated at the start of its next part"
+    )]
+    overlap_tokens: usize,
+
+    #[arg(
+        long,
+        help = "Attach the doc comment and id of a function's declaration in foo.h to its definition in foo.c"
+    )]
+    pair_headers: bool,
+
+    #[arg(
+        long,
+        value_parser = parse_context_filter,
+        help = "Only emit chunks nested in an ancestor of this kind and name, repeat to require several (Example: --context-filter impl=Config)"
+    )]
+    context_filter: Vec<ContextPart>,
+
+    #[arg(
+        long,
+        help = "Pipe chunks as JSONL batches through this command, which must answer line-for-line with the same ids"
+    )]
+    post_process: Option<String>,
+
+    #[arg(long, default_value_t = 100, help = "Chunks per --post-process batch")]
+    post_process_batch_size: usize,
+
+    #[arg(
+        long,
+        default_value_t = 30,
+        help = "Seconds the --post-process command may take to answer one batch"
+    )]
+    post_process_timeout_secs: u64,
+
+    #[arg(
+        long,
+        help = "Fail instead of warning when a driver's query names node kinds missing from its grammar"
+    )]
+    strict: bool,
+
+    #[arg(
+        long,
+        conflicts_with = "from_patch",
+        help = "Write a hash chain over the written chunks to this file and add content_hash to every chunk"
+    )]
+    audit_chain: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "Show file paths in chunks and messages relative to --path or absolute, default is as found under --path"
     )]
-    max_chunk_tokens: usize,
+    print_paths: Option<PrintPaths>,
+
+    #[arg(
+        long,
+        help = "Fail before scanning if the output or any other file this run writes would land inside --path"
+    )]
+    assert_read_only: bool,
+
+    #[arg(
+        long,
+        help = "Fail before scanning if a setting could reach the network, such as a --post-process command"
+    )]
+    offline: bool,
+
+    #[arg(
+        long,
+        default_value_t = 0,
+        help = "Files chunked in parallel, 0 uses one thread per CPU core"
+    )]
+    threads: usize,
+
+    #[arg(
+        long,
+        help = "Settings file, default is .chunker.toml or chunker.toml in the working directory if present"
+    )]
+    config: Option<PathBuf>,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["post_process", "audit_chain"],
+        help = "Chunk as usual but write nothing, print chunk and token totals per file instead"
+    )]
+    dry_run: bool,
+
+    #[arg(
+        long,
+        help = "Only emit chunks of this chunk_type, repeatable (Example: --filter-type function_item --filter-type impl_item)"
+    )]
+    filter_type: Vec<String>,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["dry_run", "post_process", "audit_chain", "watch"],
+        help = "Chunk as usual but write nothing, print the chunk types seen and their counts instead"
+    )]
+    list_types: bool,
+
+    #[arg(
+        long,
+        help = "Write only the first chunk of each structural_hash, skipping copies that differ only in whitespace"
+    )]
+    deduplicate: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["dry_run", "at_rev", "from_patch", "post_process", "audit_chain", "pair_headers", "deduplicate"],
+        help = "After the run, keep chunking files as they are written or removed and append to the output until Ctrl-C"
+    )]
+    watch: bool,
+
+    #[arg(
+        long,
+        help = "Don't show the progress bar, which is also left out when stderr isn't a terminal"
+    )]
+    no_progress: bool,
+
+    #[arg(long, value_enum, help = "Print an aggregated report after the run")]
+    report: Option<Report>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Check every driver's query against its grammar and list query patterns that never match under a path
+    Doctor {
+        #[arg(help = "Folder to scan")]
+        path: String,
+    },
+    /// Write a commented chunker.toml and an ingest script for a vector store
+    Init {
+        #[arg(long, value_enum, help = "Store the output is meant for")]
+        target: init::Target,
+
+        #[arg(long, default_value = ".", help = "Folder to write the files to")]
+        dir: PathBuf,
+
+        #[arg(long, help = "Overwrite existing files")]
+        force: bool,
+    },
+    /// Show every rule a scan applies to one path and whether it would be chunked
+    Explain {
+        #[arg(help = "File to explain")]
+        path: PathBuf,
+    },
+    /// Check an existing JSONL output for schema and invariant violations
+    Lint {
+        #[arg(help = "Output file to check")]
+        input: PathBuf,
+
+        #[arg(long, default_value = "512M", value_parser = parse_byte_size, help = "Memory for chunk ids before they spill to temp files (Example: 2G)")]
+        max_memory: usize,
+    },
+    /// Print record, file and token totals of an existing JSONL output
+    Stats {
+        #[arg(help = "Output file to summarize")]
+        input: PathBuf,
+    },
+    /// Work with --audit-chain files
+    Audit {
+        #[command(subcommand)]
+        action: AuditCommand,
+    },
+    /// Chunk every fixture in a folder and compare it with its <stem>.expected.yaml
+    Conformance {
+        #[arg(help = "Folder with the fixtures")]
+        dir: PathBuf,
+
+        #[arg(long, help = "Rewrite the expected files from the current output")]
+        bless: bool,
+    },
+    /// Compare two JSONL outputs by chunk id
+    Diff {
+        #[arg(help = "Earlier output file")]
+        old: PathBuf,
+
+        #[arg(help = "Later output file")]
+        new: PathBuf,
+
+        #[arg(long, help = "Print every added (+), removed (-) and changed (~) id")]
+        list: bool,
+
+        #[arg(long, default_value = "512M", value_parser = parse_byte_size, help = "Memory for chunk ids before they spill to temp files (Example: 2G)")]
+        max_memory: usize,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum AuditCommand {
+    /// Check an output against its chain and the chunks' current source lines
+    Verify {
+        #[arg(long, help = "Output file the chain was written for")]
+        output: PathBuf,
+
+        #[arg(long, help = "Chain file written by --audit-chain")]
+        chain: PathBuf,
+
+        #[arg(
+            long,
+            help = "Folder the output's file paths are relative to, in place of the one the chain records. For a tree checked out elsewhere"
+        )]
+        root: Option<PathBuf>,
+    },
 }
 
-fn main() -> Result<()> {
+fn parse_token_limit(value: &str) -> Result<TokenLimit, String> {
+    let parse = |n: &str| {
+        n.trim()
+            .parse::<usize>()
+            .map_err(|_| format!("invalid token count: {}", n))
+    };
+    match value.split_once('=') {
+        Some((language, n)) => Ok(TokenLimit::Language(
+            language.trim().to_lowercase(),
+            parse(n)?,
+        )),
+        None => Ok(TokenLimit::Default(parse(value)?)),
+    }
+}
+
+fn parse_byte_size(value: &str) -> Result<usize, String> {
+    let value = value.trim();
+    let (digits, shift) = match value.to_ascii_uppercase().chars().last() {
+        Some('K') => (&value[..value.len() - 1], 10),
+        Some('M') => (&value[..value.len() - 1], 20),
+        Some('G') => (&value[..value.len() - 1], 30),
+        _ => (value, 0),
+    };
+    digits
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_mul(1 << shift))
+        .ok_or_else(|| format!("invalid size: {}", value))
+}
+
+fn parse_context_filter(value: &str) -> Result<ContextPart, String> {
+    match value.split_once('=') {
+        Some((kind, name)) if !kind.trim().is_empty() => Ok(ContextPart {
+            kind: kind.trim().to_string(),
+            name: name.trim().to_string(),
+        }),
+        _ => Err(format!("expected KIND=NAME, got: {}", value)),
+    }
+}
+
+// Config settings fill in the flags that weren't given. Token limits are merged with
+// the config's in Config::token_limits
+fn apply_config(args: &mut Args, config: &config::Config) {
+    if args.path.is_empty() {
+        args.path.extend(config.path.clone());
+    }
+    if args.output.is_none() {
+        args.output = config.output.clone();
+    }
+    if args.since.is_none() {
+        args.since = config.since.clone();
+    }
+    if args.include.is_empty() {
+        args.include = config.include_patterns.clone();
+    }
+    if args.exclude.is_empty() {
+        args.exclude = config.exclude_patterns.clone();
+    }
+}
+
+fn build_options(args: &Args, config: &config::Config) -> Result<ChunkOptions> {
+    let (max_chunk_tokens, language_max_tokens) = config.token_limits(None, &args.max_chunk_tokens)?;
+
+    let mut id_fields = args.id_fields.clone();
+    id_fields.sort_unstable();
+    id_fields.dedup();
+    if id_fields.is_empty() {
+        return Err(anyhow!("--id-fields needs at least one component"));
+    }
+    if id_fields.contains(&IdField::Part) && !id_fields.contains(&IdField::Content) {
+        return Err(anyhow!(
+            "--id-fields part only tells apart the pieces of a split chunk, use it together with content"
+        ));
+    }
+
+    let at_rev = match (&args.at_rev, args.path.as_slice()) {
+        (Some(rev), [path]) => {
+            let repo = PathBuf::from(path);
+            let commit = git::resolve_commit(&repo, rev)?;
+            Some(GitRevision {
+                blobs: Arc::new(git::BlobReader::new(&repo)),
+                repo,
+                commit: Arc::from(commit),
+            })
+        }
+        (Some(_), []) => return Err(anyhow!("--at-rev needs --path to the repository")),
+        (Some(_), _) => return Err(anyhow!("--at-rev reads one repository, give a single --path")),
+        (None, _) => None,
+    };
+    // With several paths, --print-paths relative is relative to the folder they share
+    let mut roots = args.path.iter().map(|path| {
+        let root = types::absolute_path(Path::new(path));
+        if root.is_file() {
+            root.parent()
+                .map_or_else(|| root.clone(), Path::to_path_buf)
+        } else {
+            root
+        }
+    });
+    let first = roots
+        .next()
+        .unwrap_or_else(|| types::absolute_path(Path::new(".")));
+    let path_base = roots.fold(first, |base, root| {
+        base.ancestors()
+            .find(|ancestor| root.starts_with(ancestor))
+            .map_or(base.clone(), Path::to_path_buf)
+    });
+
+    Ok(ChunkOptions {
+        max_chunk_tokens,
+        language_max_tokens,
+        id_format: args.id_format,
+        id_fields,
+        markers: args.markers.clone(),
+        stale_retries: args.stale_retries,
+        emit_gaps: args.emit_gaps,
+        parse_timeout_ms: args.parse_timeout_ms,
+        max_comment_gap: args.max_comment_gap,
+        coverage: args.coverage || args.coverage_report.is_some(),
+        context_filters: args.context_filter.clone(),
+        small_file_threshold: args.small_file_threshold,
+        emit_normalized: args.emit_normalized,
+        debug_fields: args.debug_fields,
+        wide_line_threshold: args.wide_line_threshold,
+        lsp_ranges: args.lsp_ranges,
+        max_context_chars: args.max_context_chars,
+        max_signature_chars: args.max_signature_chars,
+        split_strategy: args.split_strategy,
+        split_lookahead: args.split_lookahead,
+        overlap_tokens: args.overlap_tokens,
+        at_rev,
+        print_paths: args.print_paths,
+        audit: args.audit_chain.is_some(),
+        path_base,
+        tokenizer: Tokenizer::default(),
+        #[cfg(feature = "cache")]
+        cache: None,
+        min_tokens: args.min_tokens,
+        max_tokens: args.max_tokens,
+        max_tokens_factor: args.max_tokens_factor,
+        token_filtered: Arc::default(),
+        type_filters: args.filter_type.clone(),
+    })
+}
+
+// The --cache of a run. Git already narrows --since down to the changed files, and runs
+// that write nothing only read the cache and don't create one
+#[cfg(feature = "cache")]
+fn open_cache(args: &Args, config: &config::Config, options: &ChunkOptions) -> Result<Option<Arc<FileCache>>> {
+    // Chunks cached under other options or by another version are stale, and project
+    // overrides change the options of their files
+    let projects: BTreeMap<_, _> = config.project.iter().collect();
+    let fingerprint = hash::compute_hash(&format!("{}\nprojects={:?}", options.fingerprint(), projects));
+    Ok(match (&args.cache, &args.since) {
+        (Some(path), None) if args.dry_run || args.list_types => {
+            FileCache::open_read_only(path, args.cache_replay, &fingerprint)?.map(Arc::new)
+        }
+        (Some(path), None) => Some(Arc::new(FileCache::open(path, args.cache_replay, &fingerprint)?)),
+        (Some(_), Some(_)) => {
+            println!("--cache is not used together with --since");
+            None
+        }
+        (None, _) => None,
+    })
+}
+
+// `[=====>    ] 34/200 files (17%)` with the elapsed time, an ETA and the file being
+// chunked. Hidden with --no-progress, and by indicatif when stderr isn't a terminal
+fn progress_bar(files: usize, no_progress: bool) -> ProgressBar {
+    if no_progress {
+        return ProgressBar::hidden();
+    }
+    let style = ProgressStyle::with_template(
+        "[{bar:30}] {pos}/{len} files ({percent}%) [{elapsed_precise}, ETA {eta}] - Processing {msg}",
+    )
+    .expect("valid progress template")
+    .progress_chars("=> ");
+    ProgressBar::new(files as u64).with_style(style)
+}
+
+// Errors print as one line per cause, never with a backtrace full of the build
+// machine's source paths
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("Error: {:#}", err);
+        std::process::exit(1);
+    }
+}
+
+fn run() -> Result<()> {
     let args = Args::parse();
-    let files: Vec<PathBuf> = files::get_files(&args.path, &args.since)?;
-    if files.is_empty() {
+    match &args.command {
+        Some(Command::Lint { input, max_memory }) => return lint::lint_output(input, *max_memory),
+        Some(Command::Stats { input }) => return stats::run_stats(input),
+        Some(Command::Diff {
+            old,
+            new,
+            list,
+            max_memory,
+        }) => return diff::run_diff(old, new, *list, *max_memory),
+        Some(Command::Audit {
+            action:
+                AuditCommand::Verify {
+                    output,
+                    chain,
+                    root,
+                },
+        }) => return audit::run_verify(output, chain, root.as_deref()),
+        Some(Command::Conformance { dir, bless }) => {
+            return conformance::run_conformance(dir, *bless, &conformance::fixture_options());
+        }
+        Some(Command::Doctor { path }) => return doctor::run_doctor(path),
+        Some(Command::Init { target, dir, force }) => {
+            return init::run_init(*target, dir, *force, |flags| {
+                let argv = ["smart-chunker", "--path", "."]
+                    .into_iter()
+                    .chain(flags.iter().map(String::as_str));
+                build_options(&Args::try_parse_from(argv)?, &config::Config::default()).map(|_| ())
+            });
+        }
+        // Needs the config's filter, handled once it is loaded
+        Some(Command::Explain { .. }) | None => {}
+    }
+    if lang_driver::supported_languages().is_empty() {
+        return Err(anyhow!(
+            "No language drivers compiled in, enable at least one lang-* feature"
+        ));
+    }
+    doctor::check_grammars(args.strict)?;
+    let config = config::load(args.config.as_deref())?;
+    let mut args = args;
+    apply_config(&mut args, &config);
+    // Explained against the same filter a run builds
+    if let Some(Command::Explain { path }) = &args.command {
+        let filter = FileFilter::new(&config.languages, &args.include, &args.exclude)?;
+        let root = args.path.iter().map(Path::new).find(|root| path.starts_with(root));
+        return explain::run_explain(path, &filter, root.unwrap_or(Path::new("")));
+    }
+    if args.path.is_empty() && !args.from_patch {
+        return Err(anyhow!(
+            "--path is required unless `path` is set in the config or --from-patch is given"
+        ));
+    }
+    // Both read the chunks back as JSONL records
+    if args.format != OutputFormat::Jsonl && (args.post_process.is_some() || args.audit_chain.is_some()) {
+        return Err(anyhow!("--post-process and --audit-chain need --format jsonl"));
+    }
+    // Only JSONL stays valid with records appended to it
+    if args.format != OutputFormat::Jsonl && args.watch {
+        return Err(anyhow!("--watch needs --format jsonl"));
+    }
+    // Builds without a feature keep its flags, so the same scripts get a clear error
+    if args.cache.is_some() && cfg!(not(feature = "cache")) {
+        return Err(anyhow!("--cache needs a build with the cache feature"));
+    }
+    if args.watch && cfg!(not(feature = "watch")) {
+        return Err(anyhow!("--watch needs a build with the watch feature"));
+    }
+    if args.watch && args.path.len() > 1 {
+        return Err(anyhow!("--watch watches one folder, give a single --path"));
+    }
+    // The chunker itself never connects anywhere, only commands it runs might. Every
+    // setting that hands chunks to one belongs here
+    if let (true, Some(command)) = (args.offline, &args.post_process) {
+        return Err(anyhow!(
+            "--offline: --post-process runs `{}`, which may reach the network",
+            command
+        ));
+    }
+    let output = args.output.clone().unwrap_or_else(|| "output.jsonl".to_string());
+    let options = build_options(&args, &config)?;
+    // Each pool thread keeps its own parser, see the map_init below
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(args.threads)
+        .build_global()?;
+    if args.assert_read_only {
+        safety::protect(&args.path.iter().map(PathBuf::from).collect::<Vec<_>>());
+        safety::check_writable("--output", Path::new(&output))?;
+        if let Some(path) = &args.coverage_report {
+            safety::check_writable("--coverage-report", path)?;
+        }
+        if let Some(path) = &args.audit_chain {
+            safety::check_writable("--audit-chain", path)?;
+        }
+        if let Some(path) = &args.cache {
+            safety::check_writable("--cache", path)?;
+        }
+    }
+    #[cfg(feature = "cache")]
+    let options = ChunkOptions {
+        cache: open_cache(&args, &config, &options)?,
+        ..options
+    };
+    let filter = FileFilter::new(&config.languages, &args.include, &args.exclude)?;
+    let files: Vec<PathBuf> = match &options.at_rev {
+        Some(rev) => git::files_at_commit(&rev.repo, &rev.commit, args.since.as_deref())?
+            .into_iter()
+            .filter(|file| filter.keeps(file, Path::new("")))
+            .collect(),
+        // Overlapping paths, like `src` and `src/parser`, find some files twice
+        None => {
+            let mut files = Vec::new();
+            for path in &args.path {
+                files.extend(files::get_files(path, &args.since, &filter)?);
+            }
+            files::dedupe_files(files)
+        }
+    };
+    if files.is_empty() && !args.from_patch {
         println!("No files found in the specified path.");
         return Ok(());
     }
+    let workspace = Workspace::detect(
+        &files,
+        options.at_rev.as_ref().map(|rev| rev.repo.as_path()),
+    );
+    let mut project_options = HashMap::new();
+    for (project, overrides) in &config.project {
+        if !workspace.contains(project) {
+            eprintln!("WARNING: config overrides unknown project {:?}", project);
+        }
+        let mut project_option = options.clone();
+        (project_option.max_chunk_tokens, project_option.language_max_tokens) =
+            config.token_limits(Some(overrides), &args.max_chunk_tokens)?;
+        project_options.insert(project.as_str(), project_option);
+    }
 
     let (tx, rx) = bounded::<ChunkData>(1000);
+    let progress = progress_bar(files.len(), args.no_progress);
 
-    let output_path = args.output.clone();
-    let writer_handle = thread::spawn(move || -> Result<usize> {
-        let file = OpenOptions::new()
-            .create(true)
-            .write(true)
-            .open(&output_path)?;
+    let output_path = output.clone();
+    let report = args.report;
+    let dry_run = args.dry_run;
+    let list_types = args.list_types;
+    let deduplicate = args.deduplicate;
+    let post_process = args.post_process.clone();
+    let post_process_batch_size = args.post_process_batch_size;
+    let format = args.format;
+    let post_process_timeout_secs = args.post_process_timeout_secs;
+    let pair_headers = args.pair_headers;
+    let audit_chain = args.audit_chain.clone();
+    let chain_base = options.path_root();
+    let sort_by_file = args.audit_chain.is_some();
+    let meta = OutputMeta::new(&options);
+    let writer_progress = progress.clone();
+    let writer_handle = thread::spawn(move || -> Result<RunStats> {
+        // A dry run or type listing only counts, no file is created
+        let mut writer: Option<Box<dyn OutputWriter>> = match dry_run || list_types {
+            true => None,
+            false => {
+                let file = BufWriter::new(safety::create_file("--output", Path::new(&output_path))?);
+                meta.write(Path::new(&output_path))?;
+                Some(match post_process {
+                    Some(command) => {
+                        let timeout = Duration::from_secs(post_process_timeout_secs);
+                        let post_processor = PostProcessor::spawn(&command, timeout)?;
+                        Box::new(PostProcessWriter::new(post_processor, post_process_batch_size, file))
+                    }
+                    None => create_writer(format, file),
+                })
+            }
+        };
+        let mut stats = RunStats::new(report == Some(Report::Markers), dry_run, list_types);
+        let mut chain = audit_chain
+            .as_deref()
+            .map(|path| audit::ChainWriter::create(path, &chain_base))
+            .transpose()?;
 
-        let mut writer = BufWriter::new(file);
-        let mut count = 0;
+        let mut written_hashes = HashSet::new();
 
+        let mut emit = |chunk: ChunkData| -> Result<()> {
+            // Gap records have no code to compare
+            if deduplicate && chunk.reason.is_none() && !written_hashes.insert(chunk.structural_hash.clone()) {
+                stats.duplicates += 1;
+                return Ok(());
+            }
+            // Chained in output order, so the chain verifies against this very file
+            if let Some(chain) = &mut chain {
+                chain.append(&chunk)?;
+            }
+            stats.add(&chunk);
+            let Some(writer) = &mut writer else {
+                return Ok(());
+            };
+            writer.write_chunk(&chunk)?;
+            // The bar already shows how far the run is
+            if stats.chunks % 10 == 0 && writer_progress.is_hidden() {
+                println!("{} chunks written to file...", stats.chunks);
+            }
+            Ok(())
+        };
+        // Header and source halves can come from any thread in any order, so they
+        // are held until every file is done. So is everything under --audit-chain,
+        // which is written by file and line to chain the same way every run
+        let mut held = Vec::new();
+        let mut unpaired = Vec::new();
         for chunk in rx {
-            writeln!(writer, "{}", serde_json::to_string(&chunk)?)?;
-            count += 1;
-            if count % 10 == 0 {
-                println!("{} chunks written to file...", count);
+            if pair_headers && pairing::is_pairable(&chunk.file_path) {
+                held.push(chunk);
+            } else if sort_by_file {
+                unpaired.push(chunk);
+            } else {
+                emit(chunk)?;
             }
         }
-        Ok(count)
+        pairing::pair_headers(&mut held);
+        held.append(&mut unpaired);
+        // Stable, so the parts of a file keep the order its worker sent them in
+        if sort_by_file {
+            held.sort_by(|a, b| (&a.file_path, a.start_line).cmp(&(&b.file_path, b.start_line)));
+        }
+        for chunk in held {
+            emit(chunk)?;
+        }
+        if let Some(writer) = &mut writer {
+            writer.finalize()?;
+        }
+        if let Some(chain) = chain {
+            chain.finish()?;
+        }
+        Ok(stats)
     });
 
     println!(
@@ -78,25 +885,115 @@ fn main() -> Result<()> {
         rayon::current_num_threads()
     );
 
-    let max_chunk_tokens = args.max_chunk_tokens;
-    files.par_iter().for_each_init(
-        || TreeParser::new(),
-        |parser, path| {
-            let tx_clone = tx.clone();
-            if let Err(err) = process_file(path, parser, &tx_clone, max_chunk_tokens) {
-                eprintln!("Error processing file {}: {}", path.display(), err);
+    if args.from_patch {
+        let mut patch = String::new();
+        io::stdin().read_to_string(&mut patch)?;
+        let mut chunks = find_hunk_chunks("<stdin>", &patch, &options);
+        hash::disambiguate_ids(&mut chunks, options.id_format);
+        files::cap_lengths(&mut chunks, &options);
+        hash::add_structural_hashes(&mut chunks);
+        if options.emit_normalized {
+            files::add_normalized_code(&mut chunks, &options.tokenizer);
+        }
+        if options.lsp_ranges {
+            lsp::add_lsp_ranges(&mut chunks, &patch);
+        }
+        for chunk in chunks {
+            if tx.send(chunk).is_err() {
+                break;
             }
-        },
-    );
+        }
+    }
+
+    let outcomes: Vec<FileOutcome> = files
+        .par_iter()
+        .map_init(TreeParser::new, |parser, path| {
+            let tx_clone = tx.clone();
+            let project = workspace.project_of(path);
+            let options = project
+                .as_deref()
+                .and_then(|project| project_options.get(project))
+                .unwrap_or(&options);
+            let shown = options.shown_path(path);
+            progress.set_message(shown.display().to_string());
+            let outcome = process_file(path, project, parser, &tx_clone, options)
+                .map_err(|err| {
+                    progress.suspend(|| eprintln!("Error processing file {}: {}", shown.display(), err))
+                })
+                .ok();
+            progress.inc(1);
+            outcome
+        })
+        .flatten()
+        .collect();
+    progress.finish_and_clear();
+    let mut coverage: Vec<FileCoverage> = Vec::new();
+    let mut skipped: BTreeMap<SkipReason, usize> = BTreeMap::new();
+    for outcome in outcomes {
+        match outcome {
+            FileOutcome::Chunked(file_coverage) => coverage.extend(file_coverage),
+            FileOutcome::Skipped(reason) => *skipped.entry(reason).or_default() += 1,
+        }
+    }
 
     drop(tx);
-    let total_chunks = writer_handle
+    let stats = writer_handle
         .join()
-        .map_err(|_| anyhow!("Writer thread panicked"))?;
-    println!(
-        "Processing completed. Total chunks written: {:?}",
-        total_chunks
-    );
-    println!("Output file: {}", args.output);
+        .map_err(|_| anyhow!("Writer thread panicked"))??;
+    if report == Some(Report::Markers) {
+        println!("Markers ({}):", stats.marker_rows.len());
+        for (file, line, kind, text) in &stats.marker_rows {
+            println!("{}:{} [{}] {}", file, line, kind, text);
+        }
+    }
+    if dry_run {
+        stats.print_files();
+        println!("Dry run, nothing written. Total chunks: {}", stats.chunks);
+    } else if list_types {
+        stats.print_types();
+        println!("Types listed, nothing written. Total chunks: {}", stats.chunks);
+    } else {
+        println!("Processing completed. Total chunks written: {:?}", stats.chunks);
+        println!("Output file: {}", output);
+    }
+    if args.deduplicate {
+        println!("Duplicates skipped: {}", stats.duplicates);
+    }
+    if args.min_tokens > 0 {
+        let dropped = options.token_filtered.parts_under_min.load(Ordering::Relaxed);
+        println!("Under --min-tokens skipped: {} chunks", dropped);
+    }
+    if args.max_tokens.is_some() {
+        let skipped = options.token_filtered.items_over_max.load(Ordering::Relaxed);
+        println!("Over --max-tokens skipped: {} items", skipped);
+    }
+    for (reason, count) in filter.skipped() {
+        if count > 0 {
+            *skipped.entry(reason).or_default() += count;
+        }
+    }
+    for (reason, count) in &skipped {
+        println!("Skipped ({}): {} files", reason.describe(), count);
+    }
+    if options.coverage {
+        coverage.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+        coverage::print_summary(&coverage);
+        if let Some(path) = &args.coverage_report {
+            coverage::write_report(path, &coverage)?;
+            println!("Coverage report: {}", path.display());
+        }
+    }
+    #[cfg(feature = "watch")]
+    if let (true, [path]) = (args.watch, args.path.as_slice()) {
+        let mut parser = TreeParser::new();
+        watch::run_watch(Path::new(path), Path::new(&output), &filter, &options, |file, tx| {
+            let project = workspace.project_of(file);
+            let options = project
+                .as_deref()
+                .and_then(|project| project_options.get(project))
+                .unwrap_or(&options);
+            process_file(file, project, &mut parser, tx, options)
+        })?;
+    }
     Ok(())
 }
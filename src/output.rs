@@ -0,0 +1,208 @@
+use crate::safety;
+use crate::types::{ChunkData, ChunkId, ChunkOptions, IdField, IdFormat, OutputFormat};
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// How a run made its ids, written next to the output as `<output>.meta.json` so lint, diff
+// and stats can tell without a record in the output that isn't a chunk
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct OutputMeta {
+    pub version: String,
+    pub id_format: IdFormat,
+    pub id_fields: Vec<IdField>,
+}
+
+impl OutputMeta {
+    pub fn new(options: &ChunkOptions) -> Self {
+        OutputMeta {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            id_format: options.id_format,
+            id_fields: options.id_fields.clone(),
+        }
+    }
+
+    pub fn path(output: &Path) -> PathBuf {
+        let mut path = output.as_os_str().to_owned();
+        path.push(".meta.json");
+        PathBuf::from(path)
+    }
+
+    pub fn write(&self, output: &Path) -> Result<()> {
+        let mut file = safety::create_file("--output", &Self::path(output))?;
+        writeln!(file, "{}", serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    // None for outputs written before the sidecar existed, or copied without it
+    pub fn read(output: &Path) -> Result<Option<Self>> {
+        let path = Self::path(output);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let text = fs::read_to_string(&path)?;
+        let meta = serde_json::from_str(&text).with_context(|| format!("Invalid {}", path.display()))?;
+        Ok(Some(meta))
+    }
+
+    // Like "hex of content, path, part", in the names --id-format and --id-fields take
+    pub fn describe(&self) -> String {
+        let fields: Vec<String> = self.id_fields.iter().map(value_name).collect();
+        format!("{} of {}", value_name(&self.id_format), fields.join(", "))
+    }
+}
+
+pub fn value_name(value: &impl ValueEnum) -> String {
+    value.to_possible_value().map_or_else(String::new, |value| value.get_name().to_string())
+}
+
+// Where the chunks of a run go, one at a time in output order. `finalize` is called once
+// after the last chunk and writes whatever closes the format
+pub trait OutputWriter {
+    fn write_chunk(&mut self, chunk: &ChunkData) -> Result<()>;
+    fn finalize(&mut self) -> Result<()>;
+}
+
+pub fn create_writer<W: Write + 'static>(format: OutputFormat, out: W) -> Box<dyn OutputWriter> {
+    match format {
+        OutputFormat::Jsonl => Box::new(JsonlWriter { out }),
+        OutputFormat::Json => Box::new(JsonArrayWriter { out, written: 0 }),
+        OutputFormat::Csv => Box::new(CsvWriter { out, header_written: false }),
+        OutputFormat::Markdown => Box::new(MarkdownWriter { out }),
+    }
+}
+
+struct JsonlWriter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> OutputWriter for JsonlWriter<W> {
+    fn write_chunk(&mut self, chunk: &ChunkData) -> Result<()> {
+        writeln!(self.out, "{}", serde_json::to_string(chunk)?)?;
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> Result<()> {
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+// Streamed element by element, the brackets are only written around them
+struct JsonArrayWriter<W: Write> {
+    out: W,
+    written: usize,
+}
+
+impl<W: Write> OutputWriter for JsonArrayWriter<W> {
+    fn write_chunk(&mut self, chunk: &ChunkData) -> Result<()> {
+        let separator = if self.written == 0 { "[\n" } else { ",\n" };
+        write!(self.out, "{}{}", separator, serde_json::to_string(chunk)?)?;
+        self.written += 1;
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> Result<()> {
+        match self.written {
+            0 => writeln!(self.out, "[]")?,
+            _ => writeln!(self.out, "\n]")?,
+        }
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+const CSV_COLUMNS: &str =
+    "id,file_path,language,chunk_type,chunk_name,context,signature,start_line,end_line,token_count,code";
+
+// The fields a spreadsheet can show, quoted as RFC 4180 has it
+struct CsvWriter<W: Write> {
+    out: W,
+    header_written: bool,
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn id_text(id: &ChunkId) -> String {
+    match id {
+        ChunkId::Text(text) => text.clone(),
+        ChunkId::Int(n) => n.to_string(),
+    }
+}
+
+impl<W: Write> OutputWriter for CsvWriter<W> {
+    fn write_chunk(&mut self, chunk: &ChunkData) -> Result<()> {
+        if !self.header_written {
+            writeln!(self.out, "{}", CSV_COLUMNS)?;
+            self.header_written = true;
+        }
+        let fields = [
+            csv_field(&id_text(&chunk.id)),
+            csv_field(&chunk.file_path),
+            csv_field(&chunk.language),
+            csv_field(&chunk.chunk_type),
+            csv_field(&chunk.chunk_name),
+            csv_field(&chunk.context),
+            csv_field(&chunk.signature),
+            chunk.start_line.to_string(),
+            chunk.end_line.to_string(),
+            chunk.token_count.to_string(),
+            csv_field(&chunk.code),
+        ];
+        writeln!(self.out, "{}", fields.join(","))?;
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> Result<()> {
+        if !self.header_written {
+            writeln!(self.out, "{}", CSV_COLUMNS)?;
+        }
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+// A section per chunk: the file and its lines, the context, then the code fenced with
+// the language as info string
+struct MarkdownWriter<W: Write> {
+    out: W,
+}
+
+// One backtick more than the longest run in the code, so the code can't close it
+fn fence(code: &str) -> String {
+    let longest = code
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    "`".repeat((longest + 1).max(3))
+}
+
+impl<W: Write> OutputWriter for MarkdownWriter<W> {
+    fn write_chunk(&mut self, chunk: &ChunkData) -> Result<()> {
+        writeln!(self.out, "## {}:{}-{}", chunk.file_path, chunk.start_line, chunk.end_line)?;
+        writeln!(self.out)?;
+        writeln!(self.out, "`{}` {} `{}`", chunk.context, chunk.chunk_type, chunk.chunk_name)?;
+        writeln!(self.out)?;
+        let fence = fence(&chunk.code);
+        writeln!(self.out, "{}{}", fence, chunk.language.to_lowercase())?;
+        writeln!(self.out, "{}", chunk.code.trim_end_matches('\n'))?;
+        writeln!(self.out, "{}", fence)?;
+        writeln!(self.out)?;
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> Result<()> {
+        self.out.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,127 @@
+use crate::types::{ChunkData, ChunkId};
+use std::collections::HashMap;
+use std::path::Path;
+
+const HEADER_EXTENSIONS: &[&str] = &["h", "hh", "hpp", "hxx"];
+const SOURCE_EXTENSIONS: &[&str] = &["c", "cc", "cpp", "cxx"];
+
+fn extension(path: &str) -> String {
+    Path::new(path)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+fn is_header(path: &str) -> bool {
+    HEADER_EXTENSIONS.contains(&extension(path).as_str())
+}
+
+// Chunks the writer holds back under --pair-headers until the whole run is in
+pub fn is_pairable(path: &str) -> bool {
+    let extension = extension(path);
+    HEADER_EXTENSIONS.contains(&extension.as_str())
+        || SOURCE_EXTENSIONS.contains(&extension.as_str())
+}
+
+// Header and source pair up when their paths match without the extension and any
+// `include` or `src` directory: a/foo.h with a/foo.c, lib/include/net/sock.h with
+// lib/src/net/sock.c
+fn pairing_key(path: &str) -> String {
+    Path::new(path)
+        .with_extension("")
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .filter(|c| c != "include" && c != "src")
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+// Top-level parameters in the list right after the first `name(` of the text, None
+// when the name isn't followed by a complete list. `()` and `(void)` count as none
+fn parameter_count(text: &str, name: &str) -> Option<usize> {
+    let open = text.match_indices(name).find_map(|(i, _)| {
+        let after = i + name.len();
+        let rest = &text[after..];
+        let trimmed = rest.trim_start();
+        trimmed
+            .starts_with('(')
+            .then(|| after + rest.len() - trimmed.len())
+    })?;
+
+    let (mut depth, mut commas) = (0, 0);
+    for (i, c) in text[open..].char_indices() {
+        match c {
+            '(' | '[' | '{' | '<' => depth += 1,
+            ')' | ']' | '}' | '>' => {
+                depth -= 1;
+                if depth == 0 {
+                    let list = text[open + 1..open + i].trim();
+                    return Some(match list {
+                        "" | "void" => 0,
+                        _ => commas + 1,
+                    });
+                }
+            }
+            ',' if depth == 1 => commas += 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+// Parts of one split definition share everything but code and lines, and only the
+// first part holds the parameter list
+fn logical_key(chunk: &ChunkData) -> (&str, &str, &str, &str) {
+    (
+        &chunk.file_path,
+        &chunk.chunk_name,
+        &chunk.context,
+        &chunk.signature,
+    )
+}
+
+// Attaches the header declaration's comment and id to the matching definition in the
+// paired source file, matched by name and parameter count. No preprocessing, a
+// declaration in any other header is not found
+pub fn pair_headers(chunks: &mut [ChunkData]) {
+    let mut counts: HashMap<(&str, &str, &str, &str), usize> = HashMap::new();
+    for chunk in chunks.iter() {
+        // Skip the leading comment, which may mention the name too
+        let start = chunk.code.find(chunk.signature.trim()).unwrap_or(0);
+        if let Some(count) = parameter_count(&chunk.code[start..], &chunk.chunk_name) {
+            counts.entry(logical_key(chunk)).or_insert(count);
+        }
+    }
+
+    let mut declarations: HashMap<(String, &str, usize), (&ChunkId, &str)> = HashMap::new();
+    for chunk in chunks.iter().filter(|c| is_header(&c.file_path)) {
+        if let Some(count) = counts.get(&logical_key(chunk)) {
+            declarations
+                .entry((pairing_key(&chunk.file_path), &chunk.chunk_name, *count))
+                .or_insert((&chunk.id, &chunk.comment));
+        }
+    }
+
+    let mut matches = Vec::new();
+    for (index, chunk) in chunks.iter().enumerate() {
+        if is_header(&chunk.file_path) {
+            continue;
+        }
+        let key = counts.get(&logical_key(chunk)).map(|count| {
+            (
+                pairing_key(&chunk.file_path),
+                chunk.chunk_name.as_str(),
+                *count,
+            )
+        });
+        if let Some((id, comment)) = key.and_then(|key| declarations.get(&key)) {
+            let comment = (!comment.is_empty()).then(|| comment.to_string());
+            matches.push((index, (*id).clone(), comment));
+        }
+    }
+
+    for (index, id, comment) in matches {
+        chunks[index].declared_in = Some(id);
+        chunks[index].header_comment = comment;
+    }
+}
@@ -0,0 +1,153 @@
+use crate::files::{find_markers, split_text_by_token_limit};
+use crate::hash::compute_chunk_id;
+use crate::types::{ChunkData, ChunkOptions, HunkRange};
+use std::sync::Arc;
+
+pub const PATCH_LANGUAGE: &str = "Diff";
+
+pub fn is_patch_extension(extension: &str) -> bool {
+    matches!(extension, "patch" | "diff")
+}
+
+struct Hunk<'a> {
+    file: String,
+    header: &'a str,
+    range: HunkRange,
+    body: Vec<&'a str>,
+    body_start_row: usize,
+}
+
+// Parses "@@ -120,8 +120,12 @@ optional section", a missing count means 1
+fn parse_hunk_header(line: &str) -> Option<HunkRange> {
+    let mut parts = line.strip_prefix("@@ ")?.split_whitespace();
+    let (old_start, old_lines) = parse_range(parts.next()?.strip_prefix('-')?)?;
+    let (new_start, new_lines) = parse_range(parts.next()?.strip_prefix('+')?)?;
+    Some(HunkRange {
+        old_start,
+        old_lines,
+        new_start,
+        new_lines,
+    })
+}
+
+fn parse_range(range: &str) -> Option<(usize, usize)> {
+    match range.split_once(',') {
+        Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+        None => Some((range.parse().ok()?, 1)),
+    }
+}
+
+fn strip_diff_prefix(path: &str) -> &str {
+    let path = path.split('\t').next().unwrap_or(path).trim();
+    path.strip_prefix("a/")
+        .or_else(|| path.strip_prefix("b/"))
+        .unwrap_or(path)
+}
+
+fn parse_hunks(text: &str) -> Vec<Hunk<'_>> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut hunks = Vec::new();
+    let mut old_file = String::new();
+    let mut new_file = String::new();
+    let mut row = 0;
+
+    while row < lines.len() {
+        let line = lines[row];
+        if let Some(path) = line.strip_prefix("--- ") {
+            old_file = strip_diff_prefix(path).to_string();
+        } else if let Some(path) = line.strip_prefix("+++ ") {
+            new_file = strip_diff_prefix(path).to_string();
+        } else if let Some(range) = parse_hunk_header(line) {
+            // Deleted files only have a meaningful old path
+            let file = if new_file == "/dev/null" || new_file.is_empty() {
+                old_file.clone()
+            } else {
+                new_file.clone()
+            };
+
+            let mut old_left = range.old_lines;
+            let mut new_left = range.new_lines;
+            let mut end = row + 1;
+            while end < lines.len()
+                && (old_left > 0 || new_left > 0 || lines[end].starts_with('\\'))
+            {
+                match lines[end].chars().next() {
+                    Some('-') => old_left = old_left.saturating_sub(1),
+                    Some('+') => new_left = new_left.saturating_sub(1),
+                    Some('\\') => {}
+                    // Context line, some tools strip the leading space of empty ones
+                    Some(' ') | None => {
+                        old_left = old_left.saturating_sub(1);
+                        new_left = new_left.saturating_sub(1);
+                    }
+                    Some(_) => break,
+                }
+                end += 1;
+            }
+
+            hunks.push(Hunk {
+                file,
+                header: line,
+                range,
+                body: lines[row + 1..end].to_vec(),
+                body_start_row: row + 1,
+            });
+            row = end;
+            continue;
+        }
+        row += 1;
+    }
+    hunks
+}
+
+pub fn find_hunk_chunks(file_path: &str, content: &str, options: &ChunkOptions) -> Vec<ChunkData> {
+    let mut chunks = Vec::new();
+    let max_tokens = options.max_tokens_for(PATCH_LANGUAGE);
+    let file_path: Arc<str> = Arc::from(file_path);
+    let language: Arc<str> = Arc::from(PATCH_LANGUAGE);
+    let chunk_type: Arc<str> = Arc::from("hunk");
+    for hunk in parse_hunks(content) {
+        let context: Arc<str> = Arc::from(hunk.file.as_str());
+        let chunk_name = format!(
+            "{}@@-{},{} +{},{}",
+            hunk.file,
+            hunk.range.old_start,
+            hunk.range.old_lines,
+            hunk.range.new_start,
+            hunk.range.new_lines
+        );
+        let body = hunk.body.join("\n");
+        let body_start_line = hunk.body_start_row + 1;
+
+        let sub_chunks =
+            split_text_by_token_limit(&body, max_tokens, options.overlap_tokens, &options.tokenizer);
+        for (i, (sub_text, token_count, line_offset, overlap_lines)) in sub_chunks.into_iter().enumerate() {
+            // The text starts with the overlap, the chunk right after it
+            let text_start_line = body_start_line + line_offset - overlap_lines;
+            let start_line = body_start_line + line_offset;
+            let markers = find_markers(&sub_text, &options.markers, |l| text_start_line + l);
+
+            let mut chunk = ChunkData {
+                file_path: file_path.clone(),
+                language: language.clone(),
+                chunk_type: chunk_type.clone(),
+                chunk_name: chunk_name.clone(),
+                context: context.clone(),
+                signature: hunk.header.to_string(),
+                comment: String::new(),
+                end_line: text_start_line + sub_text.lines().count().max(1) - 1,
+                start_line,
+                overlap_start_line: (overlap_lines > 0).then_some(text_start_line),
+                code: sub_text,
+                token_count,
+                max_tokens_applied: max_tokens,
+                markers,
+                hunk: Some(hunk.range.clone()),
+                ..Default::default()
+            };
+            chunk.id = compute_chunk_id(&chunk, i, options);
+            chunks.push(chunk);
+        }
+    }
+    chunks
+}
@@ -0,0 +1,228 @@
+use crate::output::OutputWriter;
+use crate::types::ChunkData;
+use anyhow::{Result, anyhow};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender, unbounded};
+use serde_json::Value;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// One long-lived child per run, fed a JSONL batch at a time and expected to answer
+// line-for-line with the same ids in the same order
+pub struct PostProcessor {
+    command: String,
+    child: Child,
+    stdin: Option<Sender<String>>,
+    stdout: Receiver<String>,
+    timeout: Duration,
+}
+
+fn shell_command(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C").arg(command);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c").arg(command);
+        shell
+    }
+}
+
+impl PostProcessor {
+    pub fn spawn(command: &str, timeout: Duration) -> Result<Self> {
+        let mut child = shell_command(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|err| {
+                anyhow!(
+                    "Failed to start post-process command '{}': {}",
+                    command,
+                    err
+                )
+            })?;
+
+        // Writing and reading happen on their own threads so a child that answers
+        // before reading the whole batch can't deadlock on full pipes
+        let mut child_stdin = child.stdin.take().expect("stdin is piped");
+        let (stdin_tx, stdin_rx) = unbounded::<String>();
+        thread::spawn(move || {
+            for batch in stdin_rx {
+                if child_stdin.write_all(batch.as_bytes()).is_err() || child_stdin.flush().is_err()
+                {
+                    break;
+                }
+            }
+        });
+
+        let child_stdout = child.stdout.take().expect("stdout is piped");
+        let (stdout_tx, stdout_rx) = unbounded::<String>();
+        thread::spawn(move || {
+            for line in BufReader::new(child_stdout).lines() {
+                let Ok(line) = line else { break };
+                if stdout_tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(PostProcessor {
+            command: command.to_string(),
+            child,
+            stdin: Some(stdin_tx),
+            stdout: stdout_rx,
+            timeout,
+        })
+    }
+
+    pub fn process(&mut self, batch: &[ChunkData]) -> Result<Vec<String>> {
+        let mut input = String::new();
+        let mut ids = Vec::with_capacity(batch.len());
+        for chunk in batch {
+            input.push_str(&serde_json::to_string(chunk)?);
+            input.push('\n');
+            ids.push(serde_json::to_value(&chunk.id)?);
+        }
+        if let Some(stdin) = &self.stdin {
+            stdin
+                .send(input)
+                .map_err(|_| anyhow!("Post-process command '{}' closed its input", self.command))?;
+        }
+
+        let deadline = Instant::now() + self.timeout;
+        let mut output = Vec::with_capacity(batch.len());
+        for (index, id) in ids.iter().enumerate() {
+            let line = match self
+                .stdout
+                .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            {
+                Ok(line) => line,
+                Err(RecvTimeoutError::Timeout) => {
+                    let _ = self.child.kill();
+                    return Err(anyhow!(
+                        "Post-process command '{}' timed out after {:?} with {} of {} lines answered",
+                        self.command,
+                        self.timeout,
+                        index,
+                        batch.len()
+                    ));
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(anyhow!(
+                        "Post-process command '{}' exited with {} of {} lines answered (missing id {})",
+                        self.command,
+                        index,
+                        batch.len(),
+                        id
+                    ));
+                }
+            };
+
+            let record: Value = serde_json::from_str(&line).map_err(|err| {
+                anyhow!(
+                    "Post-process command '{}' returned invalid JSON: {}",
+                    self.command,
+                    err
+                )
+            })?;
+            match record.get("id") {
+                Some(returned) if returned == id => output.push(line),
+                Some(returned) => {
+                    return Err(anyhow!(
+                        "Post-process command '{}' returned id {} where {} was expected",
+                        self.command,
+                        returned,
+                        id
+                    ));
+                }
+                None => {
+                    return Err(anyhow!(
+                        "Post-process command '{}' returned a record without an id where {} was expected",
+                        self.command,
+                        id
+                    ));
+                }
+            }
+        }
+        Ok(output)
+    }
+
+    // Closes the child's input and checks it neither failed nor answered with extra records
+    pub fn finish(mut self) -> Result<()> {
+        self.stdin.take();
+        let status = self.child.wait()?;
+        let extra = self
+            .stdout
+            .iter()
+            .filter(|line| !line.trim().is_empty())
+            .count();
+        if extra > 0 {
+            return Err(anyhow!(
+                "Post-process command '{}' returned {} more records than it was given",
+                self.command,
+                extra
+            ));
+        }
+        if !status.success() {
+            return Err(anyhow!(
+                "Post-process command '{}' failed: {}",
+                self.command,
+                status
+            ));
+        }
+        Ok(())
+    }
+}
+
+// Sends chunks through the command a batch at a time and writes the lines it answers
+// with, in place of the chunks
+pub struct PostProcessWriter<W: Write> {
+    processor: Option<PostProcessor>,
+    batch: Vec<ChunkData>,
+    batch_size: usize,
+    out: W,
+}
+
+impl<W: Write> PostProcessWriter<W> {
+    pub fn new(processor: PostProcessor, batch_size: usize, out: W) -> Self {
+        PostProcessWriter {
+            processor: Some(processor),
+            batch: Vec::new(),
+            batch_size: batch_size.max(1),
+            out,
+        }
+    }
+
+    fn write_batch(&mut self) -> Result<()> {
+        if let Some(processor) = &mut self.processor {
+            for line in processor.process(&self.batch)? {
+                writeln!(self.out, "{}", line)?;
+            }
+        }
+        self.batch.clear();
+        Ok(())
+    }
+}
+
+impl<W: Write> OutputWriter for PostProcessWriter<W> {
+    fn write_chunk(&mut self, chunk: &ChunkData) -> Result<()> {
+        self.batch.push(chunk.clone());
+        if self.batch.len() >= self.batch_size {
+            self.write_batch()?;
+        }
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> Result<()> {
+        if !self.batch.is_empty() {
+            self.write_batch()?;
+        }
+        if let Some(processor) = self.processor.take() {
+            processor.finish()?;
+        }
+        self.out.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+use crate::types::absolute_path;
+use anyhow::{Result, anyhow};
+use std::fs::{self, File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+// Scanned roots nothing may be written under, set once under --assert-read-only
+static READ_ONLY_ROOTS: OnceLock<Vec<PathBuf>> = OnceLock::new();
+
+// Symlinks are resolved through the deepest existing ancestor, so a path that
+// doesn't exist yet resolves to where the write would really land
+fn resolve(path: &Path) -> PathBuf {
+    let path = absolute_path(path);
+    let mut existing = path.as_path();
+    let mut missing = Vec::new();
+    while !existing.exists() {
+        let (Some(parent), Some(name)) = (existing.parent(), existing.file_name()) else {
+            return path;
+        };
+        missing.push(name);
+        existing = parent;
+    }
+    let mut resolved = fs::canonicalize(existing).unwrap_or_else(|_| existing.to_path_buf());
+    resolved.extend(missing.iter().rev());
+    resolved
+}
+
+pub fn protect(roots: &[PathBuf]) {
+    let _ = READ_ONLY_ROOTS.set(roots.iter().map(|root| resolve(root)).collect());
+}
+
+// `what` names the setting behind the path in the error, like `--output`
+pub fn check_writable(what: &str, path: &Path) -> Result<()> {
+    let Some(roots) = READ_ONLY_ROOTS.get() else {
+        return Ok(());
+    };
+    let resolved = resolve(path);
+    match roots.iter().find(|root| resolved.starts_with(root)) {
+        Some(root) => Err(anyhow!(
+            "--assert-read-only: {} path {} is inside the scanned root {}",
+            what,
+            resolved.display(),
+            root.display()
+        )),
+        None => Ok(()),
+    }
+}
+
+// Every file the tool writes is created through here. Sources are only ever read
+// with fs::read, which opens them read-only
+pub fn create_file(what: &str, path: &Path) -> Result<File> {
+    check_writable(what, path)?;
+    Ok(File::create(path)?)
+}
+
+// Opens an existing output to add to it, like --watch does after the first run
+pub fn append_file(what: &str, path: &Path) -> Result<File> {
+    check_writable(what, path)?;
+    Ok(OpenOptions::new().append(true).open(path)?)
+}
+
+pub fn create_dir_all(what: &str, path: &Path) -> Result<()> {
+    check_writable(what, path)?;
+    Ok(fs::create_dir_all(path)?)
+}
@@ -0,0 +1,1934 @@
+use crate::files::{
+    Tokenizer, context_display, find_markers, is_over_max_tokens, split_text_at_boundaries, whole_file_chunks,
+};
+#[cfg(feature = "lang-markdown")]
+use crate::files::split_text_by_token_limit;
+use crate::hash::compute_chunk_id;
+use crate::types::{ChunkData, ChunkOptions, ContextPart, SplitStrategy};
+use std::path::Path;
+use std::sync::Arc;
+
+// Drivers for languages without a grammar crate that builds against our tree-sitter.
+// Items are found by scanning the text, with comments and strings masked out so the
+// brackets in them don't count
+
+// An item a scanner found, as byte offsets into the file
+pub struct ScannedItem {
+    pub kind: &'static str,
+    pub name: String,
+    // Enclosing scopes, outermost first
+    pub context: Vec<ContextPart>,
+    pub start: usize,
+    pub end: usize,
+    // Declaration part of the item, None for its first line
+    pub signature: Option<String>,
+    // Start of a docstring right above the item, which is its comment then
+    pub doc_start: Option<usize>,
+}
+
+pub trait ScanDriver: Send + Sync {
+    fn get_name(&self) -> &'static str;
+    // Items in source order
+    fn find_items(&self, content: &str) -> Vec<ScannedItem>;
+    // Whether a trimmed line is a comment, those right above an item are its `comment`
+    fn is_comment_line(&self, line: &str) -> bool;
+    // Parts of an item's text over the token limit, for drivers that need to choose
+    // the cuts themselves. None splits it like any item
+    fn split(&self, _text: &str, _max_tokens: usize, _tokenizer: &Tokenizer) -> Option<Vec<Part>> {
+        None
+    }
+    // Items inside an item over the token limit, like the keys of a large mapping, which
+    // are chunked in its place. None or none found splits the item itself
+    fn inner_items(&self, _content: &str, _item: &ScannedItem) -> Option<Vec<ScannedItem>> {
+        None
+    }
+    // Whether a file without items is chunked whole rather than left out
+    fn chunks_whole_file_without_items(&self) -> bool { false }
+}
+
+// (Text, TokenCount, LineOffset, OverlapLines) of a part, as `split_text_at_boundaries`
+// returns them
+pub type Part = (String, usize, usize, usize);
+
+// What in a language's text isn't code
+pub struct Syntax {
+    pub line_comments: &'static [&'static str],
+    pub block_comments: &'static [(&'static str, &'static str)],
+    // Opening and closing delimiters, a backslash escapes the character after it
+    pub strings: &'static [(&'static str, &'static str)],
+    // Code that starts like a comment or string, like Perl's `$#array`
+    pub code: &'static [&'static str],
+    // Postgres strings between a `$tag$` and the same tag, `$$` included
+    pub dollar_quotes: bool,
+}
+
+pub fn find_from(bytes: &[u8], from: usize, needle: &[u8]) -> Option<usize> {
+    bytes
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|n| from + n)
+}
+
+// Whether each byte of `content` is code, false inside comments and strings
+pub fn code_mask(content: &str, syntax: &Syntax) -> Vec<bool> {
+    let bytes = content.as_bytes();
+    let starts = |i: usize, delimiter: &str| bytes[i..].starts_with(delimiter.as_bytes());
+    let mut mask = vec![true; bytes.len()];
+    let mut i = 0;
+    while i < bytes.len() {
+        if let Some(code) = syntax.code.iter().find(|code| starts(i, code)) {
+            i += code.len();
+            continue;
+        }
+        let end = if syntax.line_comments.iter().any(|comment| starts(i, comment)) {
+            find_from(bytes, i, b"\n").unwrap_or(bytes.len())
+        } else if let Some((open, close)) = syntax.block_comments.iter().find(|(open, _)| starts(i, open)) {
+            find_from(bytes, i + open.len(), close.as_bytes()).map_or(bytes.len(), |n| n + close.len())
+        } else if let Some(end) = dollar_quote_end(bytes, i).filter(|_| syntax.dollar_quotes) {
+            end
+        } else if let Some((open, close)) = syntax.strings.iter().find(|(open, _)| starts(i, open)) {
+            let mut j = i + open.len();
+            while j < bytes.len() && !starts(j, close) {
+                j += if bytes[j] == b'\\' { 2 } else { 1 };
+            }
+            (j + close.len()).min(bytes.len())
+        } else {
+            i += 1;
+            continue;
+        };
+        mask[i..end].fill(false);
+        i = end;
+    }
+    mask
+}
+
+// End of the dollar-quoted string opening at `i`. Tags don't start with a digit, `$1`
+// is a parameter
+fn dollar_quote_end(bytes: &[u8], i: usize) -> Option<usize> {
+    if bytes[i] != b'$' || bytes.get(i + 1).is_some_and(u8::is_ascii_digit) {
+        return None;
+    }
+    let tag_len = bytes[i + 1..]
+        .iter()
+        .position(|byte| !(byte.is_ascii_alphanumeric() || *byte == b'_'))?;
+    let close = i + 1 + tag_len;
+    if bytes[close] != b'$' {
+        return None;
+    }
+    let tag = &bytes[i..=close];
+    Some(find_from(bytes, close + 1, tag).map_or(bytes.len(), |n| n + tag.len()))
+}
+
+// Offset right after the bracket closing the one at `open`
+pub fn close_of(content: &str, mask: &[bool], open: usize) -> Option<usize> {
+    let bytes = content.as_bytes();
+    let (opening, closing) = match bytes[open] {
+        b'{' => (b'{', b'}'),
+        b'(' => (b'(', b')'),
+        b'[' => (b'[', b']'),
+        _ => return None,
+    };
+    let mut depth = 0;
+    for (i, &byte) in bytes.iter().enumerate().skip(open) {
+        if !mask[i] {
+            continue;
+        }
+        if byte == opening {
+            depth += 1;
+        } else if byte == closing {
+            depth -= 1;
+            if depth == 0 {
+                return Some(i + 1);
+            }
+        }
+    }
+    None
+}
+
+// First code byte out of `targets` from `from` on, outside any bracket opened after it
+pub fn find_code_byte(content: &str, mask: &[bool], from: usize, targets: &[u8]) -> Option<usize> {
+    let bytes = content.as_bytes();
+    let mut depth = 0;
+    for (i, &byte) in bytes.iter().enumerate().skip(from) {
+        if !mask[i] {
+            continue;
+        }
+        if depth == 0 && targets.contains(&byte) {
+            return Some(i);
+        }
+        match byte {
+            b'(' | b'[' => depth += 1,
+            b')' | b']' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+// Start of the string or comment starting with `open` that ends right above the line
+// starting at `line_start`, when nothing else is on its lines. Docstrings are written
+// that way, and Perl's POD
+pub fn docstring_above(content: &str, mask: &[bool], line_start: usize, open: &str) -> Option<usize> {
+    let bytes = content.as_bytes();
+    let mut close = line_start.checked_sub(1)?;
+    while bytes[close].is_ascii_whitespace() && mask[close] {
+        close = close.checked_sub(1)?;
+    }
+    if mask[close] {
+        return None;
+    }
+    let mut start = close;
+    while start > 0 && !mask[start - 1] {
+        start -= 1;
+    }
+    // A block opening at a line start may hold the newline before it
+    start += content[start..].len() - content[start..].trim_start().len();
+    let line = content[..start].rfind('\n').map_or(0, |n| n + 1);
+    (content[start..].starts_with(open) && content[line..start].trim().is_empty()).then_some(start)
+}
+
+// Identifier at the start of `text`
+pub fn leading_identifier(text: &str) -> Option<&str> {
+    let end = text
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(text.len());
+    (end > 0).then(|| &text[..end])
+}
+
+// Comment lines right above `row`, walking back over at most `max_blank_lines` blank
+// lines at a time. The joined text and the row of each of its lines
+fn preceding_comments(
+    lines: &[&str],
+    row: usize,
+    driver: &dyn ScanDriver,
+    max_blank_lines: usize,
+) -> Option<(String, Vec<usize>)> {
+    let mut comments = Vec::new();
+    let mut rows = Vec::new();
+    let mut blank_lines = 0;
+    for current in (0..row.min(lines.len())).rev() {
+        let line = lines[current].trim();
+        if line.is_empty() {
+            blank_lines += 1;
+            if blank_lines > max_blank_lines {
+                break;
+            }
+            continue;
+        }
+        if !driver.is_comment_line(line) {
+            break;
+        }
+        blank_lines = 0;
+        comments.push(line);
+        rows.push(current);
+    }
+    if comments.is_empty() {
+        return None;
+    }
+    comments.reverse();
+    rows.reverse();
+    Some((comments.join("\n"), rows))
+}
+
+// Chunks a file the way `find_chunks` does for tree-sitter drivers, from the items the
+// scanner found
+pub fn find_scanned_chunks(
+    path: &Path,
+    content: &str,
+    driver: &dyn ScanDriver,
+    options: &ChunkOptions,
+) -> Vec<ChunkData> {
+    let language = driver.get_name();
+    let max_tokens = options.max_tokens_for(language);
+    let items = driver.find_items(content);
+
+    // Small files become one `file` chunk, as for any other driver
+    let file_tokens = options.tokenizer.count(content);
+    if file_tokens < options.small_file_threshold && file_tokens <= max_tokens {
+        if content.trim().is_empty() {
+            return Vec::new();
+        }
+        let members = items.into_iter().map(|item| item.name).collect();
+        return whole_file_chunks(path, content, language, options, members);
+    }
+    if items.is_empty() && driver.chunks_whole_file_without_items() && !content.trim().is_empty() {
+        return whole_file_chunks(path, content, language, options, Vec::new());
+    }
+    // One level down only, below that items are split by lines like any other
+    let items = items.into_iter().flat_map(|item| {
+        let too_large = options.tokenizer.count(&content[item.start..item.end]) > max_tokens;
+        match too_large.then(|| driver.inner_items(content, &item)).flatten() {
+            Some(inner) if !inner.is_empty() => inner,
+            _ => vec![item],
+        }
+    });
+
+    let file_path: Arc<str> = Arc::from(path.to_string_lossy().as_ref());
+    let language: Arc<str> = Arc::from(language);
+    let lines: Vec<&str> = content.lines().collect();
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    let lookahead = match options.split_strategy {
+        SplitStrategy::Boundaries => options.split_lookahead,
+        SplitStrategy::LinesGreedy => 0,
+    };
+
+    let mut chunks = Vec::new();
+    for item in items {
+        let start_row = line_starts.partition_point(|start| *start <= item.start) - 1;
+        let code = content[item.start..item.end].trim_end();
+        let (comments, comment_rows) = match item.doc_start {
+            Some(doc) => {
+                let docstring = content[doc..item.start].trim_end().to_string();
+                let doc_row = line_starts.partition_point(|start| *start <= doc) - 1;
+                let rows = (doc_row..).take(docstring.lines().count().max(1)).collect();
+                (docstring, rows)
+            }
+            None => preceding_comments(&lines, start_row, driver, options.max_comment_gap)
+                .unwrap_or((String::new(), vec![start_row])),
+        };
+        let signature = item
+            .signature
+            .unwrap_or_else(|| code.lines().next().unwrap_or("").to_string());
+        let context: Arc<str> = match item.context.is_empty() {
+            true => Arc::from("root"),
+            false => Arc::from(context_display(&item.context)),
+        };
+        let context_parts: Arc<[ContextPart]> = Arc::from(item.context);
+        let chunk_type: Arc<str> = Arc::from(item.kind);
+
+        // The text always starts with the comment block, an empty line if none
+        let text = format!("{}\n{}", comments, code);
+        if is_over_max_tokens(path, &item.name, &text, options) {
+            continue;
+        }
+        let comment_lines = comments.lines().count().max(1);
+        let to_file_line = |text_line: usize| {
+            if text_line < comment_lines {
+                comment_rows[text_line] + 1
+            } else {
+                start_row + 1 + text_line - comment_lines
+            }
+        };
+        let parts = driver.split(&text, max_tokens, &options.tokenizer).unwrap_or_else(|| {
+            split_text_at_boundaries(
+                &text,
+                max_tokens,
+                options.overlap_tokens,
+                &options.tokenizer,
+                lookahead,
+                Vec::new,
+            )
+        });
+        for (i, (sub_text, token_count, own_offset, overlap_lines)) in parts.into_iter().enumerate() {
+            let line_offset = own_offset - overlap_lines;
+            let mut chunk = ChunkData {
+                file_path: file_path.clone(),
+                language: language.clone(),
+                chunk_type: chunk_type.clone(),
+                chunk_name: item.name.clone(),
+                context: context.clone(),
+                context_parts: context_parts.clone(),
+                signature: signature.clone(),
+                comment: comments.clone(),
+                start_line: to_file_line(own_offset),
+                end_line: to_file_line(line_offset + sub_text.lines().count().max(1) - 1),
+                overlap_start_line: (overlap_lines > 0).then(|| to_file_line(line_offset)),
+                markers: find_markers(&sub_text, &options.markers, |l| to_file_line(line_offset + l)),
+                code: sub_text,
+                token_count,
+                max_tokens_applied: max_tokens,
+                ..Default::default()
+            };
+            chunk.id = compute_chunk_id(&chunk, i, options);
+            chunks.push(chunk);
+        }
+    }
+    chunks
+}
+
+#[cfg(feature = "lang-zig")]
+const ZIG_SYNTAX: Syntax = Syntax {
+    // `\\` starts a line of a multiline string
+    line_comments: &["//", "\\\\"],
+    block_comments: &[],
+    strings: &[("\"", "\""), ("'", "'")],
+    code: &[],
+    dollar_quotes: false,
+};
+
+// A file is a struct of its own, so containers are scanned for their declarations
+// the same way. Function bodies aren't, what is declared in them is local
+#[cfg(feature = "lang-zig")]
+struct ZigDriver;
+#[cfg(feature = "lang-zig")]
+impl ZigDriver {
+    // Kind and name of the declaration a line starts. A struct, enum or union is named
+    // by the `const` it is bound to, a test by its description
+    fn declaration(line: &str) -> Option<(&'static str, &str)> {
+        let mut rest = line.strip_prefix("pub ").unwrap_or(line).trim_start();
+        let modifiers = ["export ", "inline ", "noinline ", "extern "];
+        while let Some(after) = modifiers.iter().find_map(|m| rest.strip_prefix(m)) {
+            rest = after.trim_start();
+            // `extern "c" fn`
+            if let Some(library) = rest.strip_prefix('"') {
+                rest = library.split_once('"')?.1.trim_start();
+            }
+        }
+        if let Some(after) = rest.strip_prefix("fn ") {
+            return Some(("function_declaration", leading_identifier(after.trim_start())?));
+        }
+        if let Some(after) = rest.strip_prefix("test").filter(|after| after.starts_with([' ', '"', '{'])) {
+            let after = after.trim_start();
+            let name = match after.strip_prefix('"') {
+                Some(description) => description.split('"').next()?,
+                None => leading_identifier(after).unwrap_or("test"),
+            };
+            return Some(("test_declaration", name));
+        }
+        let binding = rest.strip_prefix("const ").or_else(|| rest.strip_prefix("var "))?;
+        let name = leading_identifier(binding.trim_start())?;
+        let mut value = binding.split_once('=')?.1.trim_start();
+        for modifier in ["extern ", "packed "] {
+            value = value.strip_prefix(modifier).unwrap_or(value).trim_start();
+        }
+        let kind = [("struct", "struct_declaration"), ("enum", "enum_declaration"), ("union", "union_declaration")]
+            .into_iter()
+            .find(|(keyword, _)| {
+                value
+                    .strip_prefix(keyword)
+                    .is_some_and(|after| !after.starts_with(|c: char| c.is_alphanumeric() || c == '_'))
+            })?
+            .1;
+        Some((kind, name))
+    }
+
+    // Declarations between `from` and `to` outside any block, and those of the
+    // containers among them
+    fn scan(
+        content: &str,
+        mask: &[bool],
+        (from, to): (usize, usize),
+        context: &[ContextPart],
+        items: &mut Vec<ScannedItem>,
+    ) {
+        let bytes = content.as_bytes();
+        let mut depth = 0;
+        let mut line = from;
+        while line < to {
+            let line_end = find_from(bytes, line, b"\n").map_or(to, |n| n.min(to));
+            let text = &content[line..line_end];
+            let start = line + (text.len() - text.trim_start().len());
+            let declaration = (depth == 0 && start < line_end && mask[start])
+                .then(|| Self::declaration(&content[start..line_end]))
+                .flatten();
+            if let Some((kind, name)) = declaration
+                && let Some(body) = find_code_byte(content, mask, start, b"{;")
+            {
+                let end = match bytes[body] {
+                    b';' => Some(body + 1),
+                    _ => close_of(content, mask, body),
+                };
+                if let Some(end) = end.filter(|end| *end <= to) {
+                    let signature = (kind == "function_declaration" && bytes[body] == b'{')
+                        .then(|| content[start..body].trim_end().to_string());
+                    items.push(ScannedItem {
+                        kind,
+                        name: name.to_string(),
+                        context: context.to_vec(),
+                        start,
+                        end,
+                        signature,
+                        doc_start: None,
+                    });
+                    if let Some(scope) = kind.strip_suffix("_declaration").filter(|_| bytes[body] == b'{')
+                        && !matches!(scope, "function" | "test")
+                    {
+                        let mut inner = context.to_vec();
+                        inner.push(ContextPart { kind: scope.to_string(), name: name.to_string() });
+                        Self::scan(content, mask, (body + 1, end - 1), &inner, items);
+                    }
+                    line = find_from(bytes, end, b"\n").map_or(to, |n| n + 1);
+                    continue;
+                }
+            }
+            for i in line..line_end {
+                match bytes[i] {
+                    b'{' if mask[i] => depth += 1,
+                    b'}' if mask[i] => depth -= 1,
+                    _ => {}
+                }
+            }
+            line = line_end + 1;
+        }
+    }
+}
+#[cfg(feature = "lang-zig")]
+impl ScanDriver for ZigDriver {
+    fn get_name(&self) -> &'static str { "Zig" }
+
+    fn find_items(&self, content: &str) -> Vec<ScannedItem> {
+        let mask = code_mask(content, &ZIG_SYNTAX);
+        let mut items = Vec::new();
+        Self::scan(content, &mask, (0, content.len()), &[], &mut items);
+        items
+    }
+
+    // `//!` documents the file, not the item below it
+    fn is_comment_line(&self, line: &str) -> bool { line.starts_with("//") && !line.starts_with("//!") }
+}
+
+#[cfg(feature = "lang-julia")]
+const JULIA_SYNTAX: Syntax = Syntax {
+    line_comments: &["#"],
+    // Comments and triple-quoted strings before the forms they start with
+    block_comments: &[("#=", "=#")],
+    strings: &[("\"\"\"", "\"\"\""), ("\"", "\""), ("`", "`")],
+    code: &[],
+    dollar_quotes: false,
+};
+
+// Keywords a Julia block opens with, each closed by an `end`
+#[cfg(feature = "lang-julia")]
+const JULIA_BLOCKS: &[&str] = &[
+    "function", "macro", "module", "baremodule", "struct", "if", "for", "while", "let", "begin", "quote", "try", "do",
+];
+
+// Blocks are matched by counting keywords against `end`s, outside brackets where `end`
+// is an index. Items are found at file level and in modules, structs and top-level
+// `if` and `begin` blocks, never in functions
+#[cfg(feature = "lang-julia")]
+struct JuliaDriver;
+#[cfg(feature = "lang-julia")]
+impl JuliaDriver {
+    fn is_word_byte(byte: u8) -> bool { byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'!' || byte >= 0x80 }
+
+    // Word starting at `i` when it is one, not a field like `x.end` or a symbol `:end`
+    fn word_at<'a>(content: &'a str, mask: &[bool], i: usize) -> Option<&'a str> {
+        let bytes = content.as_bytes();
+        // Identifiers may start with any letter, `α` or `δt`
+        let starts = mask[i]
+            && content.is_char_boundary(i)
+            && content[i..].starts_with(|c: char| c.is_alphabetic() || c == '_')
+            && (i == 0 || !(Self::is_word_byte(bytes[i - 1]) || matches!(bytes[i - 1], b'.' | b':' | b'@')));
+        if !starts {
+            return None;
+        }
+        let end = (i..bytes.len()).find(|j| !Self::is_word_byte(bytes[*j])).unwrap_or(bytes.len());
+        Some(&content[i..end])
+    }
+
+    // Offset right after the statement starting at `from`: the end of a line where no
+    // bracket or block is open and that doesn't end in an operator
+    fn statement_end(content: &str, mask: &[bool], from: usize) -> usize {
+        let bytes = content.as_bytes();
+        let (mut brackets, mut blocks) = (0, 0);
+        let mut last_code = b' ';
+        let mut i = from;
+        while i < bytes.len() {
+            if bytes[i] == b'\n' {
+                let continues = brackets > 0 || blocks > 0 || b"=+-*/,|&(\\<>".contains(&last_code);
+                if !continues {
+                    return i;
+                }
+            } else if mask[i] {
+                match bytes[i] {
+                    b'(' | b'[' | b'{' => brackets += 1,
+                    b')' | b']' | b'}' => brackets -= 1,
+                    _ => {}
+                }
+                if brackets == 0
+                    && let Some(word) = Self::word_at(content, mask, i)
+                {
+                    match word {
+                        "end" => blocks -= 1,
+                        word if JULIA_BLOCKS.contains(&word) => blocks += 1,
+                        _ => {}
+                    }
+                    last_code = bytes[i + word.len() - 1];
+                    i += word.len();
+                    continue;
+                }
+                if !bytes[i].is_ascii_whitespace() {
+                    last_code = bytes[i];
+                }
+            }
+            i += 1;
+        }
+        bytes.len()
+    }
+
+    // `f(x) = ...`, `Base.show(io, x) = ...` and `f(x::T)::T where T = ...` starting the
+    // line at `start`. The name and the end of the definition
+    fn short_form<'a>(content: &'a str, mask: &[bool], start: usize) -> Option<(&'a str, usize)> {
+        let bytes = content.as_bytes();
+        let name_end = (start..bytes.len())
+            .find(|i| !(Self::is_word_byte(bytes[*i]) || bytes[*i] == b'.'))
+            .unwrap_or(bytes.len());
+        let name = Self::word_at(content, mask, start).map(|_| &content[start..name_end])?;
+        if JULIA_BLOCKS.contains(&name) || matches!(name, "end" | "return" | "elseif" | "else" | "global" | "local" | "const") {
+            return None;
+        }
+        let mut open = name_end;
+        if bytes.get(open) == Some(&b'{') {
+            open = close_of(content, mask, open)?;
+        }
+        if bytes.get(open) != Some(&b'(') {
+            return None;
+        }
+        let after = close_of(content, mask, open)?;
+        let line_end = find_from(bytes, after, b"\n").unwrap_or(bytes.len());
+        let equals = (after..line_end).find(|i| {
+            mask[*i]
+                && bytes[*i] == b'='
+                && !matches!(bytes.get(i + 1), Some(b'=' | b'>'))
+                && !matches!(bytes[i - 1], b'=' | b'<' | b'>' | b'!' | b':')
+        })?;
+        let between = content[after..equals].trim();
+        let plain = between.is_empty() || between.starts_with("::") || between.starts_with("where");
+        plain.then(|| (name, Self::statement_end(content, mask, equals + 1)))
+    }
+}
+#[cfg(feature = "lang-julia")]
+impl ScanDriver for JuliaDriver {
+    fn get_name(&self) -> &'static str { "Julia" }
+
+    fn find_items(&self, content: &str) -> Vec<ScannedItem> {
+        struct Block {
+            // Whether items may sit directly in it
+            holds_items: bool,
+            item: Option<usize>,
+            scope: bool,
+        }
+        let mask = code_mask(content, &JULIA_SYNTAX);
+        let bytes = content.as_bytes();
+        let mut items: Vec<ScannedItem> = Vec::new();
+        let mut blocks: Vec<Block> = Vec::new();
+        let mut context: Vec<ContextPart> = Vec::new();
+        // Inside a short-form definition, nothing in it is an item
+        let mut skip_until = 0;
+        let mut brackets = 0;
+        let mut i = 0;
+        while i < bytes.len() {
+            // Past the first byte of a multi-byte character
+            if !content.is_char_boundary(i) {
+                i += 1;
+                continue;
+            }
+            let in_scope = i >= skip_until && brackets == 0 && blocks.iter().all(|block| block.holds_items);
+            let line_start = content[..i].rfind('\n').map_or(0, |n| n + 1);
+            let first_on_line = content[line_start..i].trim().is_empty();
+            if in_scope
+                && first_on_line
+                && !bytes[i].is_ascii_whitespace()
+                && let Some((name, end)) = Self::short_form(content, &mask, i)
+            {
+                items.push(ScannedItem {
+                    kind: "short_function_definition",
+                    name: name.to_string(),
+                    context: context.clone(),
+                    start: i,
+                    end,
+                    signature: None,
+                    doc_start: docstring_above(content, &mask, line_start, "\"\"\""),
+                });
+                skip_until = end;
+            }
+            if !mask[i] {
+                i += 1;
+                continue;
+            }
+            match bytes[i] {
+                b'(' | b'[' | b'{' => brackets += 1,
+                b')' | b']' | b'}' => brackets -= 1,
+                _ => {}
+            }
+            let Some(word) = Self::word_at(content, &mask, i).filter(|_| brackets == 0) else {
+                i += 1;
+                continue;
+            };
+            let mut next = i + word.len();
+            let rest = &content[next..];
+            // `abstract type Shape end`, `primitive type Byte 8 end`
+            let type_keyword = matches!(word, "abstract" | "primitive")
+                && rest.starts_with(char::is_whitespace)
+                && rest.trim_start().strip_prefix("type").is_some_and(|after| after.starts_with(char::is_whitespace));
+            if word == "end" {
+                if let Some(block) = blocks.pop() {
+                    if let Some(item) = block.item {
+                        items[item].end = next;
+                    }
+                    if block.scope {
+                        context.pop();
+                    }
+                }
+            } else if JULIA_BLOCKS.contains(&word) || type_keyword {
+                if type_keyword {
+                    next = next + rest.len() - rest.trim_start().len() + "type".len();
+                }
+                // Macro calls and `mutable` may come first on the line, `@kwdef mutable struct`
+                let prefix = content[line_start..i].split_whitespace().all(|w| w.contains('@') || w == "mutable");
+                let (kind, scope) = match word {
+                    "function" => ("function_definition", None),
+                    "macro" => ("macro_definition", None),
+                    "module" | "baremodule" => ("module_definition", Some("module")),
+                    "struct" => ("struct_definition", Some("struct")),
+                    "abstract" => ("abstract_definition", None),
+                    "primitive" => ("primitive_definition", None),
+                    _ => ("", None),
+                };
+                let is_item = in_scope && prefix && !kind.is_empty();
+                let mut item = None;
+                if is_item {
+                    let after = content[next..].trim_start();
+                    let name = match word {
+                        // `function (p::Polynomial)(x)` makes instances callable
+                        "function" if after.starts_with('(') => {
+                            let open = content.len() - after.len();
+                            close_of(content, &mask, open).map_or("", |close| &content[open..close])
+                        }
+                        "function" => &after[..after.find(['(', '{', ' ', '\t', '\n', ';']).unwrap_or(after.len())],
+                        _ => leading_identifier(after).unwrap_or(""),
+                    };
+                    let name = match word {
+                        "macro" => format!("@{}", name),
+                        _ => name.to_string(),
+                    };
+                    let start = line_start + (content[line_start..].len() - content[line_start..].trim_start().len());
+                    item = Some(items.len());
+                    items.push(ScannedItem {
+                        kind,
+                        name: name.clone(),
+                        context: context.clone(),
+                        start,
+                        end: content.len(),
+                        signature: None,
+                        doc_start: docstring_above(content, &mask, line_start, "\"\"\""),
+                    });
+                    if let Some(scope) = scope {
+                        context.push(ContextPart { kind: scope.to_string(), name });
+                    }
+                }
+                let holds_items = match word {
+                    "module" | "baremodule" | "struct" => is_item,
+                    "if" | "begin" => in_scope,
+                    _ => false,
+                };
+                blocks.push(Block { holds_items, item, scope: is_item && scope.is_some() });
+            }
+            i = next;
+        }
+        items
+    }
+
+    fn is_comment_line(&self, line: &str) -> bool { line.starts_with('#') }
+}
+
+#[cfg(feature = "lang-perl")]
+const PERL_SYNTAX: Syntax = Syntax {
+    line_comments: &["#"],
+    // POD runs from a command at the start of a line to `=cut`
+    block_comments: &[
+        ("\n=pod", "\n=cut"),
+        ("\n=head", "\n=cut"),
+        ("\n=item", "\n=cut"),
+        ("\n=over", "\n=cut"),
+        ("\n=begin", "\n=cut"),
+        ("\n=for", "\n=cut"),
+        ("\n=encoding", "\n=cut"),
+    ],
+    strings: &[("\"", "\""), ("'", "'")],
+    // The last index of an array and punctuation variables
+    code: &["$#", "$\"", "$'"],
+    dollar_quotes: false,
+};
+
+// Packages aren't scopes of their own: `package Foo;` puts what follows it in Foo, up to
+// the next one or the end of the enclosing block. `package Foo { ... }` is scoped
+#[cfg(feature = "lang-perl")]
+struct PerlDriver;
+#[cfg(feature = "lang-perl")]
+impl PerlDriver {
+    fn name_at(text: &str) -> &str {
+        let end = text.find(|c: char| !(c.is_alphanumeric() || c == '_' || c == ':' || c == '\'')).unwrap_or(text.len());
+        &text[..end]
+    }
+}
+#[cfg(feature = "lang-perl")]
+impl ScanDriver for PerlDriver {
+    fn get_name(&self) -> &'static str { "Perl" }
+
+    fn find_items(&self, content: &str) -> Vec<ScannedItem> {
+        struct Block {
+            // Package of a `package Foo { ... }` block
+            package: Option<String>,
+            // Set by the last `package Foo;` statement in the block
+            current: Option<String>,
+            holds_items: bool,
+        }
+        let mask = code_mask(content, &PERL_SYNTAX);
+        let bytes = content.as_bytes();
+        let mut items = Vec::new();
+        let mut blocks = vec![Block { package: None, current: None, holds_items: true }];
+        // Package named by `package Foo` until the `{` or `;` after it
+        let mut pending: Option<String> = None;
+        let mut i = 0;
+        while i < bytes.len() {
+            if !mask[i] {
+                i += 1;
+                continue;
+            }
+            match bytes[i] {
+                b'{' => {
+                    let holds_items = pending.is_some() && blocks.iter().all(|block| block.holds_items);
+                    blocks.push(Block { package: pending.take(), current: None, holds_items });
+                    i += 1;
+                    continue;
+                }
+                b'}' => {
+                    if blocks.len() > 1 {
+                        blocks.pop();
+                    }
+                    i += 1;
+                    continue;
+                }
+                b';' => {
+                    if let Some(package) = pending.take()
+                        && let Some(block) = blocks.last_mut()
+                    {
+                        block.current = Some(package);
+                    }
+                    i += 1;
+                    continue;
+                }
+                _ => {}
+            }
+            // Keywords are ASCII, so `i` is on a char boundary past this check
+            let at_word = bytes[i].is_ascii_alphabetic()
+                && (i == 0 || !(bytes[i - 1].is_ascii_alphanumeric() || matches!(bytes[i - 1], b'_' | b'$' | b'@' | b'%' | b'&' | b'-' | b'>' | b':')));
+            if !at_word {
+                i += 1;
+                continue;
+            }
+            let rest = &content[i..];
+            let keyword = ["package", "sub"]
+                .into_iter()
+                .find(|keyword| rest.strip_prefix(keyword).is_some_and(|after| after.starts_with(char::is_whitespace)));
+            let Some(keyword) = keyword else {
+                i += 1;
+                continue;
+            };
+            let after = rest[keyword.len()..].trim_start();
+            let name = Self::name_at(after);
+            let name_end = content.len() - after.len() + name.len();
+            if keyword == "package" {
+                pending = (!name.is_empty()).then(|| name.to_string());
+                i = name_end;
+                continue;
+            }
+            // `sub name;` only declares it, and anonymous subs aren't items
+            let body = find_code_byte(content, &mask, name_end, b"{;");
+            let end = body.filter(|body| bytes[*body] == b'{').and_then(|body| close_of(content, &mask, body));
+            match end {
+                Some(end) if !name.is_empty() && blocks.iter().all(|block| block.holds_items) => {
+                    let line_start = content[..i].rfind('\n').map_or(0, |n| n + 1);
+                    let package = blocks.iter().rev().find_map(|block| block.current.as_ref().or(block.package.as_ref()));
+                    let context = package
+                        .filter(|package| package.as_str() != "main")
+                        .map(|package| ContextPart { kind: "package".to_string(), name: package.clone() })
+                        .into_iter()
+                        .collect();
+                    items.push(ScannedItem {
+                        kind: "subroutine_declaration_statement",
+                        name: name.to_string(),
+                        context,
+                        start: i,
+                        end,
+                        signature: body.map(|body| content[i..body].trim_end().to_string()),
+                        doc_start: docstring_above(content, &mask, line_start, "="),
+                    });
+                    i = end;
+                }
+                _ => i = name_end,
+            }
+        }
+        items
+    }
+
+    fn is_comment_line(&self, line: &str) -> bool { line.starts_with('#') && !line.starts_with("#!") }
+}
+
+#[cfg(feature = "lang-proto")]
+const PROTO_SYNTAX: Syntax = Syntax {
+    line_comments: &["//"],
+    block_comments: &[("/*", "*/")],
+    strings: &[("\"", "\""), ("'", "'")],
+    code: &[],
+    dollar_quotes: false,
+};
+
+// Messages nest messages and enums, services hold rpcs
+#[cfg(feature = "lang-proto")]
+struct ProtoDriver;
+#[cfg(feature = "lang-proto")]
+impl ProtoDriver {
+    fn scan(
+        content: &str,
+        mask: &[bool],
+        (from, to): (usize, usize),
+        context: &[ContextPart],
+        items: &mut Vec<ScannedItem>,
+    ) {
+        let bytes = content.as_bytes();
+        let mut i = from;
+        while i < to {
+            let keyword = ["message", "enum", "service", "rpc"].into_iter().find(|keyword| {
+                mask[i]
+                    && content.is_char_boundary(i)
+                    && (i == 0 || !(bytes[i - 1].is_ascii_alphanumeric() || matches!(bytes[i - 1], b'_' | b'.')))
+                    && content[i..].strip_prefix(keyword).is_some_and(|after| after.starts_with(char::is_whitespace))
+            });
+            let name = keyword.and_then(|keyword| leading_identifier(content[i + keyword.len()..].trim_start()));
+            let (Some(keyword), Some(name)) = (keyword, name) else {
+                if mask[i] && bytes[i] == b'{' {
+                    // Braces of options and `oneof`s hold no items
+                    i = close_of(content, mask, i).unwrap_or(to);
+                } else {
+                    i += 1;
+                }
+                continue;
+            };
+            let Some(body) = find_code_byte(content, mask, i, b"{;") else { break };
+            let end = match bytes[body] {
+                b';' => body + 1,
+                _ => close_of(content, mask, body).unwrap_or(to).min(to),
+            };
+            // `rpc GetUser(GetUserRequest) returns (User);`, on one line
+            let signature = (keyword == "rpc").then(|| {
+                let declaration = match bytes[body] {
+                    b';' => &content[i..=body],
+                    _ => &content[i..body],
+                };
+                declaration.split_whitespace().collect::<Vec<_>>().join(" ")
+            });
+            items.push(ScannedItem {
+                kind: keyword,
+                name: name.to_string(),
+                context: context.to_vec(),
+                start: i,
+                end,
+                signature,
+                doc_start: None,
+            });
+            if matches!(keyword, "message" | "service") && bytes[body] == b'{' {
+                let mut inner = context.to_vec();
+                inner.push(ContextPart { kind: keyword.to_string(), name: name.to_string() });
+                Self::scan(content, mask, (body + 1, end - 1), &inner, items);
+            }
+            i = end;
+        }
+    }
+}
+#[cfg(feature = "lang-proto")]
+impl ScanDriver for ProtoDriver {
+    fn get_name(&self) -> &'static str { "Protobuf" }
+
+    fn find_items(&self, content: &str) -> Vec<ScannedItem> {
+        let mask = code_mask(content, &PROTO_SYNTAX);
+        let mut items = Vec::new();
+        Self::scan(content, &mask, (0, content.len()), &[], &mut items);
+        items
+    }
+
+    fn is_comment_line(&self, line: &str) -> bool { line.starts_with("//") || line.starts_with("/*") || line.starts_with('*') }
+}
+
+#[cfg(feature = "lang-graphql")]
+const GRAPHQL_SYNTAX: Syntax = Syntax {
+    line_comments: &["#"],
+    block_comments: &[],
+    strings: &[("\"\"\"", "\"\"\""), ("\"", "\"")],
+    code: &[],
+    dollar_quotes: false,
+};
+
+// Type definitions of a schema, `extend type Query { ... }` included. The description
+// string above a definition is its comment
+#[cfg(feature = "lang-graphql")]
+struct GraphQlDriver;
+#[cfg(feature = "lang-graphql")]
+impl GraphQlDriver {
+    // Kind and name of the definition a line starts
+    fn definition(line: &str) -> Option<(&'static str, &str)> {
+        let line = line.strip_prefix("extend ").unwrap_or(line).trim_start();
+        let (keyword, rest) = line.split_once(char::is_whitespace)?;
+        let kind = match keyword {
+            "type" => "object_type_definition",
+            "interface" => "interface_type_definition",
+            "enum" => "enum_type_definition",
+            "input" => "input_object_type_definition",
+            "union" => "union_type_definition",
+            _ => return None,
+        };
+        Some((kind, leading_identifier(rest.trim_start())?))
+    }
+}
+#[cfg(feature = "lang-graphql")]
+impl ScanDriver for GraphQlDriver {
+    fn get_name(&self) -> &'static str { "GraphQL" }
+
+    fn find_items(&self, content: &str) -> Vec<ScannedItem> {
+        let mask = code_mask(content, &GRAPHQL_SYNTAX);
+        let bytes = content.as_bytes();
+        let line_end = |from: usize| find_from(bytes, from, b"\n").unwrap_or(bytes.len());
+        let mut items = Vec::new();
+        let mut line = 0;
+        while line < bytes.len() {
+            let definition = mask[line..line_end(line)]
+                .first()
+                .is_some_and(|code| *code)
+                .then(|| Self::definition(&content[line..line_end(line)]))
+                .flatten();
+            let Some((kind, name)) = definition else {
+                line = line_end(line) + 1;
+                continue;
+            };
+            // The body may open on a later line, as long as no other definition starts first
+            let brace = find_code_byte(content, &mask, line, b"{").filter(|brace| {
+                let between = &content[line_end(line).min(*brace)..*brace];
+                between.lines().all(|l| Self::definition(l).is_none())
+            });
+            let end = match brace {
+                Some(brace) if kind != "union_type_definition" => close_of(content, &mask, brace).unwrap_or(bytes.len()),
+                // A union lists its members up to the last line starting or ending with `|`
+                _ => {
+                    let mut end = line_end(line);
+                    loop {
+                        let current = content[..end].rsplit('\n').next().unwrap_or("").trim_end();
+                        let next = content.get(end + 1..line_end(end + 1)).unwrap_or("").trim_start();
+                        if end < bytes.len() && (current.ends_with(['=', '|']) || next.starts_with('|')) {
+                            end = line_end(end + 1);
+                        } else {
+                            break;
+                        }
+                    }
+                    end
+                }
+            };
+            items.push(ScannedItem {
+                kind,
+                name: name.to_string(),
+                context: Vec::new(),
+                start: line,
+                end,
+                signature: None,
+                doc_start: docstring_above(content, &mask, line, "\""),
+            });
+            line = line_end(end) + 1;
+        }
+        items
+    }
+
+    fn is_comment_line(&self, line: &str) -> bool { line.starts_with('#') }
+}
+
+#[cfg(feature = "lang-hcl")]
+const HCL_SYNTAX: Syntax = Syntax {
+    line_comments: &["#", "//"],
+    block_comments: &[("/*", "*/")],
+    strings: &[("\"", "\"")],
+    code: &[],
+    dollar_quotes: false,
+};
+
+// Top-level blocks of HCL and Terraform, typed after the block type and named after
+// their labels, `resource "aws_s3_bucket" "logs"` is resource `aws_s3_bucket.logs`.
+// Blocks nested in them are part of their code
+#[cfg(feature = "lang-hcl")]
+struct HclDriver;
+#[cfg(feature = "lang-hcl")]
+impl HclDriver {
+    // Block type and labels of a block header, up to its `{`
+    fn header(line: &str) -> Option<(&str, Vec<&str>)> {
+        let block_type = leading_identifier(line)?;
+        let mut rest = line[block_type.len()..].trim_start();
+        let mut labels = Vec::new();
+        while !rest.starts_with('{') {
+            let label = match rest.strip_prefix('"') {
+                Some(quoted) => &quoted[..quoted.find('"')?],
+                None => {
+                    let end = rest.find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))?;
+                    (end > 0).then(|| &rest[..end])?
+                }
+            };
+            labels.push(label);
+            let skip = if rest.starts_with('"') { label.len() + 2 } else { label.len() };
+            rest = rest[skip..].trim_start();
+        }
+        Some((block_type, labels))
+    }
+}
+#[cfg(feature = "lang-hcl")]
+impl ScanDriver for HclDriver {
+    fn get_name(&self) -> &'static str { "HCL" }
+
+    fn find_items(&self, content: &str) -> Vec<ScannedItem> {
+        let mask = code_mask(content, &HCL_SYNTAX);
+        let bytes = content.as_bytes();
+        let mut items = Vec::new();
+        let mut line = 0;
+        while line < bytes.len() {
+            let line_end = find_from(bytes, line, b"\n").unwrap_or(bytes.len());
+            let indent = content[line..line_end].len() - content[line..line_end].trim_start().len();
+            let start = line + indent;
+            let brace = find_code_byte(content, &mask, start, b"{\n")
+                .filter(|brace| *brace < line_end && bytes[*brace] == b'{');
+            let Some(brace) = brace.filter(|_| mask.get(start).is_some_and(|code| *code)) else {
+                line = line_end + 1;
+                continue;
+            };
+            // Attributes holding a map, like `tags = { ... }` in a .tfvars file, are skipped whole
+            let end = close_of(content, &mask, brace).unwrap_or(bytes.len());
+            if let Some((block_type, labels)) = Self::header(&content[start..=brace]) {
+                let kind = match block_type {
+                    "resource" => "resource",
+                    "data" => "data",
+                    "variable" => "variable",
+                    "output" => "output",
+                    "module" => "module",
+                    "provider" => "provider",
+                    "locals" => "locals",
+                    "terraform" => "terraform",
+                    "moved" => "moved",
+                    "import" => "import",
+                    "check" => "check",
+                    _ => "block",
+                };
+                let name = match labels.is_empty() {
+                    true => block_type.to_string(),
+                    false => labels.join("."),
+                };
+                items.push(ScannedItem {
+                    kind,
+                    name,
+                    context: Vec::new(),
+                    start,
+                    end,
+                    signature: Some(content[start..brace].trim_end().to_string()),
+                    doc_start: None,
+                });
+            }
+            line = find_from(bytes, end, b"\n").map_or(bytes.len(), |n| n + 1);
+        }
+        items
+    }
+
+    fn is_comment_line(&self, line: &str) -> bool {
+        line.starts_with('#') || line.starts_with("//") || line.starts_with("/*") || line.starts_with('*')
+    }
+}
+
+// One chunk per build stage, from its `FROM` up to the next one. Stages are named after
+// `AS name`, unnamed ones `stage-N` by their index the way `--target` counts them
+#[cfg(feature = "lang-dockerfile")]
+struct DockerfileDriver;
+#[cfg(feature = "lang-dockerfile")]
+impl ScanDriver for DockerfileDriver {
+    fn get_name(&self) -> &'static str { "Dockerfile" }
+
+    fn find_items(&self, content: &str) -> Vec<ScannedItem> {
+        let mut lines = Vec::new();
+        let mut offset = 0;
+        for line in content.split_inclusive('\n') {
+            lines.push((offset, line.trim()));
+            offset += line.len();
+        }
+        // Instructions start lines that don't continue the previous one
+        let froms: Vec<usize> = (0..lines.len())
+            .filter(|&i| i == 0 || !lines[i - 1].1.ends_with('\\'))
+            .filter(|&i| {
+                let mut words = lines[i].1.split_whitespace();
+                words.next().is_some_and(|word| word.eq_ignore_ascii_case("FROM"))
+            })
+            .collect();
+        let mut items = Vec::new();
+        for (index, &from) in froms.iter().enumerate() {
+            let next = froms.get(index + 1).copied().unwrap_or(lines.len());
+            // Comments above the next stage are its comment, not this stage's code
+            let mut last = next - 1;
+            while last > from && (lines[last].1.is_empty() || lines[last].1.starts_with('#')) {
+                last -= 1;
+            }
+            let (start, signature) = lines[from];
+            let words: Vec<&str> = signature
+                .split_whitespace()
+                .skip(1)
+                .filter(|word| !word.starts_with("--"))
+                .collect();
+            let name = match words.as_slice() {
+                [_, keyword, name, ..] if keyword.eq_ignore_ascii_case("AS") => name.to_string(),
+                _ => format!("stage-{}", index),
+            };
+            items.push(ScannedItem {
+                kind: "stage",
+                name,
+                context: Vec::new(),
+                start,
+                end: lines[last].0 + lines[last].1.len(),
+                signature: Some(signature.to_string()),
+                doc_start: None,
+            });
+        }
+        items
+    }
+
+    fn is_comment_line(&self, line: &str) -> bool { line.starts_with('#') }
+}
+
+#[cfg(feature = "lang-sql")]
+const SQL_SYNTAX: Syntax = Syntax {
+    line_comments: &["--"],
+    block_comments: &[("/*", "*/")],
+    // Quoted identifiers too, a `;` in them doesn't end a statement
+    strings: &[("'", "'"), ("\"", "\""), ("`", "`")],
+    code: &[],
+    dollar_quotes: true,
+};
+
+// CREATE TABLE, VIEW, FUNCTION, PROCEDURE, INDEX and TRIGGER statements, named after
+// the object they create. A file without any, like a migration of INSERTs, is chunked
+// per statement instead, with runs of statements on the same table as one item
+#[cfg(feature = "lang-sql")]
+struct SqlDriver;
+#[cfg(feature = "lang-sql")]
+impl SqlDriver {
+    // Code words of `content[from..to]` with their offsets
+    fn words<'a>(content: &'a str, mask: &[bool], from: usize, to: usize) -> Vec<(usize, &'a str)> {
+        let bytes = content.as_bytes();
+        let is_word = |i: usize| mask[i] && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_');
+        let mut words = Vec::new();
+        let mut i = from;
+        while i < to {
+            if is_word(i) && (i == 0 || !is_word(i - 1)) {
+                let end = (i..to).find(|&j| !is_word(j)).unwrap_or(to);
+                words.push((i, &content[i..end]));
+                i = end;
+            } else {
+                i += 1;
+            }
+        }
+        words
+    }
+
+    // Offset of the `;` ending the statement at `start`. BEGIN ... END bodies of
+    // routines without a dollar-quoted body hold `;` of their own
+    fn statement_end(content: &str, mask: &[bool], start: usize, routine: bool) -> Option<usize> {
+        let bytes = content.as_bytes();
+        let mut depth = 0i32;
+        let mut word_end = start;
+        for i in start..bytes.len() {
+            if !mask[i] {
+                continue;
+            }
+            if bytes[i] == b';' && depth <= 0 {
+                return Some(i);
+            }
+            let inside_word = i > 0 && mask[i - 1] && (bytes[i - 1].is_ascii_alphanumeric() || bytes[i - 1] == b'_');
+            if !routine || i < word_end || !bytes[i].is_ascii_alphabetic() || inside_word {
+                continue;
+            }
+            let word = leading_identifier(&content[i..]).unwrap_or("");
+            word_end = i + word.len();
+            let next = leading_identifier(content[word_end..].trim_start()).unwrap_or("");
+            if word.eq_ignore_ascii_case("BEGIN") || word.eq_ignore_ascii_case("CASE") {
+                depth += 1;
+            } else if word.eq_ignore_ascii_case("END")
+                && !["IF", "LOOP", "WHILE", "REPEAT", "FOR"].iter().any(|w| next.eq_ignore_ascii_case(w))
+            {
+                depth -= 1;
+            }
+        }
+        None
+    }
+
+    // Kind and name of a CREATE statement from its code words
+    fn definition(content: &str, words: &[(usize, &str)]) -> Option<(&'static str, String)> {
+        let upper: Vec<String> = words.iter().map(|(_, word)| word.to_ascii_uppercase()).collect();
+        if upper.first().map(String::as_str) != Some("CREATE") {
+            return None;
+        }
+        let modifiers = ["OR", "REPLACE", "TEMP", "TEMPORARY", "UNLOGGED", "GLOBAL", "LOCAL", "MATERIALIZED", "UNIQUE", "RECURSIVE", "CONSTRAINT", "DEFINER", "ALGORITHM", "UNDEFINED", "MERGE", "CLUSTERED", "NONCLUSTERED"];
+        let at = upper.iter().skip(1).position(|word| !modifiers.contains(&word.as_str()))? + 1;
+        let kind = match upper[at].as_str() {
+            "TABLE" => "create_table",
+            "VIEW" => "create_view",
+            "FUNCTION" => "create_function",
+            "PROCEDURE" | "PROC" => "create_procedure",
+            "INDEX" => "create_index",
+            "TRIGGER" => "create_trigger",
+            _ => return None,
+        };
+        let mut name = at + 1;
+        while upper.get(name).is_some_and(|word| ["IF", "NOT", "EXISTS", "CONCURRENTLY"].contains(&word.as_str())) {
+            name += 1;
+        }
+        // An index may go unnamed, `CREATE INDEX ON users (email)`
+        if kind == "create_index" && upper.get(name).map(String::as_str) == Some("ON") {
+            return Some((kind, "anonymous".to_string()));
+        }
+        Some((kind, Self::name_after(content, words[name - 1])?))
+    }
+
+    // `schema.name` right after a word, quotes and brackets dropped. Quoted names are
+    // strings to the mask, so they are read off the text
+    fn name_after(content: &str, (offset, word): (usize, &str)) -> Option<String> {
+        let mut rest = content[offset + word.len()..].trim_start();
+        let mut name = String::new();
+        loop {
+            let part = match rest.chars().next()? {
+                quote @ ('"' | '`' | '[') => {
+                    let close = if quote == '[' { ']' } else { quote };
+                    let end = rest[1..].find(close)? + 1;
+                    (&rest[1..end], end + 1)
+                }
+                _ => {
+                    let part = leading_identifier(rest)?;
+                    (part, part.len())
+                }
+            };
+            name.push_str(part.0);
+            rest = &rest[part.1..];
+            match rest.strip_prefix('.') {
+                Some(after) => {
+                    name.push('.');
+                    rest = after;
+                }
+                None => return Some(name),
+            }
+        }
+    }
+
+    // Kind and name of a data statement, by its verb and the table it works on
+    fn statement(content: &str, words: &[(usize, &str)]) -> (&'static str, String) {
+        let upper: Vec<String> = words.iter().take(4).map(|(_, word)| word.to_ascii_uppercase()).collect();
+        let table = |verb: usize| Self::name_after(content, words[verb]);
+        let (kind, table) = match upper.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+            ["INSERT", "INTO", ..] => ("insert_statement", table(1)),
+            ["UPDATE", ..] => ("update_statement", table(0)),
+            ["DELETE", "FROM", ..] => ("delete_statement", table(1)),
+            _ => ("statement", None),
+        };
+        let name = table.unwrap_or_else(|| upper.first().map_or("statement".to_string(), |verb| verb.to_lowercase()));
+        (kind, name)
+    }
+}
+#[cfg(feature = "lang-sql")]
+impl ScanDriver for SqlDriver {
+    fn get_name(&self) -> &'static str { "SQL" }
+
+    fn find_items(&self, content: &str) -> Vec<ScannedItem> {
+        let mask = code_mask(content, &SQL_SYNTAX);
+        let bytes = content.as_bytes();
+        let mut definitions = Vec::new();
+        let mut statements: Vec<ScannedItem> = Vec::new();
+        let mut from = 0;
+        while let Some(start) = (from..bytes.len()).find(|&i| mask[i] && !bytes[i].is_ascii_whitespace()) {
+            let head_end = find_code_byte(content, &mask, start, b"(;").unwrap_or(bytes.len());
+            let head = Self::words(content, &mask, start, head_end);
+            let definition = Self::definition(content, &head);
+            let routine = matches!(definition, Some(("create_function" | "create_procedure" | "create_trigger", _)));
+            let end = Self::statement_end(content, &mask, start, routine).map_or(bytes.len(), |semi| semi + 1);
+            from = end;
+            if bytes[start] == b';' {
+                continue;
+            }
+            if let Some((kind, name)) = definition {
+                definitions.push(ScannedItem {
+                    kind,
+                    name,
+                    context: Vec::new(),
+                    start,
+                    end,
+                    signature: None,
+                    doc_start: None,
+                });
+                continue;
+            }
+            let (kind, name) = Self::statement(content, &head);
+            match statements.last_mut() {
+                // Runs of statements on one table, with nothing but whitespace between them
+                Some(last) if last.kind == kind && last.name == name && content[last.end..start].trim().is_empty() => {
+                    last.end = end;
+                }
+                _ => statements.push(ScannedItem {
+                    kind,
+                    name,
+                    context: Vec::new(),
+                    start,
+                    end,
+                    signature: None,
+                    doc_start: None,
+                }),
+            }
+        }
+        match definitions.is_empty() {
+            true => statements,
+            false => definitions,
+        }
+    }
+
+    fn is_comment_line(&self, line: &str) -> bool {
+        line.starts_with("--") || line.starts_with("/*") || line.starts_with('*')
+    }
+}
+
+// One chunk per heading section, up to the next heading of any level, named after the
+// heading and in the context of the headings above it. Text before the first heading
+// is the `preamble`. Over the token limit a section is cut between paragraphs, and a
+// fenced code block is never cut
+#[cfg(feature = "lang-markdown")]
+struct MarkdownDriver;
+#[cfg(feature = "lang-markdown")]
+impl MarkdownDriver {
+    // Character and length of the fence a line opens or closes, up to three spaces in
+    fn fence(line: &str) -> Option<(char, usize)> {
+        let trimmed = line.trim_start_matches(' ');
+        if line.len() - trimmed.len() > 3 {
+            return None;
+        }
+        let marker = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+        let run = trimmed.len() - trimmed.trim_start_matches(marker).len();
+        (run >= 3).then_some((marker, run))
+    }
+
+    // Level and text of an ATX heading, `## Installation ##`
+    fn heading(line: &str) -> Option<(usize, &str)> {
+        let trimmed = line.trim_start_matches(' ');
+        if line.len() - trimmed.len() > 3 {
+            return None;
+        }
+        let level = trimmed.len() - trimmed.trim_start_matches('#').len();
+        let rest = &trimmed[level..];
+        if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with([' ', '\t'])) {
+            return None;
+        }
+        let text = rest.trim().trim_end_matches('#').trim_end();
+        Some((level, text))
+    }
+
+    // Whether each line is in a fenced code block, fences included
+    fn fenced_lines(lines: &[&str]) -> Vec<bool> {
+        let mut fenced = vec![false; lines.len()];
+        let mut open: Option<(char, usize)> = None;
+        for (row, line) in lines.iter().enumerate() {
+            match (open, Self::fence(line)) {
+                (None, Some(fence)) => open = Some(fence),
+                // A closing fence has nothing after it
+                (Some((marker, run)), Some((closing, length)))
+                    if closing == marker && length >= run && line.trim().len() == length =>
+                {
+                    fenced[row] = true;
+                    open = None;
+                    continue;
+                }
+                _ => {}
+            }
+            fenced[row] = open.is_some();
+        }
+        fenced
+    }
+}
+#[cfg(feature = "lang-markdown")]
+impl ScanDriver for MarkdownDriver {
+    fn get_name(&self) -> &'static str { "Markdown" }
+
+    fn find_items(&self, content: &str) -> Vec<ScannedItem> {
+        let mut offsets = Vec::new();
+        let mut lines = Vec::new();
+        let mut offset = 0;
+        for line in content.split_inclusive('\n') {
+            offsets.push(offset);
+            lines.push(line.trim_end_matches(['\n', '\r']));
+            offset += line.len();
+        }
+        let fenced = Self::fenced_lines(&lines);
+        // Front matter is never a heading
+        let mut first = 0;
+        if lines.first().is_some_and(|line| line.trim() == "---") {
+            first = (1..lines.len())
+                .find(|&row| matches!(lines[row].trim(), "---" | "..."))
+                .map_or(0, |row| row + 1);
+        }
+
+        // (row, level, text), a setext heading starts on its text line
+        let mut headings = Vec::new();
+        let mut row = first;
+        while row < lines.len() {
+            let line = lines[row];
+            if fenced[row] || line.trim().is_empty() {
+                row += 1;
+                continue;
+            }
+            if let Some((level, text)) = Self::heading(line) {
+                headings.push((row, level, text));
+                row += 1;
+                continue;
+            }
+            let underline = lines.get(row + 1).map(|next| next.trim()).unwrap_or("");
+            let level = match underline {
+                _ if fenced.get(row + 1).copied().unwrap_or(true) => None,
+                u if !u.is_empty() && u.chars().all(|c| c == '=') => Some(1),
+                u if u.len() >= 2 && u.chars().all(|c| c == '-') => Some(2),
+                _ => None,
+            };
+            let paragraph_start = row == first || lines[row - 1].trim().is_empty();
+            match level {
+                Some(level) if paragraph_start && !line.starts_with([' ', '\t', '-', '*', '>']) => {
+                    headings.push((row, level, line.trim()));
+                    row += 2;
+                }
+                _ => row += 1,
+            }
+        }
+
+        let mut items = Vec::new();
+        let first_heading = headings.first().map_or(lines.len(), |(row, _, _)| *row);
+        let end_of = |row: usize| offsets.get(row).copied().unwrap_or(content.len());
+        if !content[..end_of(first_heading)].trim().is_empty() {
+            items.push(ScannedItem {
+                kind: "preamble",
+                name: "preamble".to_string(),
+                context: Vec::new(),
+                start: 0,
+                end: end_of(first_heading),
+                signature: None,
+                doc_start: None,
+            });
+        }
+        let mut outline: Vec<(usize, ContextPart)> = Vec::new();
+        for (i, &(row, level, text)) in headings.iter().enumerate() {
+            while outline.last().is_some_and(|(above, _)| *above >= level) {
+                outline.pop();
+            }
+            let name = match text.is_empty() {
+                true => "anonymous".to_string(),
+                false => text.to_string(),
+            };
+            let next = headings.get(i + 1).map_or(lines.len(), |(next, _, _)| *next);
+            items.push(ScannedItem {
+                kind: "section",
+                name: name.clone(),
+                context: outline.iter().map(|(_, part)| part.clone()).collect(),
+                start: offsets[row],
+                end: end_of(next),
+                signature: Some(lines[row].trim().to_string()),
+                doc_start: None,
+            });
+            let kind = format!("h{}", level);
+            outline.push((level, ContextPart { kind, name }));
+        }
+        items
+    }
+
+    // HTML comments are text like any other
+    fn is_comment_line(&self, _line: &str) -> bool { false }
+
+    // Cuts go before a paragraph, a block of lines after a blank one, filled up to the
+    // limit. A block over the limit on its own is cut by lines unless it holds a fence
+    fn split(&self, text: &str, max_tokens: usize, tokenizer: &Tokenizer) -> Option<Vec<Part>> {
+        if tokenizer.count(text) <= max_tokens {
+            return None;
+        }
+        let lines: Vec<&str> = text.split('\n').collect();
+        let fenced = Self::fenced_lines(&lines);
+        let costs: Vec<usize> = lines.iter().map(|line| tokenizer.count(line) + 1).collect();
+        // The text starts with the comment line, empty here, which is no block of its own
+        let first_text = lines.iter().position(|line| !line.trim().is_empty()).unwrap_or(0);
+        let mut blocks = vec![0];
+        for row in first_text + 1..lines.len() {
+            if !fenced[row] && !lines[row].trim().is_empty() && lines[row - 1].trim().is_empty() {
+                blocks.push(row);
+            }
+        }
+        blocks.push(lines.len());
+
+        let mut parts = Vec::new();
+        let mut start = 0;
+        let mut block = 0;
+        while start < lines.len() {
+            let mut end = blocks[block + 1];
+            let mut tokens: usize = costs[start..end].iter().sum();
+            block += 1;
+            if tokens > max_tokens && !fenced[start..end].contains(&true) {
+                let paragraph = lines[start..end].join("\n");
+                for (part, tokens, offset, _) in split_text_by_token_limit(&paragraph, max_tokens, 0, tokenizer) {
+                    parts.push((part, tokens, start + offset, 0));
+                }
+                start = end;
+                continue;
+            }
+            while block + 1 < blocks.len() {
+                let more: usize = costs[end..blocks[block + 1]].iter().sum();
+                if tokens + more > max_tokens {
+                    break;
+                }
+                tokens += more;
+                end = blocks[block + 1];
+                block += 1;
+            }
+            parts.push((lines[start..end].join("\n"), tokens, start, 0));
+            start = end;
+        }
+        Some(parts)
+    }
+}
+
+#[cfg(feature = "lang-json")]
+const JSON_SYNTAX: Syntax = Syntax {
+    // Config files like tsconfig.json have comments
+    line_comments: &["//"],
+    block_comments: &[("/*", "*/")],
+    strings: &[("\"", "\"")],
+    code: &[],
+    dollar_quotes: false,
+};
+
+// The keys of the top-level object, typed `key`. A key too large for one chunk is
+// chunked as the keys of its object value, in the context `key(name)`
+#[cfg(feature = "lang-json")]
+struct JsonDriver;
+#[cfg(feature = "lang-json")]
+impl JsonDriver {
+    // First code byte from `from` on that isn't whitespace
+    fn next_token(content: &str, mask: &[bool], from: usize) -> Option<usize> {
+        let bytes = content.as_bytes();
+        (from..bytes.len()).find(|&i| !bytes[i].is_ascii_whitespace() && (mask[i] || bytes[i] == b'"'))
+    }
+
+    // Offset after the string opening at `open`
+    fn string_end(mask: &[bool], open: usize) -> usize {
+        (open + 1..mask.len()).find(|&i| mask[i]).unwrap_or(mask.len())
+    }
+
+    // Offset of the `,`, `}` or `]` ending the value starting at `from`
+    fn value_end(content: &str, mask: &[bool], from: usize) -> usize {
+        let bytes = content.as_bytes();
+        let mut depth = 0;
+        for i in from..bytes.len() {
+            if !mask[i] {
+                continue;
+            }
+            match bytes[i] {
+                b'{' | b'[' => depth += 1,
+                b'}' | b']' if depth == 0 => return i,
+                b'}' | b']' => depth -= 1,
+                b',' if depth == 0 => return i,
+                _ => {}
+            }
+        }
+        bytes.len()
+    }
+
+    // Offset of the value of the pair whose key opens at `key`
+    fn value_start(content: &str, mask: &[bool], key: usize) -> Option<usize> {
+        let colon = Self::next_token(content, mask, Self::string_end(mask, key))?;
+        (content.as_bytes()[colon] == b':').then(|| Self::next_token(content, mask, colon + 1))?
+    }
+
+    // Pairs of the object opening at `open`
+    fn pairs(content: &str, mask: &[bool], open: usize, context: &[ContextPart]) -> Vec<ScannedItem> {
+        let bytes = content.as_bytes();
+        let mut items = Vec::new();
+        let mut at = open + 1;
+        while let Some(key) = Self::next_token(content, mask, at) {
+            if bytes[key] != b'"' {
+                break;
+            }
+            let Some(value) = Self::value_start(content, mask, key) else {
+                break;
+            };
+            let end = Self::value_end(content, mask, value);
+            items.push(ScannedItem {
+                kind: "key",
+                name: content[key + 1..Self::string_end(mask, key) - 1].to_string(),
+                context: context.to_vec(),
+                start: key,
+                end: key + content[key..end].trim_end().len(),
+                signature: None,
+                doc_start: None,
+            });
+            if bytes.get(end) != Some(&b',') {
+                break;
+            }
+            at = end + 1;
+        }
+        items
+    }
+}
+#[cfg(feature = "lang-json")]
+impl ScanDriver for JsonDriver {
+    fn get_name(&self) -> &'static str { "JSON" }
+
+    fn find_items(&self, content: &str) -> Vec<ScannedItem> {
+        let mask = code_mask(content, &JSON_SYNTAX);
+        match Self::next_token(content, &mask, 0) {
+            Some(open) if content.as_bytes()[open] == b'{' => Self::pairs(content, &mask, open, &[]),
+            _ => Vec::new(),
+        }
+    }
+
+    fn inner_items(&self, content: &str, item: &ScannedItem) -> Option<Vec<ScannedItem>> {
+        let mask = code_mask(content, &JSON_SYNTAX);
+        let value = Self::value_start(content, &mask, item.start)?;
+        if content.as_bytes()[value] != b'{' {
+            return None;
+        }
+        let mut context = item.context.clone();
+        context.push(ContextPart { kind: "key".to_string(), name: item.name.clone() });
+        Some(Self::pairs(content, &mask, value, &context))
+    }
+
+    fn is_comment_line(&self, line: &str) -> bool {
+        line.starts_with("//") || line.starts_with("/*") || line.starts_with('*')
+    }
+
+    // A top-level array, or a lone value
+    fn chunks_whole_file_without_items(&self) -> bool { true }
+}
+
+// Keys at the top level of each document, typed `key`, in the context `document(N)` when
+// the file holds several. A Kubernetes manifest, a document with `apiVersion` and `kind`,
+// is one `document` item named after its `metadata.name`. An item too large for one
+// chunk is chunked as the keys one level down, in the context `key(name)`
+#[cfg(feature = "lang-yaml")]
+struct YamlDriver;
+#[cfg(feature = "lang-yaml")]
+impl YamlDriver {
+    // Lines with their offsets, without line endings
+    fn lines(content: &str) -> Vec<(usize, &str)> {
+        let mut lines = Vec::new();
+        let mut offset = 0;
+        for line in content.split_inclusive('\n') {
+            lines.push((offset, line.trim_end_matches(['\n', '\r'])));
+            offset += line.len();
+        }
+        lines
+    }
+
+    fn indent(line: &str) -> usize {
+        line.len() - line.trim_start_matches(' ').len()
+    }
+
+    fn is_filler(line: &str) -> bool {
+        line.trim().is_empty() || line.trim_start().starts_with('#')
+    }
+
+    // Key of a `key: value` or `key:` line, indentation stripped
+    fn key(line: &str) -> Option<&str> {
+        let (key, rest) = match line.chars().next()? {
+            quote @ ('"' | '\'') => {
+                let end = line[1..].find(quote)? + 1;
+                (&line[1..end], &line[end + 1..])
+            }
+            '-' | '#' | '{' | '[' | '?' | '%' | '&' | '*' | '!' | '|' | '>' => return None,
+            _ => {
+                let colon = line.match_indices(':').map(|(i, _)| i).find(|&i| {
+                    line[i + 1..].is_empty() || line[i + 1..].starts_with([' ', '\t'])
+                })?;
+                (line[..colon].trim_end(), &line[colon..])
+            }
+        };
+        let after = rest.strip_prefix(':')?;
+        (!key.is_empty() && (after.is_empty() || after.starts_with([' ', '\t']))).then_some(key)
+    }
+
+    // Value of a `key: value` line, unquoted
+    fn value(line: &str) -> &str {
+        let value = line.split_once(": ").map_or("", |(_, value)| value.trim());
+        let value = value.split(" #").next().unwrap_or(value).trim();
+        value.trim_matches(|c| c == '"' || c == '\'')
+    }
+
+    // Rows `from..to` trimmed of filler lines at both ends
+    fn content_rows(lines: &[(usize, &str)], from: usize, to: usize) -> Option<(usize, usize)> {
+        let first = (from..to).find(|&row| !Self::is_filler(lines[row].1))?;
+        let last = (first..to).rev().find(|&row| !Self::is_filler(lines[row].1))?;
+        Some((first, last))
+    }
+
+    fn end_of(lines: &[(usize, &str)], row: usize) -> usize {
+        lines[row].0 + lines[row].1.len()
+    }
+
+    // Key lines at `indent` in rows `from..to`, each up to the next one
+    fn keys(lines: &[(usize, &str)], from: usize, to: usize, indent: usize, context: &[ContextPart]) -> Vec<ScannedItem> {
+        let rows: Vec<(usize, &str)> = (from..to)
+            .filter(|&row| !Self::is_filler(lines[row].1) && Self::indent(lines[row].1) == indent)
+            .filter_map(|row| Some((row, Self::key(&lines[row].1[indent..])?)))
+            .collect();
+        let mut items = Vec::new();
+        for (i, &(row, key)) in rows.iter().enumerate() {
+            let next = rows.get(i + 1).map_or(to, |(next, _)| *next);
+            let Some((_, last)) = Self::content_rows(lines, row, next) else {
+                continue;
+            };
+            items.push(ScannedItem {
+                kind: "key",
+                name: key.to_string(),
+                context: context.to_vec(),
+                start: lines[row].0 + indent,
+                end: Self::end_of(lines, last),
+                signature: None,
+                doc_start: None,
+            });
+        }
+        items
+    }
+
+    // Rows of each document, between `---` lines
+    fn documents(lines: &[(usize, &str)]) -> Vec<(usize, usize)> {
+        let mut documents = Vec::new();
+        let mut start = 0;
+        for (row, (_, line)) in lines.iter().enumerate() {
+            let line = line.trim_end();
+            if line == "---" || line.starts_with("--- ") || line == "..." {
+                documents.push((start, row));
+                start = row + 1;
+            }
+        }
+        documents.push((start, lines.len()));
+        documents.retain(|&(from, to)| Self::content_rows(lines, from, to).is_some());
+        documents
+    }
+}
+#[cfg(feature = "lang-yaml")]
+impl ScanDriver for YamlDriver {
+    fn get_name(&self) -> &'static str { "YAML" }
+
+    fn find_items(&self, content: &str) -> Vec<ScannedItem> {
+        let lines = Self::lines(content);
+        let documents = Self::documents(&lines);
+        let mut items = Vec::new();
+        for (n, &(from, to)) in documents.iter().enumerate() {
+            let context = match documents.len() {
+                1 => Vec::new(),
+                _ => vec![ContextPart { kind: "document".to_string(), name: (n + 1).to_string() }],
+            };
+            let keys = Self::keys(&lines, from, to, 0, &context);
+            let has = |name: &str| keys.iter().any(|key| key.name == name);
+            if !(has("apiVersion") && has("kind")) {
+                items.extend(keys);
+                continue;
+            }
+            let Some((first, last)) = Self::content_rows(&lines, from, to) else {
+                continue;
+            };
+            let value_of = |key: &ScannedItem| Self::value(content[key.start..].lines().next().unwrap_or("")).to_string();
+            let name = keys
+                .iter()
+                .find(|key| key.name == "metadata")
+                .and_then(|metadata| {
+                    let row = lines.partition_point(|(offset, _)| *offset <= metadata.start) - 1;
+                    let end = lines.partition_point(|(offset, _)| *offset < metadata.end);
+                    let (child, _) = Self::content_rows(&lines, row + 1, end)?;
+                    let indent = Self::indent(lines[child].1);
+                    let children = Self::keys(&lines, row + 1, end, indent, &[]);
+                    children.iter().find(|key| key.name == "name").map(value_of)
+                })
+                .filter(|name| !name.is_empty())
+                .or_else(|| keys.iter().find(|key| key.name == "kind").map(value_of))
+                .unwrap_or_else(|| "document".to_string());
+            items.push(ScannedItem {
+                kind: "document",
+                name,
+                context,
+                start: lines[first].0,
+                end: Self::end_of(&lines, last),
+                signature: None,
+                doc_start: None,
+            });
+        }
+        items
+    }
+
+    fn inner_items(&self, content: &str, item: &ScannedItem) -> Option<Vec<ScannedItem>> {
+        let lines = Self::lines(content);
+        let row = lines.partition_point(|(offset, _)| *offset <= item.start) - 1;
+        let to = lines.partition_point(|(offset, _)| *offset < item.end);
+        let mut context = item.context.clone();
+        if item.kind == "document" {
+            context.push(ContextPart { kind: "document".to_string(), name: item.name.clone() });
+            return Some(Self::keys(&lines, row, to, 0, &context));
+        }
+        let (child, _) = Self::content_rows(&lines, row + 1, to)?;
+        let indent = Self::indent(lines[child].1);
+        if indent <= Self::indent(lines[row].1) {
+            return None;
+        }
+        context.push(ContextPart { kind: "key".to_string(), name: item.name.clone() });
+        Some(Self::keys(&lines, row + 1, to, indent, &context))
+    }
+
+    fn is_comment_line(&self, line: &str) -> bool { line.starts_with('#') }
+
+    // A document of a list or a lone value
+    fn chunks_whole_file_without_items(&self) -> bool { true }
+}
+
+// Only scanners compiled in through their `lang-*` feature are listed
+pub fn all_scan_drivers() -> Vec<Box<dyn ScanDriver>> {
+    vec![
+        #[cfg(feature = "lang-zig")]
+        Box::new(ZigDriver),
+        #[cfg(feature = "lang-julia")]
+        Box::new(JuliaDriver),
+        #[cfg(feature = "lang-perl")]
+        Box::new(PerlDriver),
+        #[cfg(feature = "lang-proto")]
+        Box::new(ProtoDriver),
+        #[cfg(feature = "lang-graphql")]
+        Box::new(GraphQlDriver),
+        #[cfg(feature = "lang-hcl")]
+        Box::new(HclDriver),
+        #[cfg(feature = "lang-dockerfile")]
+        Box::new(DockerfileDriver),
+        #[cfg(feature = "lang-sql")]
+        Box::new(SqlDriver),
+        #[cfg(feature = "lang-markdown")]
+        Box::new(MarkdownDriver),
+        #[cfg(feature = "lang-yaml")]
+        Box::new(YamlDriver),
+        #[cfg(feature = "lang-json")]
+        Box::new(JsonDriver),
+    ]
+}
+
+pub fn get_scan_driver(extension: &str) -> Option<Box<dyn ScanDriver>> {
+    match extension {
+        #[cfg(feature = "lang-zig")]
+        "zig" => Some(Box::new(ZigDriver)),
+        #[cfg(feature = "lang-julia")]
+        "jl" => Some(Box::new(JuliaDriver)),
+        #[cfg(feature = "lang-perl")]
+        "pl" | "pm" => Some(Box::new(PerlDriver)),
+        #[cfg(feature = "lang-proto")]
+        "proto" => Some(Box::new(ProtoDriver)),
+        #[cfg(feature = "lang-graphql")]
+        "graphql" | "gql" => Some(Box::new(GraphQlDriver)),
+        #[cfg(feature = "lang-hcl")]
+        "tf" | "hcl" => Some(Box::new(HclDriver)),
+        #[cfg(feature = "lang-dockerfile")]
+        "dockerfile" => Some(Box::new(DockerfileDriver)),
+        #[cfg(feature = "lang-sql")]
+        "sql" => Some(Box::new(SqlDriver)),
+        #[cfg(feature = "lang-markdown")]
+        "md" | "markdown" => Some(Box::new(MarkdownDriver)),
+        #[cfg(feature = "lang-yaml")]
+        "yaml" | "yml" => Some(Box::new(YamlDriver)),
+        #[cfg(feature = "lang-json")]
+        "json" => Some(Box::new(JsonDriver)),
+        _ => None,
+    }
+}
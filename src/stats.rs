@@ -0,0 +1,172 @@
+use crate::output::OutputMeta;
+use crate::stream::{hash_of, read_lines};
+use crate::types::ChunkData;
+use anyhow::Result;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::Path;
+
+// (file, line, kind, text), ordered so the report comes out sorted by file
+pub type MarkerRow = (String, usize, String, String);
+
+#[derive(Default)]
+struct Totals {
+    records: usize,
+    tokens: u64,
+    max_tokens: u64,
+}
+
+impl Totals {
+    fn add(&mut self, tokens: u64) {
+        self.records += 1;
+        self.tokens += tokens;
+        self.max_tokens = self.max_tokens.max(tokens);
+    }
+}
+
+// Aggregates an output file in one pass. Only per-language and per-type totals and a
+// hash per distinct file path are kept, never the records themselves
+pub fn run_stats(path: &Path) -> Result<()> {
+    let mut all = Totals::default();
+    let mut languages: BTreeMap<String, Totals> = BTreeMap::new();
+    let mut chunk_types: BTreeMap<String, usize> = BTreeMap::new();
+    let mut files = HashSet::new();
+    let mut invalid = 0;
+
+    for line in read_lines(path)? {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Ok(Value::Object(record)) = serde_json::from_str::<Value>(&line) else {
+            invalid += 1;
+            continue;
+        };
+        let text = |field: &str| {
+            record
+                .get(field)
+                .and_then(Value::as_str)
+                .unwrap_or("unknown")
+                .to_string()
+        };
+        let tokens = record
+            .get("token_count")
+            .and_then(Value::as_u64)
+            .unwrap_or(0);
+
+        all.add(tokens);
+        languages.entry(text("language")).or_default().add(tokens);
+        *chunk_types.entry(text("chunk_type")).or_default() += 1;
+        files.insert(hash_of(&text("file_path")));
+    }
+
+    println!("Records: {} from {} files", all.records, files.len());
+    if let Some(meta) = OutputMeta::read(path)? {
+        println!("Ids: {}", meta.describe());
+    }
+    if invalid > 0 {
+        println!("Lines that are not JSON objects, skipped: {}", invalid);
+    }
+    println!(
+        "Tokens: {} total, {} mean, {} max",
+        all.tokens,
+        all.tokens / all.records.max(1) as u64,
+        all.max_tokens
+    );
+    println!("By language:");
+    for (language, totals) in &languages {
+        println!(
+            "  {}: {} records, {} tokens, {} max",
+            language, totals.records, totals.tokens, totals.max_tokens
+        );
+    }
+    println!("By chunk type:");
+    for (chunk_type, count) in &chunk_types {
+        println!("  {}: {}", chunk_type, count);
+    }
+    Ok(())
+}
+
+// Collected from the chunks of a run as they go out: the count, markers for
+// `--report markers`, for `--dry-run` totals per file and for `--list-types` the
+// chunks per type
+#[derive(Default)]
+pub struct RunStats {
+    pub chunks: usize,
+    // Chunks --deduplicate left out
+    pub duplicates: usize,
+    pub marker_rows: BTreeSet<MarkerRow>,
+    collect_markers: bool,
+    files: Option<BTreeMap<String, Totals>>,
+    chunk_types: Option<BTreeMap<String, usize>>,
+}
+
+impl RunStats {
+    pub fn new(collect_markers: bool, per_file: bool, per_type: bool) -> Self {
+        RunStats {
+            collect_markers,
+            files: per_file.then(BTreeMap::new),
+            chunk_types: per_type.then(BTreeMap::new),
+            ..Default::default()
+        }
+    }
+
+    pub fn add(&mut self, chunk: &ChunkData) {
+        self.chunks += 1;
+        if self.collect_markers {
+            for marker in &chunk.markers {
+                self.marker_rows.insert((
+                    chunk.file_path.to_string(),
+                    marker.line,
+                    marker.kind.clone(),
+                    marker.text.clone(),
+                ));
+            }
+        }
+        if let Some(files) = &mut self.files {
+            files
+                .entry(chunk.file_path.to_string())
+                .or_default()
+                .add(chunk.token_count as u64);
+        }
+        if let Some(chunk_types) = &mut self.chunk_types {
+            *chunk_types.entry(chunk.chunk_type.to_string()).or_default() += 1;
+        }
+    }
+
+    // One row per chunk type, for picking --filter-type values
+    pub fn print_types(&self) {
+        let Some(chunk_types) = &self.chunk_types else {
+            return;
+        };
+        println!("Chunk types ({}):", chunk_types.len());
+        for (chunk_type, count) in chunk_types {
+            println!("  {}: {}", chunk_type, count);
+        }
+    }
+
+    // One row per file, then the grand total
+    pub fn print_files(&self) {
+        let Some(files) = &self.files else {
+            return;
+        };
+        let mut all = Totals::default();
+        println!("{:>8} {:>10} {:>8}  File", "Chunks", "Tokens", "Largest");
+        for (file, totals) in files {
+            println!(
+                "{:>8} {:>10} {:>8}  {}",
+                totals.records, totals.tokens, totals.max_tokens, file
+            );
+            all.records += totals.records;
+            all.tokens += totals.tokens;
+            all.max_tokens = all.max_tokens.max(totals.max_tokens);
+        }
+        println!(
+            "{:>8} {:>10} {:>8}  Total of {} files",
+            all.records,
+            all.tokens,
+            all.max_tokens,
+            files.len()
+        );
+    }
+}
@@ -0,0 +1,217 @@
+use crate::safety;
+use anyhow::{Result, anyhow};
+use std::collections::hash_map::DefaultHasher;
+use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Files smaller than this are read without progress lines
+const PROGRESS_MIN_BYTES: u64 = 64 << 20;
+
+// Spilled entries are hash-partitioned into this many temp files at most, so the
+// open file count stays well under the usual descriptor limit
+const MAX_BUCKETS: u64 = 256;
+const MIN_BUCKETS: u64 = 16;
+
+static SPILL_DIRS: AtomicUsize = AtomicUsize::new(0);
+
+// Counts bytes as they are read and prints a progress line to stderr every 5%
+struct ProgressReader<R> {
+    inner: R,
+    label: String,
+    total: u64,
+    read: u64,
+    next_report: u64,
+}
+
+impl<R: Read> Read for ProgressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read += n as u64;
+        if self.total >= PROGRESS_MIN_BYTES && self.read >= self.next_report {
+            eprintln!(
+                "{}: read {} of {} MiB ({}%)",
+                self.label,
+                self.read >> 20,
+                self.total >> 20,
+                self.read * 100 / self.total
+            );
+            self.next_report += self.total / 20;
+        }
+        Ok(n)
+    }
+}
+
+// Lines of an output file, read through a fixed-size buffer with progress by bytes read
+pub fn read_lines(path: &Path) -> Result<impl Iterator<Item = io::Result<String>>> {
+    let file = File::open(path)?;
+    let total = file.metadata()?.len();
+    let reader = ProgressReader {
+        inner: file,
+        label: path.display().to_string(),
+        total,
+        read: 0,
+        next_report: total / 20,
+    };
+    Ok(BufReader::new(reader).lines())
+}
+
+// Total size of the inputs, used to size the spill partitions
+pub fn input_bytes(paths: &[&Path]) -> u64 {
+    paths
+        .iter()
+        .filter_map(|path| fs::metadata(path).ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+pub fn hash_of(value: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+// One keyed value, `source` tells apart the inputs a caller feeds in
+pub struct Entry {
+    pub key: String,
+    pub source: u8,
+    pub value: u64,
+}
+
+impl Entry {
+    fn size(&self) -> usize {
+        self.key.len() + size_of::<Entry>()
+    }
+}
+
+// Keyed entries held in memory up to `max_memory` bytes, then hash-partitioned into
+// temp files. `finish` hands them back in groups, all entries of one key in the same
+// group and in the order they were pushed. Across groups keys come ordered by their
+// hash then by themselves, spilled or not, so callers print the same either way
+pub struct SpillMap {
+    max_memory: usize,
+    input_bytes: u64,
+    entries: Vec<Entry>,
+    used: usize,
+    dir: Option<PathBuf>,
+    buckets: Vec<BufWriter<File>>,
+}
+
+impl SpillMap {
+    pub fn new(max_memory: usize, input_bytes: u64) -> Self {
+        SpillMap {
+            max_memory,
+            input_bytes,
+            entries: Vec::new(),
+            used: 0,
+            dir: None,
+            buckets: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, entry: Entry) -> Result<()> {
+        if !self.buckets.is_empty() {
+            return self.write(&entry);
+        }
+        self.used += entry.size();
+        self.entries.push(entry);
+        if self.used > self.max_memory {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    // Partition count from the input size, since every key entry is smaller than the
+    // record line it came from
+    fn spill(&mut self) -> Result<()> {
+        let count =
+            (self.input_bytes / self.max_memory.max(1) as u64 + 1).clamp(MIN_BUCKETS, MAX_BUCKETS);
+        let dir = std::env::temp_dir().join(format!(
+            "smart-chunker-spill-{}-{}",
+            std::process::id(),
+            SPILL_DIRS.fetch_add(1, Ordering::Relaxed)
+        ));
+        safety::create_dir_all("spill directory", &dir)?;
+        self.dir = Some(dir.clone());
+        for i in 0..count {
+            let file = safety::create_file("spill file", &dir.join(format!("{}.tsv", i)))?;
+            self.buckets.push(BufWriter::new(file));
+        }
+        eprintln!(
+            "Memory limit of {} bytes reached, spilling keys to {} files in {}",
+            self.max_memory,
+            count,
+            dir.display()
+        );
+
+        for entry in std::mem::take(&mut self.entries) {
+            self.write(&entry)?;
+        }
+        self.used = 0;
+        Ok(())
+    }
+
+    // Keys are JSON scalars rendered as text, they never contain a line break.
+    // Buckets hold consecutive ranges of the hash, read in turn they stay in order
+    fn write(&mut self, entry: &Entry) -> Result<()> {
+        let range = (hash_of(&entry.key) as u128 * self.buckets.len() as u128) >> 64;
+        let bucket = range as usize;
+        writeln!(
+            self.buckets[bucket],
+            "{}\t{}\t{}",
+            entry.source, entry.value, entry.key
+        )?;
+        Ok(())
+    }
+
+    pub fn finish(mut self, mut group: impl FnMut(Vec<Entry>) -> Result<()>) -> Result<()> {
+        let Some(dir) = self.dir.clone() else {
+            return group(in_key_order(std::mem::take(&mut self.entries)));
+        };
+        for bucket in &mut self.buckets {
+            bucket.flush()?;
+        }
+        for i in 0..self.buckets.len() {
+            let reader = BufReader::new(File::open(dir.join(format!("{}.tsv", i)))?);
+            let mut entries = Vec::new();
+            for line in reader.lines() {
+                entries.push(parse_entry(&line?)?);
+            }
+            group(in_key_order(entries))?;
+        }
+        Ok(())
+    }
+}
+
+// The sort is stable, entries of one key keep the order they were pushed in
+fn in_key_order(mut entries: Vec<Entry>) -> Vec<Entry> {
+    entries.sort_by_cached_key(|entry| (hash_of(&entry.key), entry.key.clone()));
+    entries
+}
+
+impl Drop for SpillMap {
+    fn drop(&mut self) {
+        if let Some(dir) = &self.dir {
+            self.buckets.clear();
+            let _ = fs::remove_dir_all(dir);
+        }
+    }
+}
+
+fn parse_entry(line: &str) -> Result<Entry> {
+    let mut fields = line.splitn(3, '\t');
+    let mut next = || {
+        fields
+            .next()
+            .ok_or_else(|| anyhow!("corrupt spill line: {}", line))
+    };
+    let source = next()?.parse()?;
+    let value = next()?.parse()?;
+    Ok(Entry {
+        key: next()?.to_string(),
+        source,
+        value,
+    })
+}
@@ -1,20 +1,441 @@
-use anyhow::{Result, anyhow};
-use serde::Serialize;
-use std::sync::Mutex;
-use tree_sitter::{Language, Node, Parser as TreeParser, Query, QueryCursor};
+#[cfg(feature = "cache")]
+use crate::cache::FileCache;
+use crate::files::Tokenizer;
+use crate::git::BlobReader;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::AtomicUsize;
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdFormat {
+    /// Hex-encoded sha256 (default)
+    Hex,
+    /// UUIDv5 derived from the sha256 under a fixed namespace
+    Uuid,
+    /// First 8 bytes of the sha256 as a signed integer
+    Int64,
+}
+
+// Components hashed into a chunk id, always combined in this order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdField {
+    /// The chunk's code
+    Content,
+    /// The file path
+    Path,
+    /// chunk_name
+    Name,
+    /// chunk_type
+    Type,
+    /// Index of the part within a split chunk
+    Part,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ChunkId {
+    Text(String),
+    Int(i64),
+}
+
+impl Default for ChunkId {
+    fn default() -> Self {
+        ChunkId::Text(String::new())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SplitStrategy {
+    /// Cut at the best statement boundary, blank line or comment shortly before the limit (default)
+    Boundaries,
+    /// Cut right before the first line over the limit
+    LinesGreedy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PrintPaths {
+    /// Relative to the scanned folder
+    Relative,
+    /// Absolute, resolved against the working directory
+    Absolute,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// One JSON object per line (default)
+    Jsonl,
+    /// A single JSON array of all chunks
+    Json,
+    /// One row per chunk with its main fields, code included
+    Csv,
+    /// A fenced code block per chunk under its file path and context
+    Markdown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Report {
+    /// Aggregated TODO/FIXME style markers, sorted by file
+    Markers,
+}
+
+// `-m 512` sets the default limit, `-m python=1024` overrides it for one language
+#[derive(Debug, Clone)]
+pub enum TokenLimit {
+    Default(usize),
+    Language(String, usize),
+}
+
+// `--at-rev`: files are read from this commit of the repository at `repo`
+#[derive(Clone)]
+pub struct GitRevision {
+    pub repo: PathBuf,
+    pub commit: Arc<str>,
+    pub blobs: Arc<BlobReader>,
+}
+
+// `-m` when neither the command line nor the config sets one, sized for GPT-4
+pub const DEFAULT_MAX_CHUNK_TOKENS: usize = 800;
+
+#[derive(Clone)]
+pub struct ChunkOptions {
+    pub max_chunk_tokens: usize,
+    // Keyed by lowercased driver name
+    pub language_max_tokens: HashMap<String, usize>,
+    pub id_format: IdFormat,
+    pub id_fields: Vec<IdField>,
+    pub markers: Vec<String>,
+    pub stale_retries: usize,
+    pub emit_gaps: bool,
+    pub parse_timeout_ms: u64,
+    pub max_comment_gap: usize,
+    pub coverage: bool,
+    pub context_filters: Vec<ContextPart>,
+    pub small_file_threshold: usize,
+    pub emit_normalized: bool,
+    pub debug_fields: bool,
+    pub wide_line_threshold: usize,
+    pub lsp_ranges: bool,
+    pub max_context_chars: usize,
+    pub max_signature_chars: usize,
+    pub split_strategy: SplitStrategy,
+    pub split_lookahead: usize,
+    // Tokens of a part's last lines repeated at the start of the next part
+    pub overlap_tokens: usize,
+    pub at_rev: Option<GitRevision>,
+    pub print_paths: Option<PrintPaths>,
+    pub audit: bool,
+    // Absolute folder --print-paths relative strips, the parent when --path is a file
+    pub path_base: PathBuf,
+    pub tokenizer: Tokenizer,
+    #[cfg(feature = "cache")]
+    pub cache: Option<Arc<FileCache>>,
+    // `--min-tokens`, parts under it are dropped
+    pub min_tokens: usize,
+    // `--max-tokens` and `--max-tokens-factor`: items over max_tokens × factor tokens are
+    // skipped with a warning instead of split. None splits them whatever their size
+    pub max_tokens: Option<usize>,
+    pub max_tokens_factor: usize,
+    // Shared by every thread and project, for the totals after the run
+    pub token_filtered: Arc<TokenFiltered>,
+    // `--filter-type`, empty keeps every chunk type
+    pub type_filters: Vec<String>,
+}
+
+// What --min-tokens and --max-tokens left out
+#[derive(Default)]
+pub struct TokenFiltered {
+    pub parts_under_min: AtomicUsize,
+    pub items_over_max: AtomicUsize,
+}
+
+// The CLI's defaults, for library callers
+impl Default for ChunkOptions {
+    fn default() -> Self {
+        ChunkOptions {
+            max_chunk_tokens: DEFAULT_MAX_CHUNK_TOKENS,
+            language_max_tokens: HashMap::new(),
+            id_format: IdFormat::Hex,
+            id_fields: vec![IdField::Content, IdField::Path, IdField::Part],
+            markers: ["TODO", "FIXME", "HACK", "XXX"].map(String::from).to_vec(),
+            stale_retries: 2,
+            emit_gaps: false,
+            parse_timeout_ms: 0,
+            max_comment_gap: 1,
+            coverage: false,
+            context_filters: Vec::new(),
+            small_file_threshold: 300,
+            emit_normalized: false,
+            debug_fields: false,
+            wide_line_threshold: 5000,
+            lsp_ranges: false,
+            max_context_chars: 512,
+            max_signature_chars: 512,
+            split_strategy: SplitStrategy::Boundaries,
+            split_lookahead: 8,
+            overlap_tokens: 0,
+            at_rev: None,
+            print_paths: None,
+            audit: false,
+            path_base: PathBuf::new(),
+            tokenizer: Tokenizer::default(),
+            #[cfg(feature = "cache")]
+            cache: None,
+            min_tokens: 0,
+            max_tokens: None,
+            max_tokens_factor: 10,
+            token_filtered: Arc::default(),
+            type_filters: Vec::new(),
+        }
+    }
+}
+
+impl ChunkOptions {
+    pub fn max_tokens_for(&self, language: &str) -> usize {
+        self.language_max_tokens
+            .get(&language.to_lowercase())
+            .copied()
+            .unwrap_or(self.max_chunk_tokens)
+    }
+
+    // Everything that shapes the chunks of a file, with the version that made them.
+    // The --cache is only reused by runs with the same fingerprint
+    pub fn fingerprint(&self) -> String {
+        let language_max_tokens: BTreeMap<_, _> = self.language_max_tokens.iter().collect();
+        let tokenizer = match &self.tokenizer {
+            #[cfg(feature = "tokenize")]
+            Tokenizer::Cl100k => "cl100k",
+            Tokenizer::Estimate => "estimate",
+            Tokenizer::Custom(_) => "custom",
+        };
+        let fields = [
+            format!("version={}", env!("CARGO_PKG_VERSION")),
+            format!("max_chunk_tokens={}", self.max_chunk_tokens),
+            format!("language_max_tokens={:?}", language_max_tokens),
+            format!("id_format={:?}", self.id_format),
+            format!("id_fields={:?}", self.id_fields),
+            format!("markers={:?}", self.markers),
+            format!("emit_gaps={}", self.emit_gaps),
+            format!("parse_timeout_ms={}", self.parse_timeout_ms),
+            format!("max_comment_gap={}", self.max_comment_gap),
+            format!("context_filters={:?}", self.context_filters),
+            format!("small_file_threshold={}", self.small_file_threshold),
+            format!("emit_normalized={}", self.emit_normalized),
+            format!("debug_fields={}", self.debug_fields),
+            format!("wide_line_threshold={}", self.wide_line_threshold),
+            format!("lsp_ranges={}", self.lsp_ranges),
+            format!("max_context_chars={}", self.max_context_chars),
+            format!("max_signature_chars={}", self.max_signature_chars),
+            format!("split_strategy={:?}", self.split_strategy),
+            format!("split_lookahead={}", self.split_lookahead),
+            format!("overlap_tokens={}", self.overlap_tokens),
+            format!("at_rev={:?}", self.at_rev.as_ref().map(|rev| &rev.commit)),
+            format!("print_paths={:?}", self.print_paths),
+            format!("path_base={}", self.path_base.display()),
+            format!("audit={}", self.audit),
+            format!("tokenizer={}", tokenizer),
+            format!("min_tokens={}", self.min_tokens),
+            format!("max_tokens={:?}", self.max_tokens),
+            format!("max_tokens_factor={}", self.max_tokens_factor),
+            format!("type_filters={:?}", self.type_filters),
+        ];
+        fields.join("\n")
+    }
+
+    // Folder the paths `shown_path` gives are relative to. Absolute ones ignore it
+    pub fn path_root(&self) -> PathBuf {
+        match (self.print_paths, &self.at_rev) {
+            (Some(PrintPaths::Relative), _) => self.path_base.clone(),
+            (Some(PrintPaths::Absolute), _) => PathBuf::from("/"),
+            (None, Some(rev)) => absolute_path(&rev.repo),
+            (None, None) => absolute_path(Path::new(".")),
+        }
+    }
+
+    // How a file's path appears in chunks and messages. Without --print-paths it stays
+    // as found: under the --path argument, or relative to the repository with --at-rev
+    pub fn shown_path(&self, path: &Path) -> PathBuf {
+        let Some(style) = self.print_paths else {
+            return path.to_path_buf();
+        };
+        let full = match &self.at_rev {
+            Some(rev) => rev.repo.join(path),
+            None => path.to_path_buf(),
+        };
+        let full = absolute_path(&full);
+        match style {
+            PrintPaths::Absolute => full,
+            PrintPaths::Relative => full
+                .strip_prefix(&self.path_base)
+                .map_or_else(|_| full.clone(), Path::to_path_buf),
+        }
+    }
+}
+
+// Absolute against the working directory with `.` and `..` folded away lexically,
+// without touching the filesystem, so blob paths under --at-rev work too
+pub fn absolute_path(path: &Path) -> PathBuf {
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut folded = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                folded.pop();
+            }
+            other => folded.push(other),
+        }
+    }
+    folded
+}
+
+// One ancestor in a chunk's context, outermost first. Also used for `--context-filter kind=name`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContextPart {
+    pub kind: String,
+    pub name: String,
+}
+
+// Why a file produced no chunks. Shared by the run summary and `explain`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SkipReason {
+    NotAFile,
+    Hidden,
+    Ignored,
+    UnsupportedExtension,
+    InvalidUtf8,
+    Unchanged,
+    LanguageNotSelected,
+    Excluded,
+}
+
+impl SkipReason {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            SkipReason::NotAFile => "not a regular file",
+            SkipReason::Hidden => "hidden path",
+            SkipReason::Ignored => "matched by an ignore rule",
+            SkipReason::UnsupportedExtension => "no driver for the extension",
+            SkipReason::InvalidUtf8 => "invalid UTF-8 without --emit-gaps",
+            SkipReason::Unchanged => "unchanged since the --cache run",
+            SkipReason::LanguageNotSelected => "language not in the config's languages",
+            SkipReason::Excluded => "left out by --include or --exclude",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Marker {
+    pub kind: String,
+    pub line: usize,
+    pub text: String,
+}
+
+// Old/new line ranges of a diff hunk, as in "@@ -120,8 +120,12 @@"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HunkRange {
+    pub old_start: usize,
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
+}
+
+// Zero-based, `character` in UTF-16 code units as the Language Server Protocol expects
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LspPosition {
+    pub line: usize,
+    pub character: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LspRange {
+    pub start: LspPosition,
+    pub end: LspPosition,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ChunkData {
-    pub id: String,
-    pub file_path: String,
-    pub language: String,
-    pub chunk_type: String,
+    pub id: ChunkId,
+    // Repeated for every chunk of a file, shared instead of reallocated per chunk
+    pub file_path: Arc<str>,
+    pub language: Arc<str>,
+    pub chunk_type: Arc<str>,
     pub chunk_name: String,
-    pub context: String,
+    pub context: Arc<str>,
+    pub context_parts: Arc<[ContextPart]>,
     pub signature: String,
+    // Set when --max-context-chars / --max-signature-chars cut the field short
+    pub context_truncated: bool,
+    pub signature_truncated: bool,
     pub comment: String,
+    // Python docstring, `comment` keeps the comments above the item
+    pub docstring: Option<String>,
+    // Comment and id of the matching declaration in the paired header, only under --pair-headers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub header_comment: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub declared_in: Option<ChunkId>,
     pub code: String,
+    // sha256 of the chunk's source lines, only under --audit-chain
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+    // Hash of the code with whitespace runs collapsed, shared by reformatted copies
+    pub structural_hash: String,
+    // Only written under --emit-normalized
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code_normalized: Option<String>,
     pub start_line: usize,
     pub end_line: usize,
+    // 1-based character columns, only set for windows of wide files
+    pub start_col: Option<usize>,
+    pub end_col: Option<usize>,
+    // First line of the previous part's tail that `code` starts with, only under --overlap
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overlap_start_line: Option<usize>,
+    // Only written under --lsp-ranges
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lsp_range: Option<LspRange>,
     pub token_count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_count_normalized: Option<usize>,
+    pub max_tokens_applied: usize,
+    pub markers: Vec<Marker>,
+    pub stale: bool,
+    // Commit the file was read from under --at-rev
+    pub rev: Option<Arc<str>>,
+    // Package name of the nearest Cargo.toml, pyproject.toml or package.json
+    pub project: Option<Arc<str>>,
+    pub hunk: Option<HunkRange>,
+    pub cfg_conditions: Vec<String>,
+    pub is_extern: bool,
+    pub platform_guards: Vec<String>,
+    // Annotations written on the item, as they are in the source
+    pub attributes: Vec<String>,
+    // Parameters as written and the declared return type, for the drivers that read them.
+    // Defaulted so chunks cached before they existed still load
+    #[serde(default)]
+    pub parameters: Vec<String>,
+    pub return_type: Option<String>,
+    // Set by the drivers that read them, left out of the record otherwise
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_public: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_async: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_test: Option<bool>,
+    pub wide_file: bool,
+    // Index of the driver query pattern that captured the chunk, only under --debug-fields
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern_index: Option<usize>,
+    // Names of the items inside a whole-file chunk
+    pub members: Vec<String>,
+    // Why a `gap` record's line range could not be chunked
+    pub reason: Option<String>,
+    // Only on the tombstones --watch writes for a file removed or about to be chunked again
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deleted: bool,
 }
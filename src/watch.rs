@@ -0,0 +1,152 @@
+use crate::explain::is_ignored;
+use crate::files::{FileFilter, FileOutcome, driver_key, language_of};
+use crate::hash::compute_id;
+use crate::safety;
+use crate::types::{ChunkData, ChunkOptions, absolute_path};
+use anyhow::{Context, Result};
+use crossbeam_channel::{Receiver, select, unbounded};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Editors save in several steps, events this close together are handled as one change
+const SETTLE: Duration = Duration::from_millis(200);
+
+// Written for a removed file and before a file's new chunks, so a reader drops every
+// chunk of the path written before it
+fn tombstone(shown: &Path, language: &str, options: &ChunkOptions) -> ChunkData {
+    let file_path = shown.to_string_lossy();
+    let written_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos());
+    ChunkData {
+        id: compute_id(&format!("deleted-{}-{}", file_path, written_at), options.id_format),
+        file_path: Arc::from(file_path.as_ref()),
+        language: Arc::from(language),
+        chunk_type: Arc::from("deleted"),
+        chunk_name: shown
+            .file_name()
+            .map_or(file_path.to_string(), |name| name.to_string_lossy().into_owned()),
+        context: Arc::from("root"),
+        deleted: true,
+        ..Default::default()
+    }
+}
+
+fn write_record(out: &mut impl Write, chunk: &ChunkData) -> Result<()> {
+    writeln!(out, "{}", serde_json::to_string(chunk)?)?;
+    Ok(())
+}
+
+// Paths of an event that may have changed a file's content
+fn changed_paths(event: notify::Result<Event>, paths: &mut BTreeSet<PathBuf>) {
+    match event {
+        Ok(event) if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)) => {
+            paths.extend(event.paths);
+        }
+        Ok(_) => {}
+        Err(err) => eprintln!("WARNING: watch error: {}", err),
+    }
+}
+
+// `--watch`: after the first run, every file created or written under `root` is chunked
+// again by `process` and its chunks are appended to `output`, and every file removed
+// gets a tombstone. Runs until Ctrl-C, which flushes what was written before returning
+pub fn run_watch(
+    root: &Path,
+    output: &Path,
+    filter: &FileFilter,
+    options: &ChunkOptions,
+    mut process: impl FnMut(&Path, &crossbeam_channel::Sender<ChunkData>) -> Result<FileOutcome>,
+) -> Result<()> {
+    let (stop_tx, stop) = unbounded();
+    ctrlc::set_handler(move || {
+        let _ = stop_tx.send(());
+    })
+    .context("Failed to set the Ctrl-C handler")?;
+    let (events_tx, events) = unbounded();
+    let mut watcher = notify::recommended_watcher(move |event| {
+        let _ = events_tx.send(event);
+    })?;
+    watcher
+        .watch(root, RecursiveMode::Recursive)
+        .with_context(|| format!("Failed to watch {}", root.display()))?;
+
+    let mut out = BufWriter::new(safety::append_file("--output", output)?);
+    let absolute_root = absolute_path(root);
+    let absolute_output = absolute_path(output);
+    println!("Watching {} for changes, Ctrl-C to stop", root.display());
+    loop {
+        let mut paths = BTreeSet::new();
+        select! {
+            recv(stop) -> _ => break,
+            recv(events) -> event => match event {
+                Ok(event) => changed_paths(event, &mut paths),
+                Err(_) => break,
+            },
+        }
+        settle(&events, &mut paths);
+
+        let (mut chunked, mut removed, mut written) = (0, 0, 0);
+        for path in paths {
+            let absolute = absolute_path(&path);
+            // Paths as the first run found them, under --path as it was given
+            let Ok(relative) = absolute.strip_prefix(&absolute_root) else {
+                continue;
+            };
+            let hidden = relative
+                .components()
+                .any(|c| c.as_os_str().to_string_lossy().starts_with('.'));
+            let extension = driver_key(&absolute);
+            let Some(language) = language_of(&extension) else {
+                continue;
+            };
+            let file = root.join(relative);
+            if absolute == absolute_output || hidden || !filter.keeps(&file, root) || is_ignored(&absolute) {
+                continue;
+            }
+            let shown = options.shown_path(&file);
+            if file.is_file() {
+                let (tx, rx) = unbounded();
+                match process(&file, &tx) {
+                    Ok(FileOutcome::Chunked(_)) => {}
+                    Ok(FileOutcome::Skipped(_)) => continue,
+                    Err(err) => {
+                        eprintln!("Error processing file {}: {}", shown.display(), err);
+                        continue;
+                    }
+                }
+                drop(tx);
+                write_record(&mut out, &tombstone(&shown, language, options))?;
+                for chunk in rx {
+                    write_record(&mut out, &chunk)?;
+                    written += 1;
+                }
+                chunked += 1;
+            } else if !file.exists() {
+                write_record(&mut out, &tombstone(&shown, language, options))?;
+                removed += 1;
+            }
+        }
+        out.flush()?;
+        if chunked + removed > 0 {
+            println!(
+                "Chunked {} changed files ({} chunks appended), {} removed",
+                chunked, written, removed
+            );
+        }
+    }
+    out.flush()?;
+    println!("Stopped watching, output flushed to {}", output.display());
+    Ok(())
+}
+
+// Takes in the events that follow within SETTLE of each other
+fn settle(events: &Receiver<notify::Result<Event>>, paths: &mut BTreeSet<PathBuf>) {
+    while let Ok(event) = events.recv_timeout(SETTLE) {
+        changed_paths(event, paths);
+    }
+}
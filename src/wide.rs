@@ -0,0 +1,102 @@
+use crate::files::find_markers;
+use crate::hash::compute_chunk_id;
+use crate::types::{ChunkData, ChunkOptions};
+use std::path::Path;
+use std::sync::Arc;
+
+// Tokens repeated at the start of the next window so no token only ever appears cut off
+const WINDOW_OVERLAP_TOKENS: usize = 16;
+
+// Minified bundles and one-line data files, where line based splitting makes no sense
+pub fn is_wide(content: &str, max_line_chars: usize) -> bool {
+    max_line_chars > 0
+        && content
+            .lines()
+            .any(|line| line.len() > max_line_chars && line.chars().count() > max_line_chars)
+}
+
+// 1-based line and character column of `offset`, scanned forward from an earlier
+// known position so long files aren't rescanned from the start for every window
+fn advance(content: &str, from: (usize, usize, usize), offset: usize) -> (usize, usize, usize) {
+    let (mut line, mut col) = (from.1, from.2);
+    for c in content[from.0..offset].chars() {
+        if c == '\n' {
+            line += 1;
+            col = 1;
+        } else {
+            col += 1;
+        }
+    }
+    (offset, line, col)
+}
+
+// Cuts the file into windows of at most max_tokens tokens at token boundaries,
+// each one overlapping the previous by a few tokens
+pub fn find_window_chunks(
+    path: &Path,
+    language: &str,
+    content: &str,
+    options: &ChunkOptions,
+) -> Vec<ChunkData> {
+    let max_tokens = options.max_tokens_for(language).max(1);
+    let overlap = WINDOW_OVERLAP_TOKENS.min(max_tokens / 4);
+    let boundaries = options.tokenizer.boundaries(content);
+
+    let file_path: Arc<str> = Arc::from(path.to_string_lossy().as_ref());
+    let language: Arc<str> = Arc::from(language);
+    let chunk_type: Arc<str> = Arc::from("window");
+    let chunk_name = path.file_name().map_or_else(
+        || file_path.to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    );
+    let context: Arc<str> = Arc::from("root");
+
+    let mut chunks = Vec::new();
+    let mut first = 0;
+    let mut position = (0, 1, 1);
+    while first < boundaries.len() {
+        let (start_tokens, start) = match first {
+            0 => (0, 0),
+            _ => boundaries[first - 1],
+        };
+        // Last boundary within the token budget, at least one so the loop always advances
+        let last = boundaries
+            .partition_point(|(tokens, _)| *tokens <= start_tokens + max_tokens)
+            .saturating_sub(1)
+            .max(first);
+        let end = boundaries[last].1;
+        let text = &content[start..end];
+
+        position = advance(content, position, start);
+        let (_, start_line, start_col) = position;
+        // The last character of the window, not the position right after it
+        let last_char = text.char_indices().last().map_or(start, |(i, _)| start + i);
+        let (_, end_line, end_col) = advance(content, position, last_char);
+
+        let mut chunk = ChunkData {
+            file_path: file_path.clone(),
+            language: language.clone(),
+            chunk_type: chunk_type.clone(),
+            chunk_name: chunk_name.clone(),
+            context: context.clone(),
+            code: text.to_string(),
+            start_line,
+            end_line,
+            start_col: Some(start_col),
+            end_col: Some(end_col),
+            token_count: options.tokenizer.count(text),
+            max_tokens_applied: max_tokens,
+            markers: find_markers(text, &options.markers, |l| start_line + l),
+            wide_file: true,
+            ..Default::default()
+        };
+        chunk.id = compute_chunk_id(&chunk, chunks.len(), options);
+        chunks.push(chunk);
+
+        if last + 1 >= boundaries.len() {
+            break;
+        }
+        first = (last + 1 - overlap).max(first + 1);
+    }
+    chunks
+}
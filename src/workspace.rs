@@ -0,0 +1,94 @@
+use crate::types::absolute_path;
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use toml::Table;
+
+// Checked in this order in every folder, the first one naming a package wins
+const MANIFESTS: &[&str] = &["Cargo.toml", "pyproject.toml", "package.json"];
+
+// Package name declared by a manifest. Cargo workspace roots without a [package]
+// aren't a project of their own
+fn manifest_name(manifest: &str, text: &str) -> Option<String> {
+    let name = match manifest {
+        "package.json" => serde_json::from_str::<JsonValue>(text)
+            .ok()?
+            .get("name")?
+            .as_str()?
+            .to_string(),
+        _ => {
+            let table: Table = text.parse().ok()?;
+            let section = match manifest {
+                "Cargo.toml" => table.get("package"),
+                _ => table
+                    .get("project")
+                    .or_else(|| table.get("tool").and_then(|tool| tool.get("poetry"))),
+            };
+            section?.get("name")?.as_str()?.to_string()
+        }
+    };
+    Some(name)
+}
+
+fn project_in(dir: &Path) -> Option<Arc<str>> {
+    MANIFESTS.iter().find_map(|manifest| {
+        let text = fs::read_to_string(dir.join(manifest)).ok()?;
+        manifest_name(manifest, &text).map(Arc::from)
+    })
+}
+
+// Nearest project of every folder holding a scanned file. Manifests are read once per
+// folder, and the search stops at the repository root
+#[derive(Default)]
+pub struct Workspace {
+    // Folder --at-rev paths are relative to
+    base: Option<PathBuf>,
+    nearest: HashMap<PathBuf, Option<Arc<str>>>,
+}
+
+impl Workspace {
+    pub fn detect(files: &[PathBuf], base: Option<&Path>) -> Self {
+        let mut workspace = Workspace {
+            base: base.map(Path::to_path_buf),
+            nearest: HashMap::new(),
+        };
+        for file in files {
+            if let Some(dir) = workspace.folder_of(file) {
+                workspace.resolve(&dir);
+            }
+        }
+        workspace
+    }
+
+    fn folder_of(&self, file: &Path) -> Option<PathBuf> {
+        let full = match &self.base {
+            Some(base) => base.join(file),
+            None => file.to_path_buf(),
+        };
+        absolute_path(&full).parent().map(Path::to_path_buf)
+    }
+
+    // Nested projects resolve to the innermost one
+    fn resolve(&mut self, dir: &Path) -> Option<Arc<str>> {
+        if let Some(project) = self.nearest.get(dir) {
+            return project.clone();
+        }
+        let project = match project_in(dir) {
+            Some(project) => Some(project),
+            None if dir.join(".git").exists() => None,
+            None => dir.parent().and_then(|parent| self.resolve(parent)),
+        };
+        self.nearest.insert(dir.to_path_buf(), project.clone());
+        project
+    }
+
+    pub fn project_of(&self, file: &Path) -> Option<Arc<str>> {
+        self.nearest.get(&self.folder_of(file)?).cloned().flatten()
+    }
+
+    pub fn contains(&self, project: &str) -> bool {
+        self.nearest.values().flatten().any(|p| &**p == project)
+    }
+}
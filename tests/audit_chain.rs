@@ -0,0 +1,139 @@
+#![cfg(all(feature = "cli", feature = "lang-python"))]
+
+// `audit verify` finds the files a chain was written for from wherever it runs, and the
+// chain comes out the same on every run
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+fn workspace(name: &str) -> PathBuf {
+    let dir = common::temp_dir(name);
+    fs::create_dir_all(dir.join("proj/sub")).unwrap();
+    for (file, function) in [("a.py", "alpha"), ("b.py", "beta"), ("sub/c.py", "gamma")] {
+        let source = format!(
+            "def {}():\n    return 1\n\n\ndef {}_two():\n    return 2\n",
+            function, function
+        );
+        fs::write(dir.join("proj").join(file), source).unwrap();
+    }
+    dir
+}
+
+fn run(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_smart-code-chunker"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn write_chain(dir: &Path) {
+    let args = [
+        "--no-progress",
+        "--path",
+        "proj",
+        "--small-file-threshold",
+        "0",
+        "-o",
+        "out.jsonl",
+        "--audit-chain",
+        "chain.log",
+    ];
+    let output = run(dir, &args);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+fn verify(dir: &Path, cwd: &Path, extra: &[&str]) -> Output {
+    let (out, chain) = (dir.join("out.jsonl"), dir.join("chain.log"));
+    let mut args = vec![
+        "audit",
+        "verify",
+        "--output",
+        out.to_str().unwrap(),
+        "--chain",
+        chain.to_str().unwrap(),
+    ];
+    args.extend(extra);
+    run(cwd, &args)
+}
+
+#[test]
+fn verifies_from_another_folder() {
+    let dir = workspace("audit_elsewhere");
+    write_chain(&dir);
+    let elsewhere = common::temp_dir("audit_cwd");
+    for cwd in [&dir, &elsewhere] {
+        let output = verify(&dir, cwd, &[]);
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert!(String::from_utf8_lossy(&output.stdout).starts_with("Verified 6 records"));
+    }
+}
+
+#[test]
+fn root_replaces_the_recorded_base() {
+    let dir = workspace("audit_root");
+    write_chain(&dir);
+    let moved = common::temp_dir("audit_moved");
+    fs::rename(dir.join("proj"), moved.join("proj")).unwrap();
+    assert!(!verify(&dir, &dir, &[]).status.success());
+    let output = verify(&dir, &dir, &["--root", moved.to_str().unwrap()]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn edited_source_diverges() {
+    let dir = workspace("audit_edited");
+    write_chain(&dir);
+    fs::write(
+        dir.join("proj/b.py"),
+        "def beta():\n    return 10\n\n\ndef beta_two():\n    return 2\n",
+    )
+    .unwrap();
+    let output = verify(&dir, &dir, &[]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Chain diverges at record 3: lines 1-2 of"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn chunks_are_chained_by_file_and_line() {
+    let dir = workspace("audit_order");
+    write_chain(&dir);
+    let first = fs::read_to_string(dir.join("chain.log")).unwrap();
+    let chunks: Vec<(String, u64)> = fs::read_to_string(dir.join("out.jsonl"))
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let chunk: serde_json::Value = serde_json::from_str(line).unwrap();
+            (
+                chunk["file_path"].as_str().unwrap().to_string(),
+                chunk["start_line"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    let mut sorted = chunks.clone();
+    sorted.sort();
+    assert_eq!(chunks, sorted);
+    for _ in 0..3 {
+        write_chain(&dir);
+        assert_eq!(fs::read_to_string(dir.join("chain.log")).unwrap(), first);
+    }
+}
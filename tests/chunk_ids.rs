@@ -0,0 +1,65 @@
+#![cfg(feature = "lang-python")]
+
+// Which edits each documented --id-fields selection keeps a chunk's id through
+mod common;
+
+use smart_code_chunker::ChunkOptions;
+use smart_code_chunker::types::IdField::{self, Content, Name, Part, Path, Type};
+
+const SOURCE: &str = "def total(items):\n    return sum(items)\n";
+const EDITED: &str = "def total(items):\n    return sum(item.price for item in items)\n";
+
+// Id of the one chunk of `source` written to `file_name` in `dir`
+fn id_of(dir: &std::path::Path, file_name: &str, source: &str, id_fields: &[IdField]) -> String {
+    let path = dir.join(file_name);
+    std::fs::write(&path, source).unwrap();
+    let options = ChunkOptions {
+        small_file_threshold: 0,
+        id_fields: id_fields.to_vec(),
+        ..ChunkOptions::default()
+    };
+    let chunks = common::chunk_path(&path, &options);
+    assert_eq!(chunks.len(), 1);
+    format!("{:?}", chunks[0].id)
+}
+
+#[test]
+fn default_fields_change_with_a_rename_and_an_edit() {
+    let fields = [Content, Path, Part];
+    let dir = common::temp_dir("ids");
+    let original = id_of(&dir, "default.py", SOURCE, &fields);
+    assert_ne!(id_of(&dir, "default_moved.py", SOURCE, &fields), original);
+    assert_ne!(id_of(&dir, "default.py", EDITED, &fields), original);
+}
+
+#[test]
+fn content_and_name_survive_a_rename_not_an_edit() {
+    let fields = [Content, Name];
+    let dir = common::temp_dir("ids");
+    let original = id_of(&dir, "content_name.py", SOURCE, &fields);
+    assert_eq!(id_of(&dir, "content_name_moved.py", SOURCE, &fields), original);
+    assert_ne!(id_of(&dir, "content_name.py", EDITED, &fields), original);
+}
+
+#[test]
+fn path_name_and_type_survive_an_edit_not_a_rename() {
+    let fields = [Path, Name, Type];
+    let dir = common::temp_dir("ids");
+    let original = id_of(&dir, "path_name_type.py", SOURCE, &fields);
+    assert_eq!(id_of(&dir, "path_name_type.py", EDITED, &fields), original);
+    assert_ne!(id_of(&dir, "path_name_type_moved.py", SOURCE, &fields), original);
+}
+
+// The same function twice in one file, under two classes
+#[test]
+fn repeated_code_in_a_file_gets_distinct_ids() {
+    let source = "class A:\n    def size(self):\n        return 0\n\n\nclass B:\n    def size(self):\n        return 0\n";
+    let options = ChunkOptions {
+        small_file_threshold: 0,
+        ..ChunkOptions::default()
+    };
+    let chunks = common::chunk_source("repeated.py", source, &options);
+    let sizes: Vec<_> = chunks.iter().filter(|chunk| chunk.chunk_name == "size").collect();
+    assert_eq!(sizes.len(), 2);
+    assert_ne!(format!("{:?}", sizes[0].id), format!("{:?}", sizes[1].id));
+}
@@ -0,0 +1,216 @@
+#![cfg(all(feature = "cli", feature = "lang-python"))]
+
+// Settings that can't work together are refused before any file is read, each with
+// its own message
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+// A scratch folder holding `src/app.py`, canonical so messages with resolved paths match
+fn workspace(name: &str) -> PathBuf {
+    let dir = common::temp_dir(name);
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::write(dir.join("src/app.py"), "def main():\n    return 0\n").unwrap();
+    fs::canonicalize(dir).unwrap()
+}
+
+// First line of the error the run failed with
+fn error_of(dir: &PathBuf, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_smart-code-chunker"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(!output.status.success(), "{:?} succeeded", args);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    stderr
+        .lines()
+        .find(|line| line.starts_with("Error: ") || line.starts_with("error: "))
+        .unwrap_or_else(|| panic!("no error in {:?}", stderr))
+        .to_string()
+}
+
+#[test]
+fn path_is_required() {
+    let dir = workspace("path_is_required");
+    assert_eq!(
+        error_of(&dir, &["-o", "out.jsonl"]),
+        "Error: --path is required unless `path` is set in the config or --from-patch is given"
+    );
+}
+
+#[test]
+fn post_process_needs_jsonl() {
+    let dir = workspace("post_process_needs_jsonl");
+    assert_eq!(
+        error_of(&dir, &["--path", "src", "--format", "csv", "--post-process", "cat"]),
+        "Error: --post-process and --audit-chain need --format jsonl"
+    );
+}
+
+#[test]
+fn audit_chain_needs_jsonl() {
+    let dir = workspace("audit_chain_needs_jsonl");
+    assert_eq!(
+        error_of(&dir, &["--path", "src", "--format", "json", "--audit-chain", "chain.txt"]),
+        "Error: --post-process and --audit-chain need --format jsonl"
+    );
+}
+
+#[test]
+fn watch_needs_jsonl() {
+    let dir = workspace("watch_needs_jsonl");
+    assert_eq!(
+        error_of(&dir, &["--path", "src", "--format", "markdown", "--watch"]),
+        "Error: --watch needs --format jsonl"
+    );
+}
+
+#[test]
+fn watch_needs_one_path() {
+    let dir = workspace("watch_needs_one_path");
+    assert_eq!(
+        error_of(&dir, &["--path", "src", ".", "--watch"]),
+        "Error: --watch watches one folder, give a single --path"
+    );
+}
+
+#[test]
+fn at_rev_needs_one_path() {
+    let dir = workspace("at_rev_needs_one_path");
+    assert_eq!(
+        error_of(&dir, &["--path", "src", ".", "--at-rev", "HEAD"]),
+        "Error: --at-rev reads one repository, give a single --path"
+    );
+}
+
+#[test]
+fn at_rev_needs_a_path() {
+    let dir = workspace("at_rev_needs_a_path");
+    assert_eq!(
+        error_of(&dir, &["--from-patch", "--at-rev", "HEAD"]),
+        "Error: --at-rev needs --path to the repository"
+    );
+}
+
+#[test]
+fn id_fields_part_needs_content() {
+    let dir = workspace("id_fields_part_needs_content");
+    assert_eq!(
+        error_of(&dir, &["--path", "src", "--id-fields", "path,part"]),
+        "Error: --id-fields part only tells apart the pieces of a split chunk, use it together with content"
+    );
+}
+
+#[test]
+fn token_limit_language_must_exist() {
+    let dir = workspace("token_limit_language_must_exist");
+    let error = error_of(&dir, &["--path", "src", "-m", "cobol=512"]);
+    assert!(
+        error.starts_with("Error: Unknown language 'cobol' in --max-chunk-tokens, valid names: "),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn offline_refuses_post_process() {
+    let dir = workspace("offline_refuses_post_process");
+    assert_eq!(
+        error_of(&dir, &["--path", "src", "--offline", "--post-process", "embed --remote"]),
+        "Error: --offline: --post-process runs `embed --remote`, which may reach the network"
+    );
+}
+
+#[test]
+fn read_only_refuses_output_inside_path() {
+    let dir = workspace("read_only_refuses_output_inside_path");
+    assert_eq!(
+        error_of(&dir, &["--path", ".", "--assert-read-only", "-o", "out.jsonl"]),
+        format!(
+            "Error: --assert-read-only: --output path {} is inside the scanned root {}",
+            dir.join("out.jsonl").display(),
+            dir.display()
+        )
+    );
+}
+
+#[test]
+fn read_only_refuses_coverage_report_inside_path() {
+    let dir = workspace("read_only_refuses_coverage_report_inside_path");
+    let args = ["--path", "src", "--assert-read-only", "--coverage-report", "src/coverage.csv"];
+    assert_eq!(
+        error_of(&dir, &args),
+        format!(
+            "Error: --assert-read-only: --coverage-report path {} is inside the scanned root {}",
+            dir.join("src/coverage.csv").display(),
+            dir.join("src").display()
+        )
+    );
+}
+
+#[test]
+fn read_only_refuses_audit_chain_inside_path() {
+    let dir = workspace("read_only_refuses_audit_chain_inside_path");
+    let args = ["--path", "src", "--assert-read-only", "--audit-chain", "src/chain.txt"];
+    assert_eq!(
+        error_of(&dir, &args),
+        format!(
+            "Error: --assert-read-only: --audit-chain path {} is inside the scanned root {}",
+            dir.join("src/chain.txt").display(),
+            dir.join("src").display()
+        )
+    );
+}
+
+#[test]
+fn read_only_refuses_cache_inside_path() {
+    let dir = workspace("read_only_refuses_cache_inside_path");
+    let args = ["--path", "src", "--assert-read-only", "--cache", "src/cache.db"];
+    assert_eq!(
+        error_of(&dir, &args),
+        format!(
+            "Error: --assert-read-only: --cache path {} is inside the scanned root {}",
+            dir.join("src/cache.db").display(),
+            dir.join("src").display()
+        )
+    );
+}
+
+#[test]
+fn dry_run_conflicts_with_post_process() {
+    let dir = workspace("dry_run_conflicts_with_post_process");
+    assert_eq!(
+        error_of(&dir, &["--path", "src", "--dry-run", "--post-process", "cat"]),
+        "error: the argument '--dry-run' cannot be used with '--post-process <POST_PROCESS>'"
+    );
+}
+
+#[test]
+fn list_types_conflicts_with_watch() {
+    let dir = workspace("list_types_conflicts_with_watch");
+    assert_eq!(
+        error_of(&dir, &["--path", "src", "--list-types", "--watch"]),
+        "error: the argument '--list-types' cannot be used with '--watch'"
+    );
+}
+
+#[test]
+fn watch_conflicts_with_at_rev() {
+    let dir = workspace("watch_conflicts_with_at_rev");
+    assert_eq!(
+        error_of(&dir, &["--path", "src", "--watch", "--at-rev", "HEAD"]),
+        "error: the argument '--watch' cannot be used with '--at-rev <AT_REV>'"
+    );
+}
+
+#[test]
+fn cache_replay_needs_cache() {
+    let dir = workspace("cache_replay_needs_cache");
+    assert_eq!(
+        error_of(&dir, &["--path", "src", "--cache-replay"]),
+        "error: the following required arguments were not provided:"
+    );
+}
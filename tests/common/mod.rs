@@ -0,0 +1,39 @@
+// Helpers shared by the integration tests. Every test file builds its own copy of this
+// module and uses only some of it
+#![allow(dead_code)]
+
+use smart_code_chunker::{ChunkData, ChunkOptions, process_file};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// An empty folder of its own for every call, so tests running at the same time, in
+// this run or another, never share files
+pub fn temp_dir(name: &str) -> PathBuf {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let unique = format!(
+        "smart-code-chunker-{}-{}-{}",
+        name,
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    );
+    let dir = std::env::temp_dir().join(unique);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+// Chunks of the file at `path`, as the workers send them
+pub fn chunk_path(path: &Path, options: &ChunkOptions) -> Vec<ChunkData> {
+    let (tx, rx) = crossbeam_channel::unbounded();
+    process_file(path, None, &mut tree_sitter::Parser::new(), &tx, options).unwrap();
+    drop(tx);
+    rx.iter().collect()
+}
+
+// Chunks of `source` written to a file named `file_name` in a fresh folder
+pub fn chunk_source(file_name: &str, source: &str, options: &ChunkOptions) -> Vec<ChunkData> {
+    let path = temp_dir("source").join(file_name);
+    fs::write(&path, source).unwrap();
+    chunk_path(&path, options)
+}
@@ -0,0 +1,13 @@
+use smart_code_chunker::conformance::{fixture_options, run_conformance};
+use std::path::Path;
+
+// Every fixture under conformance/ against its expected chunks, the same check as
+// `smart-code-chunker conformance conformance`. Fixtures of drivers left out of the
+// build are skipped
+#[test]
+fn fixtures_match_expected_chunks() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("conformance");
+    if let Err(err) = run_conformance(&dir, false, &fixture_options()) {
+        panic!("{:#}", err);
+    }
+}
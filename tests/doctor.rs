@@ -0,0 +1,51 @@
+#![cfg(all(feature = "cli", feature = "lang-c"))]
+
+// `doctor` reports the query patterns that never matched, not the node kinds that were
+// never captured: parents like translation_unit only ever appear inside a pattern
+mod common;
+
+use std::fs;
+use std::process::Command;
+
+fn unmatched(sources: &[(&str, &str)]) -> Vec<String> {
+    let dir = common::temp_dir("doctor");
+    for (name, source) in sources {
+        fs::write(dir.join(name), source).unwrap();
+    }
+    let output = Command::new(env!("CARGO_BIN_EXE_smart-code-chunker"))
+        .args(["doctor", dir.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .skip_while(|line| !line.starts_with("Query patterns that matched zero times"))
+        .skip(1)
+        .map(|line| line.trim().to_string())
+        .collect()
+}
+
+#[test]
+fn unmatched_pattern_is_printed_whole() {
+    let report = unmatched(&[("main.c", "int main(void) {\n    return 0;\n}\n")]);
+    assert_eq!(report[0], "C (1 files):");
+    assert!(
+        report[1].starts_with("[ (translation_unit (declaration) @chunk)"),
+        "{:?}",
+        report
+    );
+    assert_eq!(report.len(), 2);
+}
+
+#[test]
+fn patterns_matched_through_a_parent_are_not_reported() {
+    let report = unmatched(&[(
+        "main.c",
+        "int counter;\n\nint main(void) {\n    return counter;\n}\n",
+    )]);
+    assert_eq!(report, ["C (1 files): none"]);
+}
@@ -0,0 +1,77 @@
+#![cfg(all(feature = "cli", feature = "lang-python", feature = "lang-rust"))]
+
+// Files the config's languages or the include and exclude globs leave out are counted
+// in the summary, and `explain` names the same reason
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn workspace(name: &str) -> PathBuf {
+    let dir = common::temp_dir(name);
+    fs::create_dir_all(dir.join("src/vendor")).unwrap();
+    fs::write(dir.join("src/app.py"), "def main():\n    return 0\n").unwrap();
+    fs::write(dir.join("src/vendor/lib.py"), "def lib():\n    return 0\n").unwrap();
+    fs::write(dir.join("src/lib.rs"), "fn lib() {}\n").unwrap();
+    fs::write(
+        dir.join("chunker.toml"),
+        "path = \"src\"\nlanguages = [\"python\"]\nexclude_patterns = [\"vendor/**\"]\n",
+    )
+    .unwrap();
+    dir
+}
+
+fn stdout_of(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_smart-code-chunker"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn summary_counts_filtered_files() {
+    let dir = workspace("filtered_summary");
+    let stdout = stdout_of(&dir, &["--no-progress", "-o", "out.jsonl"]);
+    assert!(
+        stdout.contains("Skipped (language not in the config's languages): 1 files"),
+        "{}",
+        stdout
+    );
+    assert!(
+        stdout.contains("Skipped (left out by --include or --exclude): 1 files"),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn explain_applies_the_same_filter() {
+    let dir = workspace("filtered_explain");
+    let verdict = |file: &str| {
+        stdout_of(&dir, &["explain", file])
+            .lines()
+            .last()
+            .unwrap()
+            .to_string()
+    };
+    assert_eq!(
+        verdict("src/lib.rs"),
+        "Verdict: skipped, language not in the config's languages (its language is Rust)"
+    );
+    assert_eq!(
+        verdict("src/vendor/lib.py"),
+        "Verdict: skipped, left out by --include or --exclude (globs matched against vendor/lib.py)"
+    );
+    assert_eq!(
+        verdict("src/app.py"),
+        "Verdict: processed with the Python driver"
+    );
+}
@@ -0,0 +1,66 @@
+#![cfg(all(feature = "cli", feature = "lang-python"))]
+
+// The same file reached through several --path forms is chunked once, under the
+// form it was first found by
+mod common;
+
+use smart_code_chunker::files::dedupe_files;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+fn workspace(name: &str) -> PathBuf {
+    let dir = common::temp_dir(name);
+    fs::create_dir_all(dir.join("src/sub")).unwrap();
+    fs::write(dir.join("src/app.py"), "def main():\n    return 0\n").unwrap();
+    fs::canonicalize(dir).unwrap()
+}
+
+// file_path of every chunk a run over `paths` writes
+fn chunked_paths(dir: &PathBuf, paths: &[&str]) -> Vec<String> {
+    let status = Command::new(env!("CARGO_BIN_EXE_smart-code-chunker"))
+        .current_dir(dir)
+        .args(["--no-progress", "-o", "out.jsonl", "--path"])
+        .args(paths)
+        .output()
+        .unwrap()
+        .status;
+    assert!(status.success());
+    fs::read_to_string(dir.join("out.jsonl"))
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let chunk: serde_json::Value = serde_json::from_str(line).unwrap();
+            chunk["file_path"].as_str().unwrap().to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn relative_and_absolute_forms_are_one_file() {
+    let dir = workspace("relative_and_absolute");
+    let absolute = dir.join("src/app.py");
+    assert_eq!(chunked_paths(&dir, &["src", absolute.to_str().unwrap()]), ["src/app.py"]);
+    assert_eq!(
+        chunked_paths(&dir, &[absolute.to_str().unwrap(), "src"]),
+        [absolute.to_str().unwrap()]
+    );
+}
+
+#[test]
+fn trailing_slash_is_the_same_folder() {
+    let dir = workspace("trailing_slash");
+    assert_eq!(chunked_paths(&dir, &["src", "src/"]), ["src/app.py"]);
+    assert_eq!(chunked_paths(&dir, &["src/", "src"]), ["src/app.py"]);
+}
+
+#[test]
+fn dot_segments_are_resolved() {
+    let dir = workspace("dot_segments");
+    let files = vec![
+        dir.join("src/app.py"),
+        dir.join("src/./app.py"),
+        dir.join("src/sub/../app.py"),
+    ];
+    assert_eq!(dedupe_files(files), [dir.join("src/app.py")]);
+}
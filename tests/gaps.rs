@@ -0,0 +1,69 @@
+#![cfg(feature = "lang-rust")]
+
+// Gap records only hold the lines no chunk covers, whether the gap comes from a syntax
+// error or from invalid UTF-8
+mod common;
+
+use smart_code_chunker::ChunkOptions;
+use std::fs;
+
+// (chunk_type, start_line, end_line) of every chunk of `source`, gaps in their own order
+fn ranges(name: &str, source: &[u8]) -> Vec<(String, usize, usize)> {
+    let path = common::temp_dir(name).join("lib.rs");
+    fs::write(&path, source).unwrap();
+    let options = ChunkOptions {
+        small_file_threshold: 0,
+        emit_gaps: true,
+        ..ChunkOptions::default()
+    };
+    common::chunk_path(&path, &options)
+        .into_iter()
+        .map(|chunk| {
+            (
+                chunk.chunk_type.to_string(),
+                chunk.start_line,
+                chunk.end_line,
+            )
+        })
+        .collect()
+}
+
+fn range(chunk_type: &str, start_line: usize, end_line: usize) -> (String, usize, usize) {
+    (chunk_type.to_string(), start_line, end_line)
+}
+
+#[test]
+fn syntax_error_inside_a_chunked_item_leaves_no_gap() {
+    let source = b"fn a() {\n    let x = ;\n    x\n}\n\nfn b() {}\n";
+    assert_eq!(
+        ranges("gap_inside", source),
+        [range("function_item", 1, 4), range("function_item", 6, 6)]
+    );
+}
+
+#[test]
+fn syntax_error_between_items_is_a_gap() {
+    let source = b"fn a() {}\n\n)))) ((( garbage\nmore garbage ]]\n\nfn b() {}\n";
+    assert_eq!(
+        ranges("gap_between", source),
+        [
+            range("gap", 3, 4),
+            range("function_item", 1, 1),
+            range("function_item", 6, 6)
+        ]
+    );
+}
+
+// Invalid bytes on three lines in a row, the middle one inside a function
+#[test]
+fn gap_around_an_item_is_split() {
+    let source = b"use std::fmt; // \xff\nfn a() { let _ = \"\xfe\"; }\nuse std::io; // \xff\n";
+    assert_eq!(
+        ranges("gap_split", source),
+        [
+            range("function_item", 2, 2),
+            range("gap", 1, 1),
+            range("gap", 3, 3)
+        ]
+    );
+}
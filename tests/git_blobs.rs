@@ -0,0 +1,69 @@
+// Blobs of a --at-rev run come from one `git cat-file --batch`, which has to stay in step
+// across missing files, folders and names with spaces
+mod common;
+
+use smart_code_chunker::git::{BlobReader, resolve_commit};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn git(repo: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+// A repository with one commit, and a working tree that has moved on since
+fn repo(name: &str) -> (PathBuf, String) {
+    let dir = common::temp_dir(name);
+    git(&dir, &["init", "-q"]);
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::write(dir.join("src/app.py"), "committed\n").unwrap();
+    fs::write(dir.join("src/two words.py"), "spaced\n").unwrap();
+    fs::write(dir.join("empty.txt"), "").unwrap();
+    git(&dir, &["add", "-A"]);
+    git(&dir, &["commit", "-q", "-m", "first"]);
+    fs::write(dir.join("src/app.py"), "edited\n").unwrap();
+    let commit = resolve_commit(&dir, "HEAD").unwrap();
+    (dir, commit)
+}
+
+#[test]
+fn reads_committed_contents() {
+    let (dir, commit) = repo("blobs_committed");
+    let blobs = BlobReader::new(&dir);
+    assert_eq!(
+        blobs.read(&commit, Path::new("src/app.py")).unwrap(),
+        b"committed\n"
+    );
+    assert_eq!(
+        blobs.read(&commit, Path::new("src/two words.py")).unwrap(),
+        b"spaced\n"
+    );
+    assert_eq!(blobs.read(&commit, Path::new("empty.txt")).unwrap(), b"");
+    assert_eq!(
+        blobs.read(&commit, Path::new("src/app.py")).unwrap(),
+        b"committed\n"
+    );
+}
+
+#[test]
+fn stays_in_step_after_missing_files_and_folders() {
+    let (dir, commit) = repo("blobs_errors");
+    let blobs = BlobReader::new(&dir);
+    let missing = blobs
+        .read(&commit, Path::new("src/gone file.py"))
+        .unwrap_err();
+    assert!(missing.to_string().contains("missing"), "{}", missing);
+    let folder = blobs.read(&commit, Path::new("src")).unwrap_err();
+    assert!(folder.to_string().contains("is a tree"), "{}", folder);
+    assert_eq!(
+        blobs.read(&commit, Path::new("src/app.py")).unwrap(),
+        b"committed\n"
+    );
+}
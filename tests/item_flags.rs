@@ -0,0 +1,120 @@
+#![cfg(any(feature = "lang-rust", feature = "lang-python"))]
+
+// is_public, is_async and is_test as the Rust and Python drivers read them, and left out
+// of the records of drivers that don't
+mod common;
+
+use smart_code_chunker::{ChunkData, ChunkOptions};
+
+// A chunk's name with its is_public, is_async and is_test
+type Flags = (String, Option<bool>, Option<bool>, Option<bool>);
+
+fn flags(file_name: &str, source: &str) -> Vec<Flags> {
+    let options = ChunkOptions {
+        small_file_threshold: 0,
+        ..ChunkOptions::default()
+    };
+    let chunks: Vec<ChunkData> = common::chunk_source(file_name, source, &options);
+    chunks
+        .into_iter()
+        .map(|chunk| {
+            (
+                chunk.chunk_name,
+                chunk.is_public,
+                chunk.is_async,
+                chunk.is_test,
+            )
+        })
+        .collect()
+}
+
+fn row(name: &str, public: bool, asynchronous: bool, test: bool) -> Flags {
+    (
+        name.to_string(),
+        Some(public),
+        Some(asynchronous),
+        Some(test),
+    )
+}
+
+#[cfg(feature = "lang-rust")]
+#[test]
+fn rust_visibility_modifiers_and_test_attributes() {
+    let source = r#"pub fn open() {}
+
+pub(crate) fn helper() {}
+
+async unsafe fn raw() {}
+
+#[tokio::test]
+async fn fetches() {}
+
+#[test]
+fn adds() {}
+"#;
+    assert_eq!(
+        flags("flags.rs", source),
+        [
+            row("open", true, false, false),
+            row("helper", false, false, false),
+            row("raw", false, true, false),
+            row("fetches", false, true, true),
+            row("adds", false, false, true),
+        ]
+    );
+}
+
+#[cfg(feature = "lang-python")]
+#[test]
+fn python_underscores_and_pytest() {
+    let source = r#"def _private():
+    pass
+
+
+class Box:
+    def __init__(self):
+        pass
+
+    def __secret(self):
+        pass
+
+
+@pytest.mark.slow
+def checks_everything():
+    pass
+
+
+def test_adds():
+    pass
+
+
+async def fetch():
+    pass
+"#;
+    assert_eq!(
+        flags("flags.py", source),
+        [
+            row("_private", false, false, false),
+            row("Box", true, false, false),
+            row("__init__", true, false, false),
+            row("__secret", false, false, false),
+            row("checks_everything", true, false, true),
+            row("test_adds", true, false, true),
+            row("fetch", true, true, false),
+        ]
+    );
+}
+
+#[cfg(feature = "lang-go")]
+#[test]
+fn other_drivers_leave_the_fields_out() {
+    let options = ChunkOptions {
+        small_file_threshold: 0,
+        ..ChunkOptions::default()
+    };
+    let chunks = common::chunk_source("main.go", "package main\n\nfunc Run() {}\n", &options);
+    let record = serde_json::to_value(&chunks[0]).unwrap();
+    for field in ["is_public", "is_async", "is_test"] {
+        assert!(record.get(field).is_none(), "{} in {}", field, record);
+    }
+}
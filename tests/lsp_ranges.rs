@@ -0,0 +1,44 @@
+// --lsp-ranges counts characters in UTF-16 code units: CJK characters take one, while
+// emoji outside the Basic Multilingual Plane take a surrogate pair
+mod common;
+
+use smart_code_chunker::lsp::LineIndex;
+
+#[test]
+fn characters_are_utf16_code_units() {
+    let content = "let a = \"中文\";\nlet b = \"😀\"; end\n";
+    let index = LineIndex::new(content);
+    let after_cjk = content.find("\";").unwrap();
+    let position = index.position(after_cjk);
+    assert_eq!((position.line, position.character), (0, 11));
+
+    let end = content.find("end").unwrap();
+    let position = index.position(end);
+    assert_eq!((position.line, position.character), (1, 14));
+}
+
+#[test]
+fn line_end_stops_before_crlf() {
+    let content = "a 👋🏽\r\nb\r\n";
+    let index = LineIndex::new(content);
+    let position = index.position(index.line_end(0));
+    // `a`, a space, then two surrogate pairs
+    assert_eq!((position.line, position.character), (0, 6));
+    assert_eq!(index.line_start(1), content.find('b').unwrap());
+}
+
+#[cfg(feature = "lang-rust")]
+#[test]
+fn chunk_ranges_on_multibyte_boundary_lines() {
+    use smart_code_chunker::ChunkOptions;
+
+    let source = "const WAVE: &str = \"👋\"; fn greet() -> &'static str {\n    \"你好 🌏\" }\n";
+    let options = ChunkOptions { small_file_threshold: 0, lsp_ranges: true, ..ChunkOptions::default() };
+    let chunks = common::chunk_source("greet.rs", source, &options);
+    let greet = chunks.iter().find(|chunk| chunk.chunk_name == "greet").unwrap();
+    let range = greet.lsp_range.as_ref().unwrap();
+    // `const WAVE: &str = "` is 20 units, the emoji 2 and `"; ` 3 more
+    assert_eq!((range.start.line, range.start.character), (0, 25));
+    // Four spaces, a quote, two CJK characters, a space, a surrogate pair, ` }`
+    assert_eq!((range.end.line, range.end.character), (1, 13));
+}
@@ -0,0 +1,42 @@
+#![cfg(any(feature = "lang-rust", feature = "lang-perl"))]
+
+// Markers in a comment block are reported on their own line of the file, also when
+// blank lines or attributes sit between the comments
+mod common;
+
+use smart_code_chunker::{ChunkData, ChunkOptions};
+
+fn marker_lines(file_name: &str, source: &str) -> Vec<(String, usize)> {
+    let options = ChunkOptions {
+        small_file_threshold: 0,
+        ..ChunkOptions::default()
+    };
+    let chunks: Vec<ChunkData> = common::chunk_source(file_name, source, &options);
+    assert_eq!(chunks.len(), 1);
+    chunks[0]
+        .markers
+        .iter()
+        .map(|marker| (marker.kind.clone(), marker.line))
+        .collect()
+}
+
+#[cfg(feature = "lang-rust")]
+#[test]
+fn comments_split_by_a_blank_line_and_an_attribute() {
+    // Below a `use`, a block at the top of the file would be its header
+    let source = "use std::fmt;\n// TODO: first\n\n#[inline]\n// FIXME: second\nfn f() {}\n";
+    assert_eq!(
+        marker_lines("split.rs", source),
+        [("TODO".to_string(), 2), ("FIXME".to_string(), 5)]
+    );
+}
+
+#[cfg(feature = "lang-perl")]
+#[test]
+fn scanned_comments_split_by_a_blank_line() {
+    let source = "# TODO: first\n\n# FIXME: second\nsub f {\n    return 1;\n}\n";
+    assert_eq!(
+        marker_lines("split.pl", source),
+        [("TODO".to_string(), 1), ("FIXME".to_string(), 3)]
+    );
+}
@@ -0,0 +1,80 @@
+#![cfg(all(feature = "cli", feature = "lang-python"))]
+
+// Every run writes how it made its ids to `<output>.meta.json`, and lint, diff and stats
+// read it back
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+fn chunker(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_smart-code-chunker"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn chunked(name: &str, outputs: &[(&str, &str)]) -> PathBuf {
+    let dir = common::temp_dir(name);
+    fs::write(dir.join("app.py"), "def main():\n    return 0\n").unwrap();
+    for (output, id_format) in outputs {
+        let args = [
+            "--no-progress",
+            "--path",
+            "app.py",
+            "-o",
+            output,
+            "--id-format",
+            id_format,
+        ];
+        assert!(chunker(&dir, &args).status.success());
+    }
+    dir
+}
+
+#[test]
+fn meta_file_records_the_id_scheme() {
+    let dir = chunked("meta_written", &[("out.jsonl", "uuid")]);
+    let meta: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.join("out.jsonl.meta.json")).unwrap())
+            .unwrap();
+    assert_eq!(meta["id_format"], "uuid");
+    assert_eq!(
+        meta["id_fields"],
+        serde_json::json!(["content", "path", "part"])
+    );
+
+    let stats = chunker(&dir, &["stats", "out.jsonl"]);
+    assert!(String::from_utf8_lossy(&stats.stdout).contains("Ids: uuid of content, path, part"));
+}
+
+#[test]
+fn lint_checks_ids_against_the_meta_file() {
+    let dir = chunked("meta_lint", &[("out.jsonl", "hex")]);
+    assert!(chunker(&dir, &["lint", "out.jsonl"]).status.success());
+
+    let meta = dir.join("out.jsonl.meta.json");
+    let text = fs::read_to_string(&meta)
+        .unwrap()
+        .replace("\"hex\"", "\"int64\"");
+    fs::write(&meta, text).unwrap();
+    let lint = chunker(&dir, &["lint", "out.jsonl"]);
+    assert!(!lint.status.success());
+    assert!(
+        String::from_utf8_lossy(&lint.stdout)
+            .contains("id not in the int64 format of the meta file: 1")
+    );
+}
+
+#[test]
+fn diff_warns_when_id_schemes_differ() {
+    let dir = chunked("meta_diff", &[("old.jsonl", "hex"), ("new.jsonl", "uuid")]);
+    let diff = chunker(&dir, &["diff", "old.jsonl", "new.jsonl"]);
+    assert!(String::from_utf8_lossy(&diff.stderr).contains("they can't match"));
+
+    let same = chunked("meta_same", &[("old.jsonl", "hex"), ("new.jsonl", "hex")]);
+    let diff = chunker(&same, &["diff", "old.jsonl", "new.jsonl"]);
+    assert!(!String::from_utf8_lossy(&diff.stderr).contains("WARNING"));
+}
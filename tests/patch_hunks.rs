@@ -0,0 +1,137 @@
+// A chunk per hunk of a unified diff, named and placed by the file it changes, including
+// patches that touch several files, rename one or delete one
+use smart_code_chunker::ChunkOptions;
+use smart_code_chunker::patch::find_hunk_chunks;
+
+// (name, context, start_line, end_line) of every chunk
+fn hunks(patch: &str) -> Vec<(String, String, usize, usize)> {
+    find_hunk_chunks("change.patch", patch, &ChunkOptions::default())
+        .into_iter()
+        .map(|chunk| {
+            (
+                chunk.chunk_name,
+                chunk.context.to_string(),
+                chunk.start_line,
+                chunk.end_line,
+            )
+        })
+        .collect()
+}
+
+fn hunk(
+    name: &str,
+    file: &str,
+    start_line: usize,
+    end_line: usize,
+) -> (String, String, usize, usize) {
+    (name.to_string(), file.to_string(), start_line, end_line)
+}
+
+#[test]
+fn every_hunk_of_a_file_is_a_chunk() {
+    let patch = "\
+diff --git a/src/lib.rs b/src/lib.rs
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,3 @@ pub fn first() {
+ pub fn first() {
+-    1
++    2
+ }
+@@ -20,2 +20,3 @@ pub fn second() {
+ pub fn second() {
++    todo!()
+ }
+";
+    assert_eq!(
+        hunks(patch),
+        [
+            hunk("src/lib.rs@@-1,3 +1,3", "src/lib.rs", 5, 8),
+            hunk("src/lib.rs@@-20,2 +20,3", "src/lib.rs", 10, 12),
+        ]
+    );
+
+    let chunks = find_hunk_chunks("change.patch", patch, &ChunkOptions::default());
+    assert_eq!(chunks[1].signature, "@@ -20,2 +20,3 @@ pub fn second() {");
+    assert_eq!(chunks[1].code, " pub fn second() {\n+    todo!()\n }");
+    let range = chunks[1].hunk.as_ref().unwrap();
+    assert_eq!(
+        (
+            range.old_start,
+            range.old_lines,
+            range.new_start,
+            range.new_lines
+        ),
+        (20, 2, 20, 3)
+    );
+}
+
+// A rename with changes is under the new name, a pure rename has no hunk
+#[test]
+fn renamed_files_go_by_their_new_name() {
+    let patch = "\
+diff --git a/old_name.py b/new_name.py
+similarity index 90%
+rename from old_name.py
+rename to new_name.py
+index 83db48f..bf269f4 100644
+--- a/old_name.py
++++ b/new_name.py
+@@ -1,2 +1,2 @@
+ def main():
+-    return 1
++    return 2
+diff --git a/moved.py b/folder/moved.py
+similarity index 100%
+rename from moved.py
+rename to folder/moved.py
+";
+    assert_eq!(
+        hunks(patch),
+        [hunk("new_name.py@@-1,2 +1,2", "new_name.py", 9, 11)]
+    );
+}
+
+// A deleted file only has its old path, an added one only its new path
+#[test]
+fn deleted_and_added_files_keep_their_real_path() {
+    let patch = "\
+diff --git a/gone.rs b/gone.rs
+deleted file mode 100644
+--- a/gone.rs
++++ /dev/null
+@@ -1,2 +0,0 @@
+-fn gone() {}
+-
+diff --git a/added.rs b/added.rs
+new file mode 100644
+--- /dev/null
++++ b/added.rs
+@@ -0,0 +1 @@
++fn added() {}
+\\ No newline at end of file
+";
+    assert_eq!(
+        hunks(patch),
+        [
+            hunk("gone.rs@@-1,2 +0,0", "gone.rs", 6, 7),
+            hunk("added.rs@@-0,0 +1,1", "added.rs", 13, 14),
+        ]
+    );
+}
+
+// A removed line that looks like a file header stays in its hunk
+#[test]
+fn removed_lines_starting_with_dashes_stay_in_the_hunk() {
+    let patch = "\
+--- a/query.sql
++++ b/query.sql
+@@ -1,2 +1,1 @@
+--- a comment
+ SELECT 1;
+";
+    assert_eq!(
+        hunks(patch),
+        [hunk("query.sql@@-1,2 +1,1", "query.sql", 4, 5)]
+    );
+}
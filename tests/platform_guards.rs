@@ -0,0 +1,62 @@
+#![cfg(feature = "lang-python")]
+
+mod common;
+
+use smart_code_chunker::{ChunkData, ChunkOptions};
+
+fn chunk_source(name: &str, source: &str) -> Vec<ChunkData> {
+    let options = ChunkOptions { small_file_threshold: 0, ..ChunkOptions::default() };
+    common::chunk_source(name, source, &options)
+}
+
+fn guards_of<'a>(chunks: &'a [ChunkData], name: &str) -> &'a [String] {
+    let chunk = chunks.iter().find(|chunk| chunk.chunk_name == name).unwrap();
+    &chunk.platform_guards
+}
+
+#[test]
+fn elif_and_else_negate_earlier_branches() {
+    let chunks = chunk_source(
+        "branches.py",
+        r#"import sys
+
+if sys.platform == "win32":
+    def windows():
+        return 1
+elif sys.platform == "darwin":
+    def mac():
+        return 2
+else:
+    def other():
+        return 3
+"#,
+    );
+    assert_eq!(guards_of(&chunks, "windows"), [r#"sys.platform == "win32""#]);
+    assert_eq!(
+        guards_of(&chunks, "mac"),
+        [r#"not (sys.platform == "win32")"#, r#"sys.platform == "darwin""#]
+    );
+    assert_eq!(
+        guards_of(&chunks, "other"),
+        [r#"not (sys.platform == "win32")"#, r#"not (sys.platform == "darwin")"#]
+    );
+}
+
+#[test]
+fn conditions_without_a_platform_are_left_out() {
+    let chunks = chunk_source(
+        "mixed.py",
+        r#"import os
+import sys
+
+if DEBUG:
+    pass
+elif os.name == "nt":
+    pass
+else:
+    def fallback():
+        return 0
+"#,
+    );
+    assert_eq!(guards_of(&chunks, "fallback"), [r#"not (os.name == "nt")"#]);
+}
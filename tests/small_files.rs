@@ -0,0 +1,126 @@
+#![cfg(feature = "lang-python")]
+
+// --small-file-threshold turns a small file into one `file` chunk naming its items, as
+// long as the file also fits the chunk limit. --min-tokens then judges that chunk like
+// any other
+mod common;
+
+use smart_code_chunker::{ChunkData, ChunkOptions};
+use std::sync::atomic::Ordering;
+
+const SOURCE: &str = r#"import os
+
+
+def load(path):
+    return open(path).read()
+
+
+class Store:
+    def get(self, key):
+        return os.environ.get(key)
+
+
+def save(path, text):
+    with open(path, "w") as f:
+        f.write(text)
+"#;
+
+fn tokens() -> usize {
+    ChunkOptions::default().tokenizer.count(SOURCE)
+}
+
+fn chunk(options: &ChunkOptions) -> Vec<ChunkData> {
+    common::chunk_source("store.py", SOURCE, options)
+}
+
+fn types(chunks: &[ChunkData]) -> Vec<&str> {
+    chunks.iter().map(|chunk| &*chunk.chunk_type).collect()
+}
+
+#[test]
+fn file_chunk_names_its_members() {
+    let options = ChunkOptions {
+        small_file_threshold: tokens() + 1,
+        ..ChunkOptions::default()
+    };
+    let chunks = chunk(&options);
+    assert_eq!(types(&chunks), ["file"]);
+    let file = &chunks[0];
+    assert_eq!(file.chunk_name, "store.py");
+    assert_eq!(file.code, SOURCE);
+    assert_eq!((file.start_line, file.end_line), (1, 15));
+    assert_eq!(file.token_count, tokens());
+    assert_eq!(file.members, ["load", "Store", "get", "save"]);
+}
+
+// The threshold is exclusive, a file of exactly that many tokens is chunked per item
+#[test]
+fn file_at_the_threshold_is_chunked_per_item() {
+    let options = ChunkOptions {
+        small_file_threshold: tokens(),
+        ..ChunkOptions::default()
+    };
+    let chunks = chunk(&options);
+    let names: Vec<&str> = chunks
+        .iter()
+        .map(|chunk| chunk.chunk_name.as_str())
+        .collect();
+    assert_eq!(names, ["load", "Store", "get", "save"]);
+    assert!(chunks.iter().all(|chunk| chunk.members.is_empty()));
+}
+
+#[test]
+fn zero_threshold_never_makes_a_file_chunk() {
+    let options = ChunkOptions {
+        small_file_threshold: 0,
+        ..ChunkOptions::default()
+    };
+    assert!(!types(&chunk(&options)).contains(&"file"));
+}
+
+// A file fitting the chunk limit exactly is still one chunk, one token over is not
+#[test]
+fn chunk_limit_takes_precedence() {
+    let at_limit = ChunkOptions {
+        small_file_threshold: 10_000,
+        max_chunk_tokens: tokens(),
+        ..ChunkOptions::default()
+    };
+    assert_eq!(types(&chunk(&at_limit)), ["file"]);
+
+    let over_limit = ChunkOptions {
+        max_chunk_tokens: tokens() - 1,
+        ..at_limit
+    };
+    assert!(!types(&chunk(&over_limit)).contains(&"file"));
+}
+
+// The file chunk holds all its items, so a file under --min-tokens is dropped whole,
+// as every one of its items would be
+#[test]
+fn min_tokens_judges_the_file_chunk() {
+    let kept = ChunkOptions {
+        small_file_threshold: 10_000,
+        min_tokens: tokens(),
+        ..ChunkOptions::default()
+    };
+    assert_eq!(types(&chunk(&kept)), ["file"]);
+    assert_eq!(
+        kept.token_filtered.parts_under_min.load(Ordering::Relaxed),
+        0
+    );
+
+    let dropped = ChunkOptions {
+        small_file_threshold: 10_000,
+        min_tokens: tokens() + 1,
+        ..ChunkOptions::default()
+    };
+    assert!(chunk(&dropped).is_empty());
+    assert_eq!(
+        dropped
+            .token_filtered
+            .parts_under_min
+            .load(Ordering::Relaxed),
+        1
+    );
+}
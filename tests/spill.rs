@@ -0,0 +1,81 @@
+#![cfg(all(feature = "cli", feature = "lang-rust"))]
+
+// `diff` and `lint` give the same output when their ids spill to temp files as when
+// they stay in memory. A 4K memory limit forces the spill on chunks of our own sources
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+const SPILLED: &str = "spilling keys";
+
+fn chunker(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_smart-code-chunker"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+// Chunks of two of our larger sources under two token limits, so split items differ
+// between them
+fn outputs(name: &str) -> (PathBuf, PathBuf, PathBuf) {
+    let dir = common::temp_dir(name);
+    let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+    let sources = [src.join("files.rs"), src.join("lang_driver.rs")];
+    let (old, new) = (dir.join("old.jsonl"), dir.join("new.jsonl"));
+    for (output, limit) in [(&old, "800"), (&new, "300")] {
+        let args = [
+            "--no-progress",
+            "--path",
+            sources[0].to_str().unwrap(),
+            sources[1].to_str().unwrap(),
+            "-m",
+            limit,
+            "-o",
+            output.to_str().unwrap(),
+        ];
+        assert!(chunker(&dir, &args).status.success());
+    }
+    (dir, old, new)
+}
+
+#[test]
+fn diff_spilled_matches_in_memory() {
+    let (dir, old, new) = outputs("diff");
+    let (old, new) = (old.to_str().unwrap(), new.to_str().unwrap());
+    let in_memory = chunker(&dir, &["diff", old, new, "--list"]);
+    let spilled = chunker(&dir, &["diff", old, new, "--list", "--max-memory", "4K"]);
+    assert!(in_memory.status.success() && spilled.status.success());
+    assert!(!String::from_utf8_lossy(&in_memory.stderr).contains(SPILLED));
+    assert!(String::from_utf8_lossy(&spilled.stderr).contains(SPILLED));
+    assert!(String::from_utf8_lossy(&spilled.stdout).contains("Changed: "));
+    assert_eq!(spilled.stdout, in_memory.stdout);
+}
+
+#[test]
+fn lint_spilled_matches_in_memory() {
+    let (dir, old, _) = outputs("lint");
+    // Every tenth record twice, so there are duplicate ids to report
+    let records = fs::read_to_string(&old).unwrap();
+    let mut doubled = String::new();
+    for (index, line) in records.lines().enumerate() {
+        doubled.push_str(line);
+        doubled.push('\n');
+        if index % 10 == 0 {
+            doubled.push_str(line);
+            doubled.push('\n');
+        }
+    }
+    let input = dir.join("doubled.jsonl");
+    fs::write(&input, doubled).unwrap();
+
+    let input = input.to_str().unwrap();
+    let in_memory = chunker(&dir, &["lint", input]);
+    let spilled = chunker(&dir, &["lint", input, "--max-memory", "4K"]);
+    assert!(!in_memory.status.success() && !spilled.status.success());
+    assert!(String::from_utf8_lossy(&spilled.stderr).contains(SPILLED));
+    assert!(String::from_utf8_lossy(&spilled.stdout).contains("duplicate id: "));
+    assert_eq!(spilled.stdout, in_memory.stdout);
+}
@@ -0,0 +1,69 @@
+#![cfg(feature = "lang-python")]
+
+// A file rewritten while a worker thread chunks it. The tokenizer is the hook: the
+// first time it counts, the worker waits until the test thread has changed the file
+mod common;
+
+use smart_code_chunker::files::Tokenizer;
+use smart_code_chunker::{ChunkData, ChunkOptions};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+const BEFORE: &str = "def before():\n    return 1\n";
+const AFTER: &str = "def after_edit():\n    return 2 + 2\n";
+
+fn fixture(name: &str) -> PathBuf {
+    let path = common::temp_dir("stale").join(name);
+    fs::write(&path, BEFORE).unwrap();
+    path
+}
+
+// Chunks `path` on another thread and rewrites it with AFTER while that thread is
+// in the middle of chunking
+fn chunk_while_rewriting(path: &Path, stale_retries: usize) -> Vec<ChunkData> {
+    let (reached, wait_reached) = mpsc::channel();
+    let (resume, wait_resume) = mpsc::channel::<()>();
+    let hook = Mutex::new(Some((reached, wait_resume)));
+    let tokenizer = Tokenizer::Custom(Arc::new(move |text: &str| {
+        if let Some((reached, wait_resume)) = hook.lock().unwrap().take() {
+            reached.send(()).unwrap();
+            wait_resume.recv().unwrap();
+        }
+        text.len().div_ceil(4)
+    }));
+    let options = ChunkOptions {
+        small_file_threshold: 0,
+        stale_retries,
+        tokenizer,
+        ..ChunkOptions::default()
+    };
+
+    let worker_path = path.to_path_buf();
+    let worker = thread::spawn(move || common::chunk_path(&worker_path, &options));
+    wait_reached.recv().unwrap();
+    fs::write(path, AFTER).unwrap();
+    resume.send(()).unwrap();
+    worker.join().unwrap()
+}
+
+#[test]
+fn retry_emits_the_final_content() {
+    let path = fixture("retried.py");
+    let chunks = chunk_while_rewriting(&path, 2);
+    assert_eq!(chunks.len(), 1);
+    assert_eq!(chunks[0].chunk_name, "after_edit");
+    assert_eq!(chunks[0].code.trim(), AFTER.trim());
+    assert!(!chunks[0].stale);
+}
+
+#[test]
+fn without_retries_the_chunks_are_marked_stale() {
+    let path = fixture("stale.py");
+    let chunks = chunk_while_rewriting(&path, 0);
+    assert_eq!(chunks.len(), 1);
+    assert_eq!(chunks[0].chunk_name, "before");
+    assert!(chunks[0].stale);
+}
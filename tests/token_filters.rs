@@ -0,0 +1,64 @@
+#![cfg(feature = "lang-python")]
+
+// --max-tokens skips items over --max-tokens-factor times it, --min-tokens drops the
+// parts under it
+mod common;
+
+use smart_code_chunker::{ChunkData, ChunkOptions};
+use std::sync::atomic::Ordering;
+
+fn source() -> String {
+    let mut source = "def tiny():\n    pass\n\n\ndef table():\n    return [\n".to_string();
+    for row in 0..60 {
+        source.push_str(&format!("        ({}, \"row number {}\", {}),\n", row, row, row * row));
+    }
+    source.push_str("    ]\n");
+    source
+}
+
+fn chunk(name: &str, options: &ChunkOptions) -> Vec<ChunkData> {
+    common::chunk_source(name, &source(), options)
+}
+
+fn names(chunks: &[ChunkData]) -> Vec<&str> {
+    chunks.iter().map(|chunk| chunk.chunk_name.as_str()).collect()
+}
+
+#[test]
+fn items_over_the_factor_are_skipped() {
+    let options = ChunkOptions {
+        small_file_threshold: 0,
+        max_tokens: Some(100),
+        max_tokens_factor: 3,
+        ..ChunkOptions::default()
+    };
+    assert_eq!(names(&chunk("skipped.py", &options)), ["tiny"]);
+    assert_eq!(options.token_filtered.items_over_max.load(Ordering::Relaxed), 1);
+}
+
+// The table is far over ten times the chunk limit, and still split by default
+#[test]
+fn without_max_tokens_items_are_split() {
+    let options = ChunkOptions {
+        small_file_threshold: 0,
+        max_chunk_tokens: 50,
+        ..ChunkOptions::default()
+    };
+    let chunks = chunk("split.py", &options);
+    assert_eq!(chunks[0].chunk_name, "tiny");
+    assert!(chunks.len() > 10);
+    assert!(chunks[1..].iter().all(|chunk| chunk.chunk_name == "table" && chunk.token_count <= 50));
+    assert_eq!(options.token_filtered.items_over_max.load(Ordering::Relaxed), 0);
+}
+
+#[test]
+fn min_tokens_drops_small_chunks() {
+    let options = ChunkOptions {
+        small_file_threshold: 0,
+        max_chunk_tokens: 2000,
+        min_tokens: 10,
+        ..ChunkOptions::default()
+    };
+    assert_eq!(names(&chunk("min.py", &options)), ["table"]);
+    assert_eq!(options.token_filtered.parts_under_min.load(Ordering::Relaxed), 1);
+}
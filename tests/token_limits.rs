@@ -0,0 +1,189 @@
+#![cfg(all(feature = "cli", feature = "lang-python", feature = "lang-rust"))]
+
+// Which limit every file is chunked with, given the command line and chunker.toml
+mod common;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+fn workspace(name: &str, config: &str) -> PathBuf {
+    let dir = common::temp_dir(name);
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::write(dir.join("chunker.toml"), config).unwrap();
+    fs::write(dir.join("src/app.py"), "def main():\n    return 0\n").unwrap();
+    fs::write(dir.join("src/lib.rs"), "pub fn main() -> i32 {\n    0\n}\n").unwrap();
+    fs::create_dir_all(dir.join("src/billing")).unwrap();
+    fs::write(
+        dir.join("src/billing/pyproject.toml"),
+        "[project]\nname = \"billing\"\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.join("src/billing/charge.py"),
+        "def charge():\n    return 0\n",
+    )
+    .unwrap();
+    dir
+}
+
+// max_tokens_applied of every chunk, by file
+fn applied_limits(dir: &PathBuf, args: &[&str]) -> BTreeMap<String, u64> {
+    let output = Command::new(env!("CARGO_BIN_EXE_smart-code-chunker"))
+        .current_dir(dir)
+        .args(["--no-progress", "-o", "out.jsonl", "--path", "src"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    fs::read_to_string(dir.join("out.jsonl"))
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let chunk: serde_json::Value = serde_json::from_str(line).unwrap();
+            let path = chunk["file_path"].as_str().unwrap().to_string();
+            (path, chunk["max_tokens_applied"].as_u64().unwrap())
+        })
+        .collect()
+}
+
+// Limits of src/app.py, src/lib.rs and the billing project's src/billing/charge.py
+fn limits(app: u64, lib: u64, billing: u64) -> BTreeMap<String, u64> {
+    [
+        ("src/app.py", app),
+        ("src/lib.rs", lib),
+        ("src/billing/charge.py", billing),
+    ]
+    .into_iter()
+    .map(|(path, limit)| (path.to_string(), limit))
+    .collect()
+}
+
+#[test]
+fn language_table_applies_to_its_files_only() {
+    let dir = workspace(
+        "language_table",
+        "max_chunk_tokens = 200\n\n[language_settings.python]\nmax_chunk_tokens = 1024\n",
+    );
+    assert_eq!(applied_limits(&dir, &[]), limits(1024, 200, 1024));
+}
+
+#[test]
+fn flag_replaces_language_tables() {
+    let dir = workspace(
+        "chunk_limit_flag",
+        "[language_settings.python]\nmax_chunk_tokens = 1024\n",
+    );
+    assert_eq!(
+        applied_limits(&dir, &["--max-chunk-tokens", "300"]),
+        limits(300, 300, 300)
+    );
+    assert_eq!(
+        applied_limits(&dir, &["--max-chunk-tokens", "rust=300"]),
+        limits(1024, 300, 1024)
+    );
+}
+
+#[test]
+fn project_limit_beats_top_level_limit() {
+    let dir = workspace(
+        "project_over_top_level",
+        "max_chunk_tokens = 200\n\n[project.\"billing\"]\nmax_chunk_tokens = 512\n",
+    );
+    assert_eq!(applied_limits(&dir, &[]), limits(200, 200, 512));
+}
+
+#[test]
+fn project_limit_beats_language_table() {
+    let dir = workspace(
+        "project_over_language",
+        "[language_settings.python]\nmax_chunk_tokens = 1024\n\n[project.\"billing\"]\nmax_chunk_tokens = 512\n",
+    );
+    assert_eq!(applied_limits(&dir, &[]), limits(1024, 800, 512));
+}
+
+#[test]
+fn flag_beats_project_limit() {
+    let dir = workspace(
+        "flag_over_project",
+        "max_chunk_tokens = 200\n\n[project.\"billing\"]\nmax_chunk_tokens = 512\n",
+    );
+    assert_eq!(applied_limits(&dir, &["-m", "300"]), limits(300, 300, 300));
+    assert_eq!(
+        applied_limits(&dir, &["-m", "python=300"]),
+        limits(300, 200, 300)
+    );
+}
+
+#[test]
+fn config_limit_caps_every_chunk() {
+    let dir = workspace("config_limit", "max_chunk_tokens = 256\n");
+    let mut source = "def table():\n    return [\n".to_string();
+    for row in 0..150 {
+        source.push_str(&format!("        ({}, \"row number {}\"),\n", row, row));
+    }
+    source.push_str("    ]\n");
+    fs::write(dir.join("src/table.py"), source).unwrap();
+    applied_limits(&dir, &[]);
+
+    let chunks: Vec<serde_json::Value> = fs::read_to_string(dir.join("out.jsonl"))
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let table: Vec<_> = chunks
+        .iter()
+        .filter(|chunk| chunk["file_path"] == "src/table.py")
+        .collect();
+    assert!(table.len() > 1);
+    for chunk in chunks {
+        assert_eq!(chunk["max_tokens_applied"], 256);
+        assert!(chunk["token_count"].as_u64().unwrap() <= 256, "{}", chunk);
+    }
+}
+
+#[test]
+fn unknown_key_in_language_table_is_named() {
+    let dir = workspace(
+        "unknown_key",
+        "[language_settings.python]\nmax_chunk_token = 1024\n",
+    );
+    let output = Command::new(env!("CARGO_BIN_EXE_smart-code-chunker"))
+        .current_dir(&dir)
+        .args(["--no-progress", "--path", "src"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown field `max_chunk_token`"));
+}
+
+// A language's settings and the languages allowlist are separate keys, so both apply
+#[test]
+fn language_settings_work_with_an_allowlist() {
+    let dir = workspace(
+        "settings_and_allowlist",
+        "languages = [\"python\"]\n\n[language_settings.python]\nmax_chunk_tokens = 1024\n",
+    );
+    let mut expected = limits(1024, 0, 1024);
+    expected.remove("src/lib.rs");
+    assert_eq!(applied_limits(&dir, &[]), expected);
+}
+
+#[test]
+fn max_tokens_key_is_rejected() {
+    let dir = workspace("max_tokens_key", "max_tokens = 1024\n");
+    let output = Command::new(env!("CARGO_BIN_EXE_smart-code-chunker"))
+        .current_dir(&dir)
+        .args(["--no-progress", "--path", "src"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("`max_chunk_tokens`, not `max_tokens`")
+    );
+}